@@ -21,6 +21,7 @@ Register-ArgumentCompleter -Native -CommandName 'clyde' -ScriptBlock {
 
     $completions = @(switch ($command) {
         'clyde' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('-V', '-V ', [CompletionResultType]::ParameterName, 'Print version')
@@ -28,41 +29,72 @@ Register-ArgumentCompleter -Native -CommandName 'clyde' -ScriptBlock {
             [CompletionResult]::new('setup', 'setup', [CompletionResultType]::ParameterValue, 'Setup Clyde')
             [CompletionResult]::new('update', 'update', [CompletionResultType]::ParameterValue, 'Update Clyde store')
             [CompletionResult]::new('install', 'install', [CompletionResultType]::ParameterValue, 'Install applications')
+            [CompletionResult]::new('fetch', 'fetch', [CompletionResultType]::ParameterValue, 'Download the assets of applications without installing them, and print their paths')
             [CompletionResult]::new('uninstall', 'uninstall', [CompletionResultType]::ParameterValue, 'Uninstall applications (alias: remove)')
             [CompletionResult]::new('show', 'show', [CompletionResultType]::ParameterValue, 'Show details about an application')
             [CompletionResult]::new('search', 'search', [CompletionResultType]::ParameterValue, 'Search for available applications')
             [CompletionResult]::new('doc', 'doc', [CompletionResultType]::ParameterValue, 'Read documentation files provided by an application')
             [CompletionResult]::new('list', 'list', [CompletionResultType]::ParameterValue, 'List installed applications')
-            [CompletionResult]::new('upgrade', 'upgrade', [CompletionResultType]::ParameterValue, 'Upgrade all installed applications, enforcing pinning')
+            [CompletionResult]::new('upgrade', 'upgrade', [CompletionResultType]::ParameterValue, 'Upgrade installed applications, enforcing pinning')
+            [CompletionResult]::new('outdated', 'outdated', [CompletionResultType]::ParameterValue, 'List installed applications which can be upgraded')
+            [CompletionResult]::new('pin', 'pin', [CompletionResultType]::ParameterValue, 'Pin an installed application, so that `upgrade` only installs matching versions')
+            [CompletionResult]::new('unpin', 'unpin', [CompletionResultType]::ParameterValue, 'Remove the pin of installed applications')
+            [CompletionResult]::new('downgrade', 'downgrade', [CompletionResultType]::ParameterValue, 'Downgrade an application to the version preceding the installed one, and pin it')
+            [CompletionResult]::new('env', 'env', [CompletionResultType]::ParameterValue, 'Print the environment variables defined by installed applications, as shell commands')
+            [CompletionResult]::new('shell-init', 'shell-init', [CompletionResultType]::ParameterValue, 'Print the activation script for a shell')
+            [CompletionResult]::new('doctor', 'doctor', [CompletionResultType]::ParameterValue, 'Check the health of the Clyde installation')
+            [CompletionResult]::new('owns', 'owns', [CompletionResultType]::ParameterValue, 'Show the installed application owning a file or a command')
+            [CompletionResult]::new('provides', 'provides', [CompletionResultType]::ParameterValue, 'List the applications providing a command')
             [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
             break
         }
         'clyde;setup' {
             [CompletionResult]::new('--url', '--url', [CompletionResultType]::ParameterName, 'URL of the Git repository to use for the store')
-            [CompletionResult]::new('-u', '-u', [CompletionResultType]::ParameterName, 'Update the activation scripts of an existing installation')
-            [CompletionResult]::new('--update-scripts', '--update-scripts', [CompletionResultType]::ParameterName, 'Update the activation scripts of an existing installation')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('-u', '-u', [CompletionResultType]::ParameterName, 'Update the activation scripts of an existing installation, for all supported shells')
+            [CompletionResult]::new('--update-scripts', '--update-scripts', [CompletionResultType]::ParameterName, 'Update the activation scripts of an existing installation, for all supported shells')
+            [CompletionResult]::new('--modify-rc', '--modify-rc', [CompletionResultType]::ParameterName, 'Add a block loading the activation script to the startup scripts of the shells you use. Can be run again on an existing installation')
+            [CompletionResult]::new('--unmodify-rc', '--unmodify-rc', [CompletionResultType]::ParameterName, 'Remove the block added by `--modify-rc` from the startup scripts of all shells')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
         }
         'clyde;update' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
         }
         'clyde;install' {
+            [CompletionResult]::new('--arch-os', '--arch-os', [CompletionResultType]::ParameterName, 'Install applications for another arch-os, like `aarch64-linux`, in a separate Clyde home. Defaults to $CLYDE_ARCH_OS if set')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
             [CompletionResult]::new('-r', '-r', [CompletionResultType]::ParameterName, 'Uninstall then reinstall already installed packages')
             [CompletionResult]::new('--reinstall', '--reinstall', [CompletionResultType]::ParameterName, 'Uninstall then reinstall already installed packages')
+            [CompletionResult]::new('--pre', '--pre', [CompletionResultType]::ParameterName, 'Allow installing pre-releases. Upgrades of the applications will also consider pre-releases')
+            [CompletionResult]::new('--no-pre', '--no-pre', [CompletionResultType]::ParameterName, 'Stop considering pre-releases for already installed applications. Without `--pre` or `--no-pre`, installed applications keep their setting')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
             break
         }
+        'clyde;fetch' {
+            [CompletionResult]::new('--arch-os', '--arch-os', [CompletionResultType]::ParameterName, 'Fetch assets for another arch-os, like `aarch64-linux`. Defaults to $CLYDE_ARCH_OS if set')
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Copy the assets to this directory')
+            [CompletionResult]::new('--output-dir', '--output-dir', [CompletionResultType]::ParameterName, 'Copy the assets to this directory')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('--pre', '--pre', [CompletionResultType]::ParameterName, 'Allow fetching pre-releases')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
         'clyde;uninstall' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
         }
         'clyde;show' {
+            [CompletionResult]::new('--arch-os', '--arch-os', [CompletionResultType]::ParameterName, 'Show the installed version and available assets for another arch-os. Defaults to $CLYDE_ARCH_OS if set')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
             [CompletionResult]::new('-l', '-l', [CompletionResultType]::ParameterName, 'List application files instead of showing information')
             [CompletionResult]::new('--list', '--list', [CompletionResultType]::ParameterName, 'List application files instead of showing information')
             [CompletionResult]::new('-j', '-j', [CompletionResultType]::ParameterName, 'Use JSON output')
@@ -72,16 +104,19 @@ Register-ArgumentCompleter -Native -CommandName 'clyde' -ScriptBlock {
             break
         }
         'clyde;search' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
         }
         'clyde;doc' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
         }
         'clyde;list' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
             [CompletionResult]::new('-j', '-j', [CompletionResultType]::ParameterName, 'Use JSON output')
             [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Use JSON output')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
@@ -89,6 +124,68 @@ Register-ArgumentCompleter -Native -CommandName 'clyde' -ScriptBlock {
             break
         }
         'clyde;upgrade' {
+            [CompletionResult]::new('-x', '-x', [CompletionResultType]::ParameterName, 'Do not upgrade this application. Can be used multiple times')
+            [CompletionResult]::new('--exclude', '--exclude', [CompletionResultType]::ParameterName, 'Do not upgrade this application. Can be used multiple times')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('--ignore-pins', '--ignore-pins', [CompletionResultType]::ParameterName, 'Upgrade pinned applications to their latest version, removing their pin')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
+        'clyde;outdated' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('-j', '-j', [CompletionResultType]::ParameterName, 'Use JSON output')
+            [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Use JSON output')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'clyde;pin' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
+        'clyde;unpin' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
+        'clyde;downgrade' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
+        'clyde;env' {
+            [CompletionResult]::new('--shell', '--shell', [CompletionResultType]::ParameterName, 'Shell to print the commands for')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
+        'clyde;shell-init' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'clyde;doctor' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('--fix', '--fix', [CompletionResultType]::ParameterName, 'Fix the problems which can be fixed')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'clyde;owns' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
+        'clyde;provides' {
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output format. The `json` format prints a stream of JSON events on stdout, one per line')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
@@ -97,12 +194,22 @@ Register-ArgumentCompleter -Native -CommandName 'clyde' -ScriptBlock {
             [CompletionResult]::new('setup', 'setup', [CompletionResultType]::ParameterValue, 'Setup Clyde')
             [CompletionResult]::new('update', 'update', [CompletionResultType]::ParameterValue, 'Update Clyde store')
             [CompletionResult]::new('install', 'install', [CompletionResultType]::ParameterValue, 'Install applications')
+            [CompletionResult]::new('fetch', 'fetch', [CompletionResultType]::ParameterValue, 'Download the assets of applications without installing them, and print their paths')
             [CompletionResult]::new('uninstall', 'uninstall', [CompletionResultType]::ParameterValue, 'Uninstall applications (alias: remove)')
             [CompletionResult]::new('show', 'show', [CompletionResultType]::ParameterValue, 'Show details about an application')
             [CompletionResult]::new('search', 'search', [CompletionResultType]::ParameterValue, 'Search for available applications')
             [CompletionResult]::new('doc', 'doc', [CompletionResultType]::ParameterValue, 'Read documentation files provided by an application')
             [CompletionResult]::new('list', 'list', [CompletionResultType]::ParameterValue, 'List installed applications')
-            [CompletionResult]::new('upgrade', 'upgrade', [CompletionResultType]::ParameterValue, 'Upgrade all installed applications, enforcing pinning')
+            [CompletionResult]::new('upgrade', 'upgrade', [CompletionResultType]::ParameterValue, 'Upgrade installed applications, enforcing pinning')
+            [CompletionResult]::new('outdated', 'outdated', [CompletionResultType]::ParameterValue, 'List installed applications which can be upgraded')
+            [CompletionResult]::new('pin', 'pin', [CompletionResultType]::ParameterValue, 'Pin an installed application, so that `upgrade` only installs matching versions')
+            [CompletionResult]::new('unpin', 'unpin', [CompletionResultType]::ParameterValue, 'Remove the pin of installed applications')
+            [CompletionResult]::new('downgrade', 'downgrade', [CompletionResultType]::ParameterValue, 'Downgrade an application to the version preceding the installed one, and pin it')
+            [CompletionResult]::new('env', 'env', [CompletionResultType]::ParameterValue, 'Print the environment variables defined by installed applications, as shell commands')
+            [CompletionResult]::new('shell-init', 'shell-init', [CompletionResultType]::ParameterValue, 'Print the activation script for a shell')
+            [CompletionResult]::new('doctor', 'doctor', [CompletionResultType]::ParameterValue, 'Check the health of the Clyde installation')
+            [CompletionResult]::new('owns', 'owns', [CompletionResultType]::ParameterValue, 'Show the installed application owning a file or a command')
+            [CompletionResult]::new('provides', 'provides', [CompletionResultType]::ParameterValue, 'List the applications providing a command')
             [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
             break
         }
@@ -115,6 +222,9 @@ Register-ArgumentCompleter -Native -CommandName 'clyde' -ScriptBlock {
         'clyde;help;install' {
             break
         }
+        'clyde;help;fetch' {
+            break
+        }
         'clyde;help;uninstall' {
             break
         }
@@ -133,6 +243,33 @@ Register-ArgumentCompleter -Native -CommandName 'clyde' -ScriptBlock {
         'clyde;help;upgrade' {
             break
         }
+        'clyde;help;outdated' {
+            break
+        }
+        'clyde;help;pin' {
+            break
+        }
+        'clyde;help;unpin' {
+            break
+        }
+        'clyde;help;downgrade' {
+            break
+        }
+        'clyde;help;env' {
+            break
+        }
+        'clyde;help;shell-init' {
+            break
+        }
+        'clyde;help;doctor' {
+            break
+        }
+        'clyde;help;owns' {
+            break
+        }
+        'clyde;help;provides' {
+            break
+        }
         'clyde;help;help' {
             break
         }
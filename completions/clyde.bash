@@ -19,6 +19,18 @@ _clyde() {
             clyde,doc)
                 cmd="clyde__subcmd__doc"
                 ;;
+            clyde,doctor)
+                cmd="clyde__subcmd__doctor"
+                ;;
+            clyde,downgrade)
+                cmd="clyde__subcmd__downgrade"
+                ;;
+            clyde,env)
+                cmd="clyde__subcmd__env"
+                ;;
+            clyde,fetch)
+                cmd="clyde__subcmd__fetch"
+                ;;
             clyde,help)
                 cmd="clyde__subcmd__help"
                 ;;
@@ -28,18 +40,36 @@ _clyde() {
             clyde,list)
                 cmd="clyde__subcmd__list"
                 ;;
+            clyde,outdated)
+                cmd="clyde__subcmd__outdated"
+                ;;
+            clyde,owns)
+                cmd="clyde__subcmd__owns"
+                ;;
+            clyde,pin)
+                cmd="clyde__subcmd__pin"
+                ;;
+            clyde,provides)
+                cmd="clyde__subcmd__provides"
+                ;;
             clyde,search)
                 cmd="clyde__subcmd__search"
                 ;;
             clyde,setup)
                 cmd="clyde__subcmd__setup"
                 ;;
+            clyde,shell-init)
+                cmd="clyde__subcmd__shell__subcmd__init"
+                ;;
             clyde,show)
                 cmd="clyde__subcmd__show"
                 ;;
             clyde,uninstall)
                 cmd="clyde__subcmd__uninstall"
                 ;;
+            clyde,unpin)
+                cmd="clyde__subcmd__unpin"
+                ;;
             clyde,update)
                 cmd="clyde__subcmd__update"
                 ;;
@@ -49,6 +79,18 @@ _clyde() {
             clyde__subcmd__help,doc)
                 cmd="clyde__subcmd__help__subcmd__doc"
                 ;;
+            clyde__subcmd__help,doctor)
+                cmd="clyde__subcmd__help__subcmd__doctor"
+                ;;
+            clyde__subcmd__help,downgrade)
+                cmd="clyde__subcmd__help__subcmd__downgrade"
+                ;;
+            clyde__subcmd__help,env)
+                cmd="clyde__subcmd__help__subcmd__env"
+                ;;
+            clyde__subcmd__help,fetch)
+                cmd="clyde__subcmd__help__subcmd__fetch"
+                ;;
             clyde__subcmd__help,help)
                 cmd="clyde__subcmd__help__subcmd__help"
                 ;;
@@ -58,18 +100,36 @@ _clyde() {
             clyde__subcmd__help,list)
                 cmd="clyde__subcmd__help__subcmd__list"
                 ;;
+            clyde__subcmd__help,outdated)
+                cmd="clyde__subcmd__help__subcmd__outdated"
+                ;;
+            clyde__subcmd__help,owns)
+                cmd="clyde__subcmd__help__subcmd__owns"
+                ;;
+            clyde__subcmd__help,pin)
+                cmd="clyde__subcmd__help__subcmd__pin"
+                ;;
+            clyde__subcmd__help,provides)
+                cmd="clyde__subcmd__help__subcmd__provides"
+                ;;
             clyde__subcmd__help,search)
                 cmd="clyde__subcmd__help__subcmd__search"
                 ;;
             clyde__subcmd__help,setup)
                 cmd="clyde__subcmd__help__subcmd__setup"
                 ;;
+            clyde__subcmd__help,shell-init)
+                cmd="clyde__subcmd__help__subcmd__shell__subcmd__init"
+                ;;
             clyde__subcmd__help,show)
                 cmd="clyde__subcmd__help__subcmd__show"
                 ;;
             clyde__subcmd__help,uninstall)
                 cmd="clyde__subcmd__help__subcmd__uninstall"
                 ;;
+            clyde__subcmd__help,unpin)
+                cmd="clyde__subcmd__help__subcmd__unpin"
+                ;;
             clyde__subcmd__help,update)
                 cmd="clyde__subcmd__help__subcmd__update"
                 ;;
@@ -83,12 +143,16 @@ _clyde() {
 
     case "${cmd}" in
         clyde)
-            opts="-h -V --help --version setup update install uninstall show search doc list upgrade help"
+            opts="-h -V --output --help --version setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 1 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -97,12 +161,104 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__doc)
-            opts="-h --help <PACKAGE_NAME>"
+            opts="-h --output --help <PACKAGE_NAME>"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__doctor)
+            opts="-h --fix --output --help"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__downgrade)
+            opts="-h --output --help <APPLICATION_NAME>"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__env)
+            opts="-h --shell --output --help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --shell)
+                    COMPREPLY=($(compgen -W "sh bash zsh fish nu powershell elvish" -- "${cur}"))
+                    return 0
+                    ;;
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__fetch)
+            opts="-o -h --pre --arch-os --output-dir --output --help <APPLICATION_NAME>..."
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --arch-os)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --output-dir)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -o)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -111,7 +267,7 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__help)
-            opts="setup update install uninstall show search doc list upgrade help"
+            opts="setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -138,6 +294,62 @@ _clyde() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        clyde__subcmd__help__subcmd__doctor)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__help__subcmd__downgrade)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__help__subcmd__env)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__help__subcmd__fetch)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         clyde__subcmd__help__subcmd__help)
             opts=""
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
@@ -180,6 +392,62 @@ _clyde() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        clyde__subcmd__help__subcmd__outdated)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__help__subcmd__owns)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__help__subcmd__pin)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__help__subcmd__provides)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         clyde__subcmd__help__subcmd__search)
             opts=""
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
@@ -208,6 +476,20 @@ _clyde() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        clyde__subcmd__help__subcmd__shell__subcmd__init)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         clyde__subcmd__help__subcmd__show)
             opts=""
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
@@ -236,6 +518,20 @@ _clyde() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        clyde__subcmd__help__subcmd__unpin)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         clyde__subcmd__help__subcmd__update)
             opts=""
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
@@ -265,12 +561,20 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__install)
-            opts="-r -h --reinstall --help <APPLICATION_NAME>..."
+            opts="-r -h --reinstall --pre --no-pre --arch-os --output --help <APPLICATION_NAME>..."
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --arch-os)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -279,12 +583,88 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__list)
-            opts="-j -h --json --help"
+            opts="-j -h --json --output --help"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__outdated)
+            opts="-j -h --json --output --help"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__owns)
+            opts="-h --output --help <PATH_OR_COMMAND>"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__pin)
+            opts="-h --output --help <APPLICATION_NAME>"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__provides)
+            opts="-h --output --help <COMMAND>"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -293,12 +673,16 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__search)
-            opts="-h --help <QUERY>"
+            opts="-h --output --help <QUERY>"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -307,7 +691,7 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__setup)
-            opts="-u -h --update-scripts --url --help"
+            opts="-u -h --update-scripts --modify-rc --unmodify-rc --url --output --help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -317,6 +701,28 @@ _clyde() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__shell__subcmd__init)
+            opts="-h --output --help sh bash zsh fish nu powershell elvish"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -325,12 +731,20 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__show)
-            opts="-l -j -h --list --json --help <PACKAGE_NAME>"
+            opts="-l -j -h --list --json --arch-os --output --help <PACKAGE_NAME>"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --arch-os)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -339,12 +753,34 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__uninstall)
-            opts="-h --help <APPLICATION_NAME>..."
+            opts="-h --output --help <APPLICATION_NAME>..."
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        clyde__subcmd__unpin)
+            opts="-h --output --help <APPLICATION_NAME>..."
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -353,12 +789,16 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__update)
-            opts="-h --help"
+            opts="-h --output --help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -367,12 +807,24 @@ _clyde() {
             return 0
             ;;
         clyde__subcmd__upgrade)
-            opts="-h --help"
+            opts="-x -h --exclude --ignore-pins --output --help [APPLICATION_NAME]..."
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --exclude)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -x)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --output)
+                    COMPREPLY=($(compgen -W "text json" -- "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -1,6 +1,6 @@
 # Print an optspec for argparse to handle cmd's options that are independent of any subcommand.
 function __fish_clyde_global_optspecs
-	string join \n h/help V/version
+	string join \n output= h/help V/version
 end
 
 function __fish_clyde_needs_command
@@ -24,40 +24,130 @@ function __fish_clyde_using_subcommand
 	contains -- $cmd[1] $argv
 end
 
+complete -c clyde -n "__fish_clyde_needs_command" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
 complete -c clyde -n "__fish_clyde_needs_command" -s h -l help -d 'Print help'
 complete -c clyde -n "__fish_clyde_needs_command" -s V -l version -d 'Print version'
 complete -c clyde -n "__fish_clyde_needs_command" -f -a "setup" -d 'Setup Clyde'
 complete -c clyde -n "__fish_clyde_needs_command" -f -a "update" -d 'Update Clyde store'
 complete -c clyde -n "__fish_clyde_needs_command" -f -a "install" -d 'Install applications'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "fetch" -d 'Download the assets of applications without installing them, and print their paths'
 complete -c clyde -n "__fish_clyde_needs_command" -f -a "uninstall" -d 'Uninstall applications (alias: remove)'
 complete -c clyde -n "__fish_clyde_needs_command" -f -a "show" -d 'Show details about an application'
 complete -c clyde -n "__fish_clyde_needs_command" -f -a "search" -d 'Search for available applications'
 complete -c clyde -n "__fish_clyde_needs_command" -f -a "doc" -d 'Read documentation files provided by an application'
 complete -c clyde -n "__fish_clyde_needs_command" -f -a "list" -d 'List installed applications'
-complete -c clyde -n "__fish_clyde_needs_command" -f -a "upgrade" -d 'Upgrade all installed applications, enforcing pinning'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "upgrade" -d 'Upgrade installed applications, enforcing pinning'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "outdated" -d 'List installed applications which can be upgraded'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "pin" -d 'Pin an installed application, so that `upgrade` only installs matching versions'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "unpin" -d 'Remove the pin of installed applications'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "downgrade" -d 'Downgrade an application to the version preceding the installed one, and pin it'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "env" -d 'Print the environment variables defined by installed applications, as shell commands'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "shell-init" -d 'Print the activation script for a shell'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "doctor" -d 'Check the health of the Clyde installation'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "owns" -d 'Show the installed application owning a file or a command'
+complete -c clyde -n "__fish_clyde_needs_command" -f -a "provides" -d 'List the applications providing a command'
 complete -c clyde -n "__fish_clyde_needs_command" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
 complete -c clyde -n "__fish_clyde_using_subcommand setup" -l url -d 'URL of the Git repository to use for the store' -r
-complete -c clyde -n "__fish_clyde_using_subcommand setup" -s u -l update-scripts -d 'Update the activation scripts of an existing installation'
+complete -c clyde -n "__fish_clyde_using_subcommand setup" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand setup" -s u -l update-scripts -d 'Update the activation scripts of an existing installation, for all supported shells'
+complete -c clyde -n "__fish_clyde_using_subcommand setup" -l modify-rc -d 'Add a block loading the activation script to the startup scripts of the shells you use. Can be run again on an existing installation'
+complete -c clyde -n "__fish_clyde_using_subcommand setup" -l unmodify-rc -d 'Remove the block added by `--modify-rc` from the startup scripts of all shells'
 complete -c clyde -n "__fish_clyde_using_subcommand setup" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand update" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
 complete -c clyde -n "__fish_clyde_using_subcommand update" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand install" -l arch-os -d 'Install applications for another arch-os, like `aarch64-linux`, in a separate Clyde home. Defaults to $CLYDE_ARCH_OS if set' -r
+complete -c clyde -n "__fish_clyde_using_subcommand install" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
 complete -c clyde -n "__fish_clyde_using_subcommand install" -s r -l reinstall -d 'Uninstall then reinstall already installed packages'
+complete -c clyde -n "__fish_clyde_using_subcommand install" -l pre -d 'Allow installing pre-releases. Upgrades of the applications will also consider pre-releases'
+complete -c clyde -n "__fish_clyde_using_subcommand install" -l no-pre -d 'Stop considering pre-releases for already installed applications. Without `--pre` or `--no-pre`, installed applications keep their setting'
 complete -c clyde -n "__fish_clyde_using_subcommand install" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c clyde -n "__fish_clyde_using_subcommand fetch" -l arch-os -d 'Fetch assets for another arch-os, like `aarch64-linux`. Defaults to $CLYDE_ARCH_OS if set' -r
+complete -c clyde -n "__fish_clyde_using_subcommand fetch" -s o -l output-dir -d 'Copy the assets to this directory' -r -F
+complete -c clyde -n "__fish_clyde_using_subcommand fetch" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand fetch" -l pre -d 'Allow fetching pre-releases'
+complete -c clyde -n "__fish_clyde_using_subcommand fetch" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand uninstall" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
 complete -c clyde -n "__fish_clyde_using_subcommand uninstall" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand show" -l arch-os -d 'Show the installed version and available assets for another arch-os. Defaults to $CLYDE_ARCH_OS if set' -r
+complete -c clyde -n "__fish_clyde_using_subcommand show" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
 complete -c clyde -n "__fish_clyde_using_subcommand show" -s l -l list -d 'List application files instead of showing information'
 complete -c clyde -n "__fish_clyde_using_subcommand show" -s j -l json -d 'Use JSON output'
 complete -c clyde -n "__fish_clyde_using_subcommand show" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand search" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
 complete -c clyde -n "__fish_clyde_using_subcommand search" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand doc" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
 complete -c clyde -n "__fish_clyde_using_subcommand doc" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand list" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
 complete -c clyde -n "__fish_clyde_using_subcommand list" -s j -l json -d 'Use JSON output'
 complete -c clyde -n "__fish_clyde_using_subcommand list" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand upgrade" -s x -l exclude -d 'Do not upgrade this application. Can be used multiple times' -r
+complete -c clyde -n "__fish_clyde_using_subcommand upgrade" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand upgrade" -l ignore-pins -d 'Upgrade pinned applications to their latest version, removing their pin'
 complete -c clyde -n "__fish_clyde_using_subcommand upgrade" -s h -l help -d 'Print help'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "setup" -d 'Setup Clyde'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "update" -d 'Update Clyde store'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "install" -d 'Install applications'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "uninstall" -d 'Uninstall applications (alias: remove)'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "show" -d 'Show details about an application'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "search" -d 'Search for available applications'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "doc" -d 'Read documentation files provided by an application'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "list" -d 'List installed applications'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "upgrade" -d 'Upgrade all installed applications, enforcing pinning'
-complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install uninstall show search doc list upgrade help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c clyde -n "__fish_clyde_using_subcommand outdated" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand outdated" -s j -l json -d 'Use JSON output'
+complete -c clyde -n "__fish_clyde_using_subcommand outdated" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c clyde -n "__fish_clyde_using_subcommand pin" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand pin" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand unpin" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand unpin" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand downgrade" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand downgrade" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand env" -l shell -d 'Shell to print the commands for' -r -f -a "sh\t''
+bash\t''
+zsh\t''
+fish\t''
+nu\t''
+powershell\t''
+elvish\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand env" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand env" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand shell-init" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand shell-init" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c clyde -n "__fish_clyde_using_subcommand doctor" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand doctor" -l fix -d 'Fix the problems which can be fixed'
+complete -c clyde -n "__fish_clyde_using_subcommand doctor" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c clyde -n "__fish_clyde_using_subcommand owns" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand owns" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand provides" -l output -d 'Output format. The `json` format prints a stream of JSON events on stdout, one per line' -r -f -a "text\t''
+json\t''"
+complete -c clyde -n "__fish_clyde_using_subcommand provides" -s h -l help -d 'Print help'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "setup" -d 'Setup Clyde'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "update" -d 'Update Clyde store'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "install" -d 'Install applications'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "fetch" -d 'Download the assets of applications without installing them, and print their paths'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "uninstall" -d 'Uninstall applications (alias: remove)'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "show" -d 'Show details about an application'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "search" -d 'Search for available applications'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "doc" -d 'Read documentation files provided by an application'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "list" -d 'List installed applications'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "upgrade" -d 'Upgrade installed applications, enforcing pinning'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "outdated" -d 'List installed applications which can be upgraded'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "pin" -d 'Pin an installed application, so that `upgrade` only installs matching versions'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "unpin" -d 'Remove the pin of installed applications'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "downgrade" -d 'Downgrade an application to the version preceding the installed one, and pin it'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "env" -d 'Print the environment variables defined by installed applications, as shell commands'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "shell-init" -d 'Print the activation script for a shell'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "doctor" -d 'Check the health of the Clyde installation'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "owns" -d 'Show the installed application owning a file or a command'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "provides" -d 'List the applications providing a command'
+complete -c clyde -n "__fish_clyde_using_subcommand help; and not __fish_seen_subcommand_from setup update install fetch uninstall show search doc list upgrade outdated pin unpin downgrade env shell-init doctor owns provides help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
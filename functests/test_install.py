@@ -7,7 +7,7 @@ import json
 
 from pathlib import Path
 
-from conftest import get_bin_path, run_clyde, run_in_clyde_home
+from conftest import IS_WINDOWS, get_bin_path, run_clyde, run_in_clyde_home
 
 
 def test_install_pinned_version(clyde_home):
@@ -57,6 +57,24 @@ def test_reinstall_package(clyde_home):
     assert starship_path.exists()
 
 
+def test_install_json_output(clyde_home):
+    # GIVEN a Clyde home
+    # WHEN running `clyde --output json install starship`
+    proc = run_clyde("--output", "json", "install", "starship")
+
+    # THEN stdout contains one JSON event per line
+    events = [json.loads(x) for x in proc.stdout.splitlines()]
+
+    # AND the installed event lists the starship binary
+    installed = [x for x in events if x["event"] == "installed"]
+    assert len(installed) == 1
+    assert installed[0]["package"] == "starship"
+    assert any(Path(x).stem == "starship" for x in installed[0]["files"])
+
+    # AND the last event is a successful result
+    assert events[-1] == {"event": "result", "success": True}
+
+
 def test_install_cleans_after_itself_in_case_of_failure(clyde_home):
     # Get the list of glab files
     #
@@ -114,3 +132,18 @@ def test_install_applies_cooldown(monkeypatch, clyde_home):
     # THEN it installs version 0.44.3
     result = run_in_clyde_home("zellij --version")
     assert "zellij 0.44.3" in result.stdout
+
+
+def test_install_for_another_arch_os(clyde_home):
+    # GIVEN a Clyde home
+    # WHEN installing starship for another arch-os
+    arch_os = "x86_64-linux" if IS_WINDOWS else "x86_64-windows"
+    run_clyde("install", "--arch-os", arch_os, "starship")
+
+    # THEN it is installed in the home of this arch-os
+    arch_os_home = Path(clyde_home) / "arch-os" / arch_os
+    bin_name = "starship" if IS_WINDOWS else "starship.exe"
+    assert (arch_os_home / "inst" / "bin" / bin_name).exists()
+
+    # AND it is not installed for the host
+    assert not get_bin_path("starship").exists()
@@ -13,12 +13,17 @@ use hex;
 use sha2::{digest::DynDigest, Sha256};
 use single_instance::SingleInstance;
 
+use crate::arch_os::{ArchFallbacks, ArchOs, Libc};
 use crate::db::Database;
 use crate::file_cache::FileCache;
 use crate::store::{GitStore, Store};
+use crate::unpacker::UnpackLimits;
 
 const DEFAULT_COOLDOWN_DAYS: usize = 7;
 
+/// Name of the database file, in Clyde home
+pub const DATABASE_FILE_NAME: &str = "clyde.sqlite";
+
 pub struct App {
     pub download_cache: FileCache,
     pub home: PathBuf,
@@ -27,6 +32,9 @@ pub struct App {
     pub store_dir: PathBuf,
     pub store: Box<dyn Store>,
     pub database: Database,
+    /// The arch-os packages are installed for. Defaults to the arch-os of the host
+    pub arch_os: ArchOs,
+    pub unpack_limits: UnpackLimits,
 }
 
 fn create_single_instance_name(home: &Path) -> String {
@@ -82,11 +90,14 @@ impl App {
                 home
             ));
         }
+        // Check CLYDE_ARCH_FALLBACKS now: ArchOs::fallbacks() cannot report errors
+        ArchFallbacks::from_env()?;
+
         let store_dir = home.join("store");
         let mut store = GitStore::new(&store_dir);
         store.set_cooldown_days(read_cooldown_days());
 
-        let db_path = home.join("clyde.sqlite");
+        let db_path = home.join(DATABASE_FILE_NAME);
         let database = Database::new_from_path(&db_path)?;
 
         let download_dir = home.join("download");
@@ -100,8 +111,75 @@ impl App {
             store_dir,
             store: Box::new(store),
             database,
+            arch_os: ArchOs::current(),
+            unpack_limits: UnpackLimits::from_env(),
         })
     }
+
+    /// Returns the arch-os to use, defined by `arch_os` or by the `CLYDE_ARCH_OS` environment
+    /// variable. Returns None to use the arch-os of the host.
+    pub fn read_arch_os(arch_os: Option<&str>) -> Result<Option<ArchOs>> {
+        let arch_os = match arch_os {
+            Some(x) => x.to_string(),
+            None => match env::var("CLYDE_ARCH_OS") {
+                Ok(x) if !x.is_empty() => x,
+                _ => return Ok(None),
+            },
+        };
+        let arch_os =
+            ArchOs::parse(&arch_os).map_err(|err| anyhow!("Invalid arch-os '{arch_os}': {err}"))?;
+        Ok(Some(arch_os))
+    }
+
+    /// Make the app install packages for `arch_os`. If it's not the arch-os of the host, packages
+    /// are installed in a separate home, stored in `<home>/arch-os/<arch-os>`, which has its own
+    /// database. The store and the download cache are shared with the host.
+    pub fn with_arch_os(mut self, arch_os: ArchOs) -> Result<App> {
+        self.arch_os = arch_os;
+        if !self.is_foreign_arch_os() {
+            // `arch_os` may not define a libc: use the one of the host, so that assets built for
+            // another libc are not picked
+            self.arch_os = ArchOs::current();
+            return Ok(self);
+        }
+        let home = App::get_arch_os_home(&self.home, &arch_os);
+        fs::create_dir_all(&home)?;
+
+        let db_path = home.join(DATABASE_FILE_NAME);
+        let db_exists = db_path.exists();
+        self.database = Database::new_from_path(&db_path)?;
+        if !db_exists {
+            self.database.create()?;
+        }
+        self.install_dir = home.join("inst");
+        self.tmp_dir = home.join("tmp");
+        Ok(self)
+    }
+
+    /// Returns the home where packages for `arch_os` are installed: `home` for the arch-os of
+    /// the host, `<home>/arch-os/<arch-os>` for a foreign one
+    pub fn get_arch_os_home(home: &Path, arch_os: &ArchOs) -> PathBuf {
+        if is_foreign_arch_os(arch_os) {
+            home.join("arch-os").join(arch_os.to_str())
+        } else {
+            home.to_path_buf()
+        }
+    }
+
+    /// True if packages are installed for another arch-os than the one of the host. Installed
+    /// binaries can't be run in this case.
+    pub fn is_foreign_arch_os(&self) -> bool {
+        is_foreign_arch_os(&self.arch_os)
+    }
+}
+
+/// True if `arch_os` is not the arch-os of the host. An arch-os without libc uses the libc of the
+/// host.
+fn is_foreign_arch_os(arch_os: &ArchOs) -> bool {
+    let current = ArchOs::current();
+    arch_os.arch != current.arch
+        || arch_os.os != current.os
+        || (arch_os.libc != Libc::Any && arch_os.libc != current.libc)
 }
 
 #[cfg(test)]
@@ -110,6 +188,8 @@ mod tests {
 
     use super::*;
 
+    use crate::arch_os::{Arch, Os};
+
     #[test]
     #[temp_env_vars]
     fn app_use_default_cooldown_days() {
@@ -126,4 +206,90 @@ mod tests {
         let app = App::new(&dir).unwrap();
         assert_eq!(app.store.cooldown_days(), 2);
     }
+
+    #[test]
+    #[temp_env_vars]
+    fn app_env_override_unpack_limits() {
+        env::set_var("CLYDE_UNPACK_MAX_SIZE_MB", "2");
+        env::set_var("CLYDE_UNPACK_MAX_ENTRIES", "100");
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = App::new(&dir).unwrap();
+        assert_eq!(
+            app.unpack_limits,
+            UnpackLimits {
+                max_size: 2 * 1024 * 1024,
+                max_entries: 100
+            }
+        );
+    }
+
+    #[test]
+    #[temp_env_vars]
+    fn with_arch_os_should_use_a_separate_home_for_foreign_arch_os() {
+        // GIVEN an app
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = App::new(&dir).unwrap();
+
+        // WHEN it is set to install packages for another arch-os
+        let current = ArchOs::current();
+        let arch = if current.arch == Arch::S390x {
+            Arch::X86_64
+        } else {
+            Arch::S390x
+        };
+        let arch_os = ArchOs::new(arch, current.os);
+        let app = app.with_arch_os(arch_os).unwrap();
+
+        // THEN it installs them in a separate home
+        assert!(app.is_foreign_arch_os());
+        let home = dir.join("arch-os").join(arch_os.to_str());
+        assert_eq!(app.install_dir, home.join("inst"));
+
+        // AND this home has its own database
+        assert!(app.database.get_installed_packages().unwrap().is_empty());
+        assert!(home.join("clyde.sqlite").exists());
+    }
+
+    #[test]
+    fn is_foreign_arch_os_should_take_libc_into_account() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let current = ArchOs::current();
+
+        // An arch-os without libc uses the libc of the host
+        let app = App::new(&dir)
+            .unwrap()
+            .with_arch_os(current.with_libc(Libc::Any))
+            .unwrap();
+        assert!(!app.is_foreign_arch_os());
+        assert_eq!(app.arch_os.libc, current.libc);
+
+        if current.os == Os::Linux {
+            let other_libc = if current.libc == Libc::Musl {
+                Libc::Gnu
+            } else {
+                Libc::Musl
+            };
+            let app = App::new(&dir)
+                .unwrap()
+                .with_arch_os(current.with_libc(other_libc))
+                .unwrap();
+            assert!(app.is_foreign_arch_os());
+        }
+    }
+
+    #[test]
+    #[temp_env_vars]
+    fn read_arch_os_should_use_env_variable_as_default() {
+        env::set_var("CLYDE_ARCH_OS", "aarch64-linux");
+        assert_eq!(
+            App::read_arch_os(None).unwrap(),
+            Some(ArchOs::new(Arch::Aarch64, Os::Linux))
+        );
+        assert_eq!(
+            App::read_arch_os(Some("x86_64-macos")).unwrap(),
+            Some(ArchOs::new(Arch::X86_64, Os::MacOs))
+        );
+        env::remove_var("CLYDE_ARCH_OS");
+        assert_eq!(App::read_arch_os(None).unwrap(), None);
+    }
 }
@@ -2,8 +2,10 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::env::consts;
+use std::collections::HashMap;
+use std::env::{self, consts};
 use std::fmt;
+use std::fs;
 
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
@@ -17,6 +19,14 @@ pub enum Arch {
     X86_64,
     X86,
     Aarch64,
+    // "arm" is the name used by `std::env::consts::ARCH`
+    #[serde(alias = "arm")]
+    Armv7,
+    #[serde(alias = "riscv64gc")]
+    Riscv64,
+    #[serde(alias = "powerpc64le")]
+    Ppc64le,
+    S390x,
 }
 
 impl fmt::Display for Arch {
@@ -32,6 +42,7 @@ pub enum Os {
     Linux,
     MacOs,
     Windows,
+    FreeBsd,
 }
 
 impl fmt::Display for Os {
@@ -40,10 +51,117 @@ impl fmt::Display for Os {
     }
 }
 
+/// The C library a Linux binary has been built against. `Any` is used for binaries which do not
+/// depend on a specific libc, and for the other OSes.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Libc {
+    Any,
+    Gnu,
+    Musl,
+}
+
+impl fmt::Display for Libc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", serde_yaml::to_string(self).unwrap().trim(),)
+    }
+}
+
+impl Libc {
+    fn parse(text: &str) -> Option<Libc> {
+        // Accept target triple variants like `gnueabihf` or `musleabi`
+        if text.starts_with("gnu") {
+            Some(Libc::Gnu)
+        } else if text.starts_with("musl") {
+            Some(Libc::Musl)
+        } else {
+            None
+        }
+    }
+
+    /// Detect the libc of the host. Only meaningful on Linux.
+    fn detect() -> Libc {
+        let is_musl = fs::read_dir("/lib").is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.file_name().to_string_lossy().starts_with("ld-musl-"))
+        });
+        if is_musl {
+            Libc::Musl
+        } else {
+            Libc::Gnu
+        }
+    }
+
+    /// Returns the libcs whose binaries can run on a host using `self`, ordered by preference.
+    /// musl binaries are usually statically linked, so they run everywhere, but gnu binaries
+    /// only run on glibc hosts.
+    fn compatible_libcs(&self) -> &'static [Libc] {
+        match self {
+            Libc::Gnu => &[Libc::Gnu, Libc::Any, Libc::Musl],
+            Libc::Musl => &[Libc::Musl, Libc::Any],
+            Libc::Any => &[Libc::Any, Libc::Musl, Libc::Gnu],
+        }
+    }
+}
+
+lazy_static! {
+    // `App::new()` fails if `CLYDE_ARCH_FALLBACKS` is invalid, so the default value is never used
+    // by commands
+    static ref ARCH_FALLBACKS: ArchFallbacks = ArchFallbacks::from_env().unwrap_or_default();
+}
+
+/// Defines which other archs can run on an arch. By default x86_64 hosts can run x86 assets.
+/// This can be changed using the `CLYDE_ARCH_FALLBACKS` environment variable, which contains a
+/// comma-separated list of `<arch>:<fallback arch>` pairs. Set it to an empty string to disable
+/// fallbacks.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ArchFallbacks {
+    fallbacks: HashMap<Arch, Vec<Arch>>,
+}
+
+impl Default for ArchFallbacks {
+    fn default() -> Self {
+        ArchFallbacks {
+            fallbacks: HashMap::from([(Arch::X86_64, vec![Arch::X86])]),
+        }
+    }
+}
+
+impl ArchFallbacks {
+    pub fn parse(text: &str) -> Result<ArchFallbacks> {
+        let mut fallbacks = HashMap::<Arch, Vec<Arch>>::new();
+        for pair in text.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+            let (arch, fallback) = pair
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid arch fallback '{pair}', expected <arch>:<arch>"))?;
+            let arch: Arch = serde_yaml::from_str(arch.trim())?;
+            let fallback: Arch = serde_yaml::from_str(fallback.trim())?;
+            fallbacks.entry(arch).or_default().push(fallback);
+        }
+        Ok(ArchFallbacks { fallbacks })
+    }
+
+    /// Reads the fallbacks from the `CLYDE_ARCH_FALLBACKS` environment variable, if it is set
+    pub fn from_env() -> Result<ArchFallbacks> {
+        match env::var("CLYDE_ARCH_FALLBACKS") {
+            Ok(value) => ArchFallbacks::parse(&value)
+                .map_err(|err| anyhow!("Invalid CLYDE_ARCH_FALLBACKS: {err}")),
+            Err(_) => Ok(ArchFallbacks::default()),
+        }
+    }
+
+    /// Returns the archs which can run on `arch`, ordered by preference
+    pub fn get(&self, arch: Arch) -> &[Arch] {
+        self.fallbacks.get(&arch).map_or(&[], |x| x.as_slice())
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ArchOs {
     pub arch: Arch,
     pub os: Os,
+    pub libc: Libc,
 }
 
 impl fmt::Display for ArchOs {
@@ -56,7 +174,7 @@ impl ArchOs {
     fn from_strings(arch: &str, os: &str) -> Result<ArchOs> {
         let arch = serde_yaml::from_str(arch)?;
         let os = serde_yaml::from_str(os)?;
-        Ok(ArchOs { arch, os })
+        Ok(ArchOs::new(arch, os))
     }
 
     pub fn any() -> ArchOs {
@@ -64,13 +182,21 @@ impl ArchOs {
     }
 
     pub fn new(arch: Arch, os: Os) -> ArchOs {
-        ArchOs { arch, os }
+        ArchOs {
+            arch,
+            os,
+            libc: Libc::Any,
+        }
+    }
+
+    pub fn with_libc(&self, libc: Libc) -> ArchOs {
+        ArchOs { libc, ..*self }
     }
 
     pub fn with_any_arch(&self) -> ArchOs {
         ArchOs {
             arch: Arch::Any,
-            os: self.os,
+            ..*self
         }
     }
 
@@ -78,9 +204,50 @@ impl ArchOs {
         ArchOs {
             arch: self.arch,
             os: Os::Any,
+            libc: Libc::Any,
         }
     }
 
+    /// Returns the arch-os whose assets can be used on `self`, ordered by preference. Arch
+    /// fallbacks are read from the `CLYDE_ARCH_FALLBACKS` environment variable.
+    pub fn fallbacks(&self) -> Vec<ArchOs> {
+        self.fallbacks_with(&ARCH_FALLBACKS)
+    }
+
+    pub fn fallbacks_with(&self, arch_fallbacks: &ArchFallbacks) -> Vec<ArchOs> {
+        let mut bases = vec![*self];
+        if self.arch != Arch::Any {
+            bases.push(self.with_any_arch());
+        }
+        for arch in arch_fallbacks.get(self.arch) {
+            bases.push(ArchOs {
+                arch: *arch,
+                ..*self
+            });
+        }
+        if self.os != Os::Any {
+            // Probably less useful than the previous one, but you never know
+            bases.push(self.with_any_os());
+        }
+        bases.push(ArchOs::any());
+
+        let mut fallbacks = Vec::<ArchOs>::new();
+        for base in bases {
+            let libcs = if base.os == Os::Linux {
+                base.libc.compatible_libcs()
+            } else {
+                &[Libc::Any]
+            };
+            for libc in libcs {
+                let arch_os = base.with_libc(*libc);
+                if !fallbacks.contains(&arch_os) {
+                    fallbacks.push(arch_os);
+                }
+            }
+        }
+        fallbacks
+    }
+
     pub fn parse(text: &str) -> Result<ArchOs> {
         if text == ANY {
             return Ok(ArchOs::new(Arch::Any, Os::Any));
@@ -99,15 +266,31 @@ impl ArchOs {
                 .ok_or_else(|| anyhow!("Could not find OS in {}", text))?,
             x => x,
         };
-        ArchOs::from_strings(arch, os)
+        let arch_os = ArchOs::from_strings(arch, os)?;
+        match iter.next().and_then(Libc::parse) {
+            Some(libc) => Ok(arch_os.with_libc(libc)),
+            None => Ok(arch_os),
+        }
     }
 
     pub fn current() -> ArchOs {
-        ArchOs::from_strings(consts::ARCH, consts::OS).unwrap()
+        let arch = match consts::ARCH {
+            "powerpc64" if cfg!(target_endian = "little") => "ppc64le",
+            x => x,
+        };
+        let arch_os = ArchOs::from_strings(arch, consts::OS).unwrap();
+        if arch_os.os == Os::Linux {
+            arch_os.with_libc(Libc::detect())
+        } else {
+            arch_os
+        }
     }
 
     pub fn to_str(&self) -> String {
-        format!("{}-{}", self.arch, self.os)
+        match self.libc {
+            Libc::Any => format!("{}-{}", self.arch, self.os),
+            libc => format!("{}-{}-{}", self.arch, self.os, libc),
+        }
     }
 }
 
@@ -123,7 +306,35 @@ mod tests {
         );
         assert_eq!(
             ArchOs::parse("x86_64-unknown-linux-gnu").unwrap(),
-            ArchOs::new(Arch::X86_64, Os::Linux)
+            ArchOs::new(Arch::X86_64, Os::Linux).with_libc(Libc::Gnu)
+        );
+        assert_eq!(
+            ArchOs::parse("x86_64-linux-musl").unwrap(),
+            ArchOs::new(Arch::X86_64, Os::Linux).with_libc(Libc::Musl)
+        );
+        assert_eq!(
+            ArchOs::parse("aarch64-unknown-linux-musleabi").unwrap(),
+            ArchOs::new(Arch::Aarch64, Os::Linux).with_libc(Libc::Musl)
+        );
+        assert_eq!(
+            ArchOs::parse("riscv64gc-unknown-linux-gnu").unwrap(),
+            ArchOs::new(Arch::Riscv64, Os::Linux).with_libc(Libc::Gnu)
+        );
+        assert_eq!(
+            ArchOs::parse("powerpc64le-unknown-linux-gnu").unwrap(),
+            ArchOs::new(Arch::Ppc64le, Os::Linux).with_libc(Libc::Gnu)
+        );
+        assert_eq!(
+            ArchOs::parse("armv7-unknown-linux-gnueabihf").unwrap(),
+            ArchOs::new(Arch::Armv7, Os::Linux).with_libc(Libc::Gnu)
+        );
+        assert_eq!(
+            ArchOs::parse("x86_64-unknown-freebsd").unwrap(),
+            ArchOs::new(Arch::X86_64, Os::FreeBsd)
+        );
+        assert_eq!(
+            ArchOs::parse("s390x-linux").unwrap(),
+            ArchOs::new(Arch::S390x, Os::Linux)
         );
     }
 
@@ -133,5 +344,62 @@ mod tests {
             ArchOs::new(Arch::X86_64, Os::Linux).to_str(),
             "x86_64-linux"
         );
+        assert_eq!(
+            ArchOs::new(Arch::X86_64, Os::Linux)
+                .with_libc(Libc::Musl)
+                .to_str(),
+            "x86_64-linux-musl"
+        );
+    }
+
+    #[test]
+    fn fallbacks_should_only_use_gnu_builds_on_glibc_hosts() {
+        let linux = ArchOs::new(Arch::X86_64, Os::Linux);
+        let gnu = linux.with_libc(Libc::Gnu);
+        let musl = linux.with_libc(Libc::Musl);
+        let no_fallbacks = ArchFallbacks::parse("").unwrap();
+
+        assert_eq!(
+            gnu.fallbacks_with(&no_fallbacks),
+            vec![
+                gnu,
+                linux,
+                musl,
+                ArchOs::new(Arch::Any, Os::Linux).with_libc(Libc::Gnu),
+                ArchOs::new(Arch::Any, Os::Linux),
+                ArchOs::new(Arch::Any, Os::Linux).with_libc(Libc::Musl),
+                ArchOs::new(Arch::X86_64, Os::Any),
+                ArchOs::any(),
+            ]
+        );
+
+        let fallbacks = musl.fallbacks_with(&no_fallbacks);
+        assert_eq!(&fallbacks[..2], &[musl, linux]);
+        assert!(!fallbacks.iter().any(|x| x.libc == Libc::Gnu));
+    }
+
+    #[test]
+    fn fallbacks_should_include_fallback_archs() {
+        let arch_fallbacks = ArchFallbacks::parse("aarch64:armv7").unwrap();
+        let arch_os = ArchOs::new(Arch::Aarch64, Os::FreeBsd);
+
+        assert_eq!(
+            arch_os.fallbacks_with(&arch_fallbacks),
+            vec![
+                arch_os,
+                ArchOs::new(Arch::Any, Os::FreeBsd),
+                ArchOs::new(Arch::Armv7, Os::FreeBsd),
+                ArchOs::new(Arch::Aarch64, Os::Any),
+                ArchOs::any(),
+            ]
+        );
+    }
+
+    #[test]
+    fn arch_fallbacks_should_default_to_x86_on_x86_64() {
+        let arch_fallbacks = ArchFallbacks::default();
+        assert_eq!(arch_fallbacks.get(Arch::X86_64), &[Arch::X86]);
+        assert!(arch_fallbacks.get(Arch::Aarch64).is_empty());
+        assert!(ArchFallbacks::parse("x86_64").is_err());
     }
 }
@@ -4,49 +4,83 @@
 
 use std::path::Path;
 
-use anyhow::{anyhow, Result};
-use semver::Version;
+use anyhow::{anyhow, Context, Result};
 
 use clyde::app::App;
 use clyde::arch_os::ArchOs;
-use clyde::checksum::compute_checksum;
+use clyde::checksum::{compute_checksums, HashAlgorithm};
 use clyde::file_cache::FileCache;
 use clyde::package::{Asset, Package, Release};
+use clyde::signature::verify_signature;
 use clyde::ui::Ui;
+use clyde::version::Version;
 
-use crate::url_selector::{select_best_urls, BestUrlOptions};
+use crate::fetch::UpstreamRelease;
+use crate::upstream_checksums::{is_checksums_file_name, verify_upstream_checksums};
+use crate::url_selector::{find_signature_url, select_best_urls, BestUrlOptions};
 
-fn compute_url_checksum(
+pub const DEFAULT_CHECKSUM_ALGORITHMS: &[HashAlgorithm] = &[HashAlgorithm::Sha256];
+
+/// Extensions of all the signature files we know how to verify
+const ALL_SIGNATURE_EXTENSIONS: &[&str] = &["minisig", "asc", "sig", "bundle"];
+
+/// Returns false if `url` points to a signature or a checksums file
+fn is_asset_url(url: &str) -> bool {
+    let is_signature = ALL_SIGNATURE_EXTENSIONS
+        .iter()
+        .any(|ext| url.ends_with(&format!(".{ext}")));
+    let name = url.rsplit_once('/').map_or(url, |x| x.1);
+    !is_signature && !is_checksums_file_name(name)
+}
+
+/// Returns the URL of the signature of `url`, if `urls` contains one and `package` defines how
+/// to verify signatures
+fn find_asset_signature_url(
     ui: &Ui,
-    cache: &FileCache,
     package: &Package,
-    version: &Version,
     url: &str,
-) -> Result<String> {
-    let path = cache.download(ui, &package.name, version, url)?;
-    ui.info("Computing checksum");
-    compute_checksum(&path)
+    urls: &[String],
+) -> Option<String> {
+    match &package.signing {
+        Some(signing) => find_signature_url(url, urls, signing.signature_extensions()),
+        None => {
+            if let Some(signature_url) = find_signature_url(url, urls, ALL_SIGNATURE_EXTENSIONS) {
+                ui.info(&format!(
+                    "Found signature {signature_url}, but the package has no `signing` entry, ignoring it"
+                ));
+            }
+            None
+        }
+    }
 }
 
-pub fn add_asset(
+/// Create an asset for `url`. The asset is checked against the checksums published in `release`.
+/// If `release` contains a signature for the asset, the signature is verified and stored in the
+/// asset.
+pub fn create_asset(
     ui: &Ui,
     cache: &FileCache,
     package: &Package,
     version: &Version,
-    release: &mut Release,
-    arch_os: &ArchOs,
     url: &str,
-) -> Result<()> {
-    let checksum = compute_url_checksum(ui, cache, package, version, url)?;
+    release: &UpstreamRelease,
+    algorithms: &[HashAlgorithm],
+) -> Result<Asset> {
+    let asset_path = cache.download(ui, &package.name, version, url)?;
+    ui.info("Computing checksum");
+    let checksums = compute_checksums(&asset_path, algorithms)?;
 
-    let asset = Asset {
-        url: url.to_string(),
-        sha256: checksum,
-    };
+    verify_upstream_checksums(ui, cache, &package.name, version, url, &asset_path, release)?;
 
-    release.assets.insert(*arch_os, asset);
+    let signature_url = find_asset_signature_url(ui, package, url, &release.urls);
+    if let (Some(signature_url), Some(signing)) = (&signature_url, &package.signing) {
+        let signature_path = cache.download(ui, &package.name, version, signature_url)?;
+        ui.info("Verifying signature");
+        verify_signature(signing, &asset_path, &signature_path)
+            .with_context(|| format!("Invalid signature {signature_url}"))?;
+    }
 
-    Ok(())
+    Ok(Asset::new(url, checksums).with_signature(signature_url))
 }
 
 pub fn add_assets(
@@ -56,8 +90,19 @@ pub fn add_assets(
     version: &Version,
     arch_os: &Option<String>,
     urls: &[String],
+    algorithms: &[HashAlgorithm],
 ) -> Result<()> {
     let package = Package::from_file(path)?;
+    let algorithms = if algorithms.is_empty() {
+        DEFAULT_CHECKSUM_ALGORITHMS
+    } else {
+        algorithms
+    };
+
+    let upstream_release = UpstreamRelease {
+        urls: urls.to_vec(),
+        ..Default::default()
+    };
 
     let mut release = match package.releases.get(version) {
         Some(x) => x.clone(),
@@ -65,36 +110,45 @@ pub fn add_assets(
     };
 
     if let Some(arch_os) = arch_os {
-        if urls.len() > 1 {
-            return Err(anyhow!("When using --arch-os, only one URL can be passed"));
+        let asset_urls: Vec<_> = urls.iter().filter(|x| is_asset_url(x)).collect();
+        if asset_urls.len() != 1 {
+            return Err(anyhow!(
+                "When using --arch-os, only one asset URL can be passed, along with its signature and checksums URLs"
+            ));
         }
-        let url = urls.first().unwrap();
+        let url = asset_urls[0];
         let arch_os = ArchOs::parse(arch_os)?;
-        add_asset(
+        let asset = create_asset(
             ui,
             &app.download_cache,
             &package,
             version,
-            &mut release,
-            &arch_os,
             url,
+            &upstream_release,
+            algorithms,
         )?;
+        release.assets.insert(arch_os, asset);
     } else {
         let urls_for_arch_os = select_best_urls(ui, urls, BestUrlOptions::default())?;
         for (arch_os, url) in urls_for_arch_os {
             ui.info(&format!("{arch_os}: {url}"));
-            let result = add_asset(
+            let result = create_asset(
                 &ui.nest(),
                 &app.download_cache,
                 &package,
                 version,
-                &mut release,
-                &arch_os,
                 &url,
+                &upstream_release,
+                algorithms,
             );
-            if let Err(err) = result {
-                ui.error(&format!("Can't add {arch_os:?} build from {url}: {err}"));
-                return Err(err);
+            match result {
+                Ok(asset) => {
+                    release.assets.insert(arch_os, asset);
+                }
+                Err(err) => {
+                    ui.error(&format!("Can't add {arch_os:?} build from {url}: {err}"));
+                    return Err(err);
+                }
             };
         }
     }
@@ -113,7 +167,8 @@ pub fn add_assets_cmd(
     version: &str,
     arch_os: &Option<String>,
     urls: &[String],
+    algorithms: &[HashAlgorithm],
 ) -> Result<()> {
-    let version = Version::parse(version)?;
-    add_assets(app, ui, path, &version, arch_os, urls)
+    let version = Package::from_file(path)?.parse_version(version)?;
+    add_assets(app, ui, path, &version, arch_os, urls, algorithms)
 }
@@ -7,7 +7,6 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
 
 use anyhow::{anyhow, Context, Result};
-use semver::Version;
 use shell_words;
 use tempfile::TempDir;
 
@@ -18,6 +17,7 @@ use clyde::package::Package;
 use clyde::store::INDEX_NAME;
 use clyde::ui::Ui;
 use clyde::vars::{expand_vars, VarsMap};
+use clyde::version::Version;
 
 struct FailedPackage {
     package_path: PathBuf,
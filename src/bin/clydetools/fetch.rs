@@ -2,30 +2,41 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::path::PathBuf;
 
 use anyhow::Result;
 use chrono::Utc;
-use semver::Version;
 
 use clyde::app::App;
 use clyde::arch_os::ArchOs;
-use clyde::package::{FetcherConfig, Package, Release};
+use clyde::checksum::{Checksum, HashAlgorithm};
+use clyde::package::{FetcherConfig, Package, Release, ReleaseAssets};
 use clyde::ui::Ui;
+use clyde::version::Version;
 
-use crate::add_assets::add_asset;
+use crate::add_assets::{create_asset, DEFAULT_CHECKSUM_ALGORITHMS};
 use crate::forgejo_fetcher::ForgejoFetcher;
 use crate::github_fetcher::GitHubFetcher;
 use crate::gitlab_fetcher::GitLabFetcher;
 use crate::script_fetcher::ScriptFetcher;
 
+/// Information about the upstream release assets come from
+#[derive(Debug, Default)]
+pub struct UpstreamRelease {
+    /// All the URLs of the release, used to find signatures and checksums files
+    pub urls: Vec<String>,
+    /// Asset digests provided by the forge API, indexed by asset URL
+    pub digests: HashMap<String, Checksum>,
+}
+
 #[derive(Debug)]
 pub enum UpdateStatus {
     UpToDate,
     NeedUpdate {
         version: Version,
         urls: HashMap<ArchOs, String>,
+        release: UpstreamRelease,
     },
 }
 
@@ -69,7 +80,51 @@ impl FetcherFinder {
     }
 }
 
-pub fn fetch_cmd(app: &App, ui: &Ui, paths: &[PathBuf]) -> Result<()> {
+/// Returns the checksum algorithms used by the assets of the latest release of `package`, so that
+/// updates keep using the same ones. Assets can use different algorithms: all of them are
+/// returned, sorted.
+fn get_package_checksum_algorithms(package: &Package) -> Vec<HashAlgorithm> {
+    let algorithms: BTreeSet<HashAlgorithm> = package
+        .get_latest_version()
+        .and_then(|version| package.releases.get(version))
+        .into_iter()
+        .flat_map(|release| release.assets.values())
+        .flat_map(|asset| asset.checksums.iter().map(|x| x.algorithm))
+        .collect();
+    if algorithms.is_empty() {
+        return DEFAULT_CHECKSUM_ALGORITHMS.to_vec();
+    }
+    algorithms.into_iter().collect()
+}
+
+/// Returns an `asset_url_template` producing the URLs of all the assets of `version`, if there is
+/// one. Needs at least two assets, to make sure the template does not only fit by chance.
+fn infer_asset_url_template(version: &Version, assets: &ReleaseAssets) -> Option<String> {
+    if assets.len() < 2 {
+        return None;
+    }
+    let version_str = version.to_string();
+    let mut templates = assets.iter().map(|(arch_os, asset)| {
+        asset
+            .url
+            .replace(&version_str, "${version}")
+            .replace(&arch_os.arch.to_string(), "${arch}")
+            .replace(&arch_os.os.to_string(), "${os}")
+    });
+    let template = templates.next()?;
+    if templates.all(|x| x == template) {
+        Some(template)
+    } else {
+        None
+    }
+}
+
+pub fn fetch_cmd(
+    app: &App,
+    ui: &Ui,
+    paths: &[PathBuf],
+    algorithms: &[HashAlgorithm],
+) -> Result<()> {
     let fetcher_finder = FetcherFinder::new();
 
     for path in paths {
@@ -91,14 +146,18 @@ pub fn fetch_cmd(app: &App, ui: &Ui, paths: &[PathBuf]) -> Result<()> {
             }
         };
 
-        let (version, urls) = match fetch_status {
+        let (version, urls, upstream_release) = match fetch_status {
             UpdateStatus::UpToDate => {
                 ui2.info("Package is up-to-date");
                 continue;
             }
-            UpdateStatus::NeedUpdate { version, urls } => {
+            UpdateStatus::NeedUpdate {
+                version,
+                urls,
+                release,
+            } => {
                 ui2.info(&format!("Package can be updated to version {version}"));
-                (version, urls)
+                (version, urls, release)
             }
         };
 
@@ -107,20 +166,115 @@ pub fn fetch_cmd(app: &App, ui: &Ui, paths: &[PathBuf]) -> Result<()> {
             continue;
         }
 
+        let algorithms = if algorithms.is_empty() {
+            get_package_checksum_algorithms(&package)
+        } else {
+            algorithms.to_vec()
+        };
+
         let mut release = Release::default().with_added_at(Some(Utc::now()));
         for (arch_os, url) in urls {
-            add_asset(
+            let asset = create_asset(
                 &ui2,
                 &app.download_cache,
                 &package,
                 &version,
-                &mut release,
-                &arch_os,
                 &url,
+                &upstream_release,
+                &algorithms,
             )?;
+            release.assets.insert(arch_os, asset);
+        }
+        let mut new_package = package.replace_release(&version, release);
+        if new_package.asset_url_template.is_none() {
+            let release = &new_package.releases[&version];
+            new_package.asset_url_template = infer_asset_url_template(&version, &release.assets);
         }
-        let new_package = package.replace_release(&version, release);
         new_package.to_file(path)?;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clyde::package::Asset;
+
+    fn create_assets(urls: &[(&str, &str)]) -> ReleaseAssets {
+        urls.iter()
+            .map(|(arch_os, url)| (ArchOs::parse(arch_os).unwrap(), Asset::new(url, vec![])))
+            .collect()
+    }
+
+    #[test]
+    fn get_package_checksum_algorithms_should_return_the_algorithms_of_all_assets() {
+        // GIVEN a package whose latest release has 2 assets using different algorithms
+        let package = Package::from_yaml_str(&format!(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                x86_64-linux:
+                  url: https://example.com/linux
+                  checksum: sha512:{}
+                x86_64-windows:
+                  url: https://example.com/windows
+                  checksum: blake3:{}
+            installs: {{}}
+            ",
+            "5678".repeat(32),
+            "abcd".repeat(16)
+        ))
+        .unwrap();
+
+        // WHEN get_package_checksum_algorithms() is called
+        let algorithms = get_package_checksum_algorithms(&package);
+
+        // THEN the algorithms of both assets are returned
+        assert_eq!(
+            algorithms,
+            vec![HashAlgorithm::Sha512, HashAlgorithm::Blake3]
+        );
+    }
+
+    #[test]
+    fn infer_asset_url_template_should_find_a_template_if_all_urls_fit() {
+        let assets = create_assets(&[
+            (
+                "x86_64-linux",
+                "https://example.com/v1.2.0/foo-1.2.0-x86_64-linux.tar.gz",
+            ),
+            (
+                "aarch64-macos",
+                "https://example.com/v1.2.0/foo-1.2.0-aarch64-macos.tar.gz",
+            ),
+        ]);
+
+        assert_eq!(
+            infer_asset_url_template(&Version::new(1, 2, 0), &assets),
+            Some("https://example.com/v${version}/foo-${version}-${arch}-${os}.tar.gz".to_string())
+        );
+    }
+
+    #[test]
+    fn infer_asset_url_template_should_return_none_if_urls_do_not_fit() {
+        let assets = create_assets(&[
+            (
+                "x86_64-linux",
+                "https://example.com/v1.2.0/foo-1.2.0-amd64-linux.tar.gz",
+            ),
+            (
+                "aarch64-macos",
+                "https://example.com/v1.2.0/foo-1.2.0-arm64-darwin.tar.gz",
+            ),
+        ]);
+
+        assert_eq!(
+            infer_asset_url_template(&Version::new(1, 2, 0), &assets),
+            None
+        );
+    }
+}
@@ -14,7 +14,7 @@ use serde_json::{self, Value};
 use clyde::package::{FetcherConfig, Package};
 use clyde::ui::Ui;
 
-use crate::fetch::{Fetcher, UpdateStatus};
+use crate::fetch::{Fetcher, UpdateStatus, UpstreamRelease};
 use crate::url_selector::{select_best_urls, BestUrlOptions};
 use crate::version_utils::version_from_tag;
 
@@ -112,7 +112,7 @@ impl Fetcher for ForgejoFetcher {
         let tag = release_json["tag_name"]
             .as_str()
             .expect("No 'tag_name' in release JSON");
-        let forgejo_latest_version = version_from_tag(tag)?;
+        let forgejo_latest_version = version_from_tag(tag, package.version_scheme)?;
 
         let package_latest_version = package
             .get_latest_version()
@@ -122,15 +122,20 @@ impl Fetcher for ForgejoFetcher {
             return Ok(UpdateStatus::UpToDate);
         }
 
+        let release_urls = extract_build_urls(&release_json)?;
         let urls = select_best_urls(
             ui,
-            &extract_build_urls(&release_json)?,
+            &release_urls,
             BestUrlOptions::try_from(&package.fetcher)?,
         )?;
 
         Ok(UpdateStatus::NeedUpdate {
             version: forgejo_latest_version,
             urls,
+            release: UpstreamRelease {
+                urls: release_urls,
+                ..Default::default()
+            },
         })
     }
 }
@@ -2,9 +2,11 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::HashMap;
 use std::env;
 use std::fs::{self, File};
 use std::path::Path;
+use std::str::FromStr;
 
 use anyhow::{anyhow, Context, Result};
 use regex::Regex;
@@ -12,10 +14,11 @@ use reqwest::blocking::Client;
 use reqwest::header::{self, HeaderMap, HeaderValue};
 use serde_json::{self, Value};
 
+use clyde::checksum::Checksum;
 use clyde::package::Package;
 use clyde::ui::Ui;
 
-use crate::fetch::{Fetcher, UpdateStatus};
+use crate::fetch::{Fetcher, UpdateStatus, UpstreamRelease};
 use crate::url_selector::{select_best_urls, BestUrlOptions};
 use crate::version_utils::version_from_tag;
 
@@ -45,7 +48,7 @@ impl Fetcher for GitHubFetcher {
         let tag = release_json["tag_name"]
             .as_str()
             .expect("No 'tag_name' in release JSON");
-        let github_latest_version = version_from_tag(tag)?;
+        let github_latest_version = version_from_tag(tag, package.version_scheme)?;
 
         let package_latest_version = package
             .get_latest_version()
@@ -55,15 +58,20 @@ impl Fetcher for GitHubFetcher {
             return Ok(UpdateStatus::UpToDate);
         }
 
+        let release_urls = extract_build_urls(&release_json)?;
         let urls = select_best_urls(
             ui,
-            &extract_build_urls(&release_json)?,
+            &release_urls,
             BestUrlOptions::try_from(&package.fetcher)?,
         )?;
 
         Ok(UpdateStatus::NeedUpdate {
             version: github_latest_version,
             urls,
+            release: UpstreamRelease {
+                urls: release_urls,
+                digests: extract_digests(ui, &release_json)?,
+            },
         })
     }
 }
@@ -137,3 +145,73 @@ fn extract_build_urls(value: &Value) -> Result<Vec<String>> {
         .collect();
     Ok(urls)
 }
+
+/// Extract the asset digests, if the API provides them. Digests using an unknown algorithm are
+/// skipped.
+fn extract_digests(ui: &Ui, value: &Value) -> Result<HashMap<String, Checksum>> {
+    let assets = value["assets"]
+        .as_array()
+        .ok_or_else(|| anyhow!("No 'assets' element in release JSON"))?;
+    let mut digests = HashMap::new();
+    for asset in assets {
+        let Some(digest) = asset["digest"].as_str() else {
+            continue;
+        };
+        let url = asset["browser_download_url"]
+            .as_str()
+            .ok_or_else(|| anyhow!("No 'browser_download_url' in release JSON asset"))?;
+        match Checksum::from_str(digest) {
+            Ok(checksum) => {
+                digests.insert(url.to_string(), checksum);
+            }
+            Err(err) => ui.warn(&format!("Ignoring digest of {url}: {err}")),
+        }
+    }
+    Ok(digests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn extract_digests_should_skip_unknown_digests() {
+        // GIVEN a release with a sha256 digest and a digest using an unknown algorithm
+        let sha256 = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+        let release_json = json!({
+            "assets": [
+                {
+                    "browser_download_url": "https://example.com/foo.tar.gz",
+                    "digest": format!("sha256:{sha256}"),
+                },
+                {
+                    "browser_download_url": "https://example.com/foo.zip",
+                    "digest": "md5:d41d8cd98f00b204e9800998ecf8427e",
+                },
+                {
+                    "browser_download_url": "https://example.com/foo.deb",
+                },
+            ]
+        });
+
+        // WHEN extract_digests() is called
+        let digests = extract_digests(&Ui::default(), &release_json).unwrap();
+
+        // THEN only the sha256 digest is returned
+        assert_eq!(
+            digests,
+            HashMap::from([(
+                "https://example.com/foo.tar.gz".to_string(),
+                Checksum::from_str(&format!("sha256:{sha256}")).unwrap()
+            )])
+        );
+    }
+
+    #[test]
+    fn extract_digests_should_fail_if_there_are_no_assets() {
+        let result = extract_digests(&Ui::default(), &json!({}));
+        assert!(result.is_err());
+    }
+}
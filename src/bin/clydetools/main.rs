@@ -15,6 +15,7 @@ pub mod forgejo_fetcher;
 pub mod github_fetcher;
 pub mod gitlab_fetcher;
 pub mod script_fetcher;
+pub mod upstream_checksums;
 pub mod url_selector;
 pub mod version_utils;
 
@@ -22,6 +23,7 @@ pub mod version_utils;
 extern crate lazy_static;
 
 use clyde::app::App;
+use clyde::checksum::HashAlgorithm;
 use clyde::ui::Ui;
 
 use add_assets::add_assets_cmd;
@@ -55,6 +57,10 @@ enum Command {
         arch_os: Option<String>,
         /// URLs of the build archives
         urls: Vec<String>,
+        /// Checksum algorithm to use (sha256, sha512 or blake3). Can be repeated to store
+        /// multiple checksums. Defaults to sha256.
+        #[arg(short, long = "checksum", value_name = "ALGORITHM")]
+        checksums: Vec<HashAlgorithm>,
     },
     /// Check the validity of packages: checks the YAML files has all the required entries, and
     /// check the latest asset installs (if it can be installed on the running machine)
@@ -68,6 +74,11 @@ enum Command {
         /// Path to the package YAML files
         #[arg(required = true)]
         package_files: Vec<PathBuf>,
+        /// Checksum algorithm to use (sha256, sha512 or blake3). Can be repeated to store
+        /// multiple checksums. Defaults to the algorithms used by the latest release of the
+        /// package.
+        #[arg(short, long = "checksum", value_name = "ALGORITHM")]
+        checksums: Vec<HashAlgorithm>,
     },
 }
 
@@ -82,15 +93,27 @@ fn main() -> Result<()> {
             version,
             arch_os,
             urls,
+            checksums,
         } => {
             let app = App::new(&home)?;
-            add_assets_cmd(&app, &ui, &package_file, &version, &arch_os, &urls)
+            add_assets_cmd(
+                &app,
+                &ui,
+                &package_file,
+                &version,
+                &arch_os,
+                &urls,
+                &checksums,
+            )
         }
         // Check can run without an existing Clyde home: it creates a temporary one to test the package
         Command::Check { package_files } => check_packages(&ui, &package_files),
-        Command::Fetch { package_files } => {
+        Command::Fetch {
+            package_files,
+            checksums,
+        } => {
             let app = App::new(&home)?;
-            fetch_cmd(&app, &ui, &package_files)
+            fetch_cmd(&app, &ui, &package_files, &checksums)
         }
     }
 }
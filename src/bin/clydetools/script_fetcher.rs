@@ -13,14 +13,13 @@ use boa_engine::{
 use boa_runtime::Console;
 use reqwest::blocking::Client;
 use reqwest::header::{self, HeaderMap, HeaderValue};
-use semver::Version;
 use serde::Deserialize;
 use serde_json;
 
 use clyde::package::Package;
 use clyde::ui::Ui;
 
-use crate::fetch::{Fetcher, UpdateStatus};
+use crate::fetch::{Fetcher, UpdateStatus, UpstreamRelease};
 use crate::url_selector::{select_best_urls, BestUrlOptions};
 
 const SCRIPT_FILE_NAME: &str = "fetch.js";
@@ -94,7 +93,7 @@ impl Fetcher for ScriptFetcher {
         ui.info("Running fetcher script");
         let response = eval_script(&script)?;
 
-        let version = Version::parse(&response.version)?;
+        let version = package.parse_version(&response.version)?;
         if let Some(latest_version) = package.get_latest_version() {
             if version <= *latest_version {
                 return Ok(UpdateStatus::UpToDate);
@@ -103,7 +102,14 @@ impl Fetcher for ScriptFetcher {
 
         let urls = select_best_urls(ui, &response.urls, BestUrlOptions::default())?;
 
-        Ok(UpdateStatus::NeedUpdate { version, urls })
+        Ok(UpdateStatus::NeedUpdate {
+            version,
+            urls,
+            release: UpstreamRelease {
+                urls: response.urls,
+                ..Default::default()
+            },
+        })
     }
 }
 
@@ -152,6 +158,8 @@ fn eval_script(script: &str) -> Result<ScriptResponse> {
 mod tests {
     use super::*;
 
+    use clyde::version::Version;
+
     #[test]
     fn eval_script_return_version() {
         // GIVEN a script which returns a version
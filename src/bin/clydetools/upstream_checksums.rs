@@ -0,0 +1,305 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use clyde::checksum::{compute_checksums, Checksum, HashAlgorithm};
+use clyde::file_cache::FileCache;
+use clyde::ui::Ui;
+use clyde::version::Version;
+
+use crate::fetch::UpstreamRelease;
+
+lazy_static! {
+    // Matches release-wide checksums files, like `SHA256SUMS`, `checksums.txt` or
+    // `foo_1.2.3_checksums.txt`
+    static ref CHECKSUMS_FILE_RX: Regex =
+        Regex::new(r"(^|[-_])(sha(256|512)sums|checksums?)(\.txt)?$").unwrap();
+
+    // Matches a `<hex> <name>` line, as produced by `sha256sum` and friends
+    static ref GNU_LINE_RX: Regex =
+        Regex::new(r"^(?P<value>[0-9a-fA-F]+)\s+\*?(?P<name>.+)$").unwrap();
+
+    // Matches a `SHA256 (<name>) = <hex>` line, as produced by BSD tools
+    static ref BSD_LINE_RX: Regex =
+        Regex::new(r"^(?P<algorithm>[A-Z0-9]+) \((?P<name>.+)\) = (?P<value>[0-9a-fA-F]+)$")
+            .unwrap();
+
+    // Matches a line containing only a checksum, as found in `<asset>.sha256` files
+    static ref VALUE_LINE_RX: Regex = Regex::new(r"^(?P<value>[0-9a-fA-F]+)$").unwrap();
+}
+
+/// Extensions of per-asset checksum files
+const ASSET_CHECKSUM_EXTENSIONS: &[&str] = &["sha256", "sha256sum", "sha512", "sha512sum"];
+
+/// Returns true if `name` is the name of a file containing checksums
+pub fn is_checksums_file_name(name: &str) -> bool {
+    let lname = name.to_ascii_lowercase();
+    CHECKSUMS_FILE_RX.is_match(&lname)
+        || ASSET_CHECKSUM_EXTENSIONS
+            .iter()
+            .any(|ext| lname.ends_with(&format!(".{ext}")))
+}
+
+/// Returns the URLs of the files from `urls` which may contain the checksum of `asset_url`
+pub fn find_checksums_urls(asset_url: &str, urls: &[String]) -> Vec<String> {
+    urls.iter()
+        .filter(|url| {
+            let Some((_, name)) = url.rsplit_once('/') else {
+                return false;
+            };
+            let lname = name.to_ascii_lowercase();
+            if CHECKSUMS_FILE_RX.is_match(&lname) {
+                return true;
+            }
+            ASSET_CHECKSUM_EXTENSIONS
+                .iter()
+                .any(|ext| **url == format!("{asset_url}.{ext}"))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Guess the algorithm used to produce `value`. `file_name` is the name of the checksums file.
+fn guess_algorithm(file_name: &str, value: &str) -> Option<HashAlgorithm> {
+    let file_name = file_name.to_ascii_lowercase();
+    if file_name.contains("b3sum") || file_name.contains("blake3") {
+        return Some(HashAlgorithm::Blake3);
+    }
+    match value.len() {
+        64 => Some(HashAlgorithm::Sha256),
+        128 => Some(HashAlgorithm::Sha512),
+        _ => None,
+    }
+}
+
+/// Parse the content of a checksums file, returns a map of file name => checksum.
+///
+/// Supports the GNU (`<hex>  <name>`) and BSD (`SHA256 (<name>) = <hex>`) formats. Lines
+/// containing only a checksum are attributed to `default_name`.
+pub fn parse_checksums_file(
+    file_name: &str,
+    content: &str,
+    default_name: &str,
+) -> HashMap<String, Checksum> {
+    let mut map = HashMap::new();
+    for line in content.lines() {
+        let line = line.trim();
+        let (name, value, algorithm) = if let Some(captures) = BSD_LINE_RX.captures(line) {
+            let algorithm = captures["algorithm"].to_ascii_lowercase().parse().ok();
+            (
+                captures["name"].to_string(),
+                captures["value"].to_string(),
+                algorithm,
+            )
+        } else if let Some(captures) = GNU_LINE_RX.captures(line) {
+            (
+                captures["name"].trim().to_string(),
+                captures["value"].to_string(),
+                None,
+            )
+        } else if let Some(captures) = VALUE_LINE_RX.captures(line) {
+            (
+                default_name.to_string(),
+                captures["value"].to_string(),
+                None,
+            )
+        } else {
+            continue;
+        };
+        let Some(algorithm) = algorithm.or_else(|| guess_algorithm(file_name, &value)) else {
+            continue;
+        };
+        // Names may contain a path, only keep the file name
+        let name = match name.rsplit_once('/') {
+            Some((_, x)) => x.to_string(),
+            None => name,
+        };
+        map.insert(name, Checksum::new(algorithm, &value.to_ascii_lowercase()));
+    }
+    map
+}
+
+/// Compare the checksum of the asset stored in `asset_path` with the checksums published
+/// upstream: the ones found in the checksums files of the release and the digest provided by the
+/// forge API, if any.
+pub fn verify_upstream_checksums(
+    ui: &Ui,
+    cache: &FileCache,
+    package_name: &str,
+    version: &Version,
+    asset_url: &str,
+    asset_path: &Path,
+    release: &UpstreamRelease,
+) -> Result<()> {
+    let (_, asset_name) = asset_url
+        .rsplit_once('/')
+        .ok_or_else(|| anyhow!("Can't find asset name in URL {}", asset_url))?;
+
+    let mut expected = Vec::<(String, Checksum)>::new();
+    if let Some(digest) = release.digests.get(asset_url) {
+        expected.push(("release digest".to_string(), digest.clone()));
+    }
+    for url in find_checksums_urls(asset_url, &release.urls) {
+        let path = cache.download(ui, package_name, version, &url)?;
+        let content = fs::read_to_string(&path)?;
+        let file_name = url.rsplit_once('/').map_or(url.as_str(), |x| x.1);
+        let checksums = parse_checksums_file(file_name, &content, asset_name);
+        if let Some(checksum) = checksums.get(asset_name) {
+            expected.push((url.clone(), checksum.clone()));
+        }
+    }
+
+    if expected.is_empty() {
+        ui.info("No upstream checksum found");
+        return Ok(());
+    }
+
+    let algorithms: Vec<_> = expected.iter().map(|(_, x)| x.algorithm).collect();
+    let actual = compute_checksums(asset_path, &algorithms)?;
+    for ((source, expected), actual) in expected.iter().zip(actual) {
+        if !expected.value.eq_ignore_ascii_case(&actual.value) {
+            return Err(anyhow!(
+                "{asset_name} does not match the checksum published in {source}.\nExpected: {expected}\nReceived: {actual}"
+            ));
+        }
+        ui.info(&format!("Checksum matches {source}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA256_1: &str = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+    const SHA256_2: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn parse_checksums_file_should_parse_gnu_format() {
+        let content = format!("{SHA256_1}  foo-linux.tar.gz\n{SHA256_2} *dist/foo-windows.zip\n");
+
+        let map = parse_checksums_file("SHA256SUMS", &content, "unused");
+
+        assert_eq!(
+            map,
+            HashMap::from([
+                (
+                    "foo-linux.tar.gz".to_string(),
+                    Checksum::new(HashAlgorithm::Sha256, SHA256_1)
+                ),
+                (
+                    "foo-windows.zip".to_string(),
+                    Checksum::new(HashAlgorithm::Sha256, SHA256_2)
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_checksums_file_should_parse_bsd_format() {
+        let content = format!("SHA256 (foo-linux.tar.gz) = {SHA256_1}\n");
+
+        let map = parse_checksums_file("checksums.txt", &content, "unused");
+
+        assert_eq!(
+            map,
+            HashMap::from([(
+                "foo-linux.tar.gz".to_string(),
+                Checksum::new(HashAlgorithm::Sha256, SHA256_1)
+            )])
+        );
+    }
+
+    #[test]
+    fn parse_checksums_file_should_use_default_name_for_single_checksum_files() {
+        let content = format!("{}\n", SHA256_1.to_ascii_uppercase());
+
+        let map = parse_checksums_file("foo-linux.tar.gz.sha256", &content, "foo-linux.tar.gz");
+
+        assert_eq!(
+            map,
+            HashMap::from([(
+                "foo-linux.tar.gz".to_string(),
+                Checksum::new(HashAlgorithm::Sha256, SHA256_1)
+            )])
+        );
+    }
+
+    #[test]
+    fn find_checksums_urls_should_find_release_and_asset_checksums_files() {
+        let urls: Vec<String> = [
+            "https://example.com/foo-linux.tar.gz",
+            "https://example.com/foo-linux.tar.gz.sha256",
+            "https://example.com/foo-macos.tar.gz",
+            "https://example.com/foo-macos.tar.gz.sha256",
+            "https://example.com/foo_1.2.3_checksums.txt",
+            "https://example.com/SHA256SUMS",
+            "https://example.com/README.txt",
+        ]
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+
+        let result = find_checksums_urls("https://example.com/foo-linux.tar.gz", &urls);
+
+        assert_eq!(
+            result,
+            vec![
+                "https://example.com/foo-linux.tar.gz.sha256".to_string(),
+                "https://example.com/foo_1.2.3_checksums.txt".to_string(),
+                "https://example.com/SHA256SUMS".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_upstream_checksums_should_reject_mismatching_digest() {
+        // GIVEN an asset
+        let dir = assert_fs::TempDir::new().unwrap();
+        let asset_path = dir.join("foo-linux.tar.gz");
+        fs::write(&asset_path, "hello\n").unwrap();
+        let cache = FileCache::new(&dir.join("cache"));
+        let version = Version::new(1, 0, 0);
+        let asset_url = "https://example.com/foo-linux.tar.gz";
+        let create_release = |value| UpstreamRelease {
+            urls: vec![asset_url.to_string()],
+            digests: HashMap::from([(
+                asset_url.to_string(),
+                Checksum::new(HashAlgorithm::Sha256, value),
+            )]),
+        };
+
+        // WHEN verifying it against a digest which does not match
+        let result = verify_upstream_checksums(
+            &Ui::default(),
+            &cache,
+            "foo",
+            &version,
+            asset_url,
+            &asset_path,
+            &create_release(SHA256_2),
+        );
+
+        // THEN it fails
+        assert!(result.is_err());
+
+        // AND it succeeds with the right digest
+        let result = verify_upstream_checksums(
+            &Ui::default(),
+            &cache,
+            "foo",
+            &version,
+            asset_url,
+            &asset_path,
+            &create_release(SHA256_1),
+        );
+        assert!(result.is_ok(), "{:?}", result);
+    }
+}
@@ -10,10 +10,12 @@ use anyhow::Error as AnyhowError;
 use anyhow::{anyhow, Result};
 use regex::Regex;
 
-use clyde::arch_os::{Arch, ArchOs, Os};
+use clyde::arch_os::{Arch, ArchOs, Libc, Os};
 use clyde::package::FetcherConfig;
 use clyde::ui::Ui;
 
+use crate::upstream_checksums::is_checksums_file_name;
+
 lazy_static! {
     // Order matters: x86_64 must be looked for before x86
     static ref ARCH_VEC: Vec<(&'static str, Arch)> = vec![
@@ -24,6 +26,12 @@ lazy_static! {
         ("i?[36]86", Arch::X86),
         ("aarch[-_]?64", Arch::Aarch64),
         ("arm64", Arch::Aarch64),
+        ("armv7(l|hf)?", Arch::Armv7),
+        ("armhf", Arch::Armv7),
+        ("riscv64(gc)?", Arch::Riscv64),
+        ("(ppc|powerpc)64le", Arch::Ppc64le),
+        ("ppc64el", Arch::Ppc64le),
+        ("s390x", Arch::S390x),
         ("32[-_]?bit", Arch::X86),
         ("64[-_]?bit", Arch::X86_64),
         ("universal", Arch::Any),
@@ -36,28 +44,36 @@ lazy_static! {
         ("mac", Os::MacOs),
         ("osx", Os::MacOs),
         ("win(|dows|32|64)", Os::Windows),
+        ("freebsd", Os::FreeBsd),
     ];
     static ref UNSUPPORTED_EXTS : HashSet<&'static str> = HashSet::from([
         "apk",
         "asc",
-        "deb",
+        "bundle",
         "dmg",
+        "minisig",
         "msi",
         "pkg",
-        "rpm",
         "sbom",
         "sh",
         "sha256",
         "sha256sum",
+        "sig",
         "txt",
         "zsync",
     ]);
 
-    // Packer extensions, ordered from worse to best
+    // Packer extensions, ordered from worse to best. Linux packages come first: they are only
+    // useful if the project does not provide a plain archive.
     static ref PACKER_EXTENSIONS: Vec<&'static str> = vec![
-        "zip", "gz", "bz2", "xz"
+        "appimage", "rpm", "deb", "tar", "7z", "lz", "zip", "gz", "bz2", "zst", "xz"
     ];
 
+    // Extensions of Linux-only packages, their names usually do not contain the OS
+    static ref LINUX_PACKAGE_EXTENSIONS: HashSet<&'static str> = HashSet::from([
+        "appimage", "deb", "rpm"
+    ]);
+
     // Libc names, ordered from worse to best. List msvc at the end because on Windows it tends to
     // produce smaller binaries
     static ref LIBC_NAMES: Vec<&'static str> = vec![
@@ -105,10 +121,28 @@ pub fn select_best_urls(
             })
             .or_insert(new_scored_url);
     }
-    Ok(best_urls
+    best_urls
+        .iter()
+        .map(|(arch_os, scored_url)| {
+            // Record the libc of Linux assets, so that Clyde does not install gnu builds on musl
+            // systems
+            let arch_os = if arch_os.os == Os::Linux {
+                arch_os.with_libc(get_libc(&get_lname(&scored_url.url)?))
+            } else {
+                *arch_os
+            };
+            Ok((arch_os, scored_url.url.clone()))
+        })
+        .collect()
+}
+
+/// Returns the URL of the signature of `asset_url`, if `urls` contains one. Signature URLs are
+/// expected to be named `<asset_url>.<ext>`, where `<ext>` is one of `extensions`.
+pub fn find_signature_url(asset_url: &str, urls: &[String], extensions: &[&str]) -> Option<String> {
+    extensions
         .iter()
-        .map(|(arch_os, scored_url)| (*arch_os, scored_url.url.clone()))
-        .collect())
+        .map(|ext| format!("{asset_url}.{ext}"))
+        .find(|signature_url| urls.contains(signature_url))
 }
 
 //- BestUrlOptions --------------------------------------------------
@@ -205,6 +239,16 @@ fn get_libc_score(name: &str) -> usize {
     }
 }
 
+fn get_libc(name: &str) -> Libc {
+    if name.contains("musl") {
+        Libc::Musl
+    } else if name.contains("gnu") {
+        Libc::Gnu
+    } else {
+        Libc::Any
+    }
+}
+
 /// Given two asset URLs, return the one we prefer to use
 fn select_best_url<'a>(ui: &Ui, u1: &'a ScoredUrl, u2: &'a ScoredUrl) -> &'a ScoredUrl {
     match u1.score.cmp(&u2.score) {
@@ -262,10 +306,16 @@ fn extract_arch_os(
         score -= 1;
         default_arch
     })?;
-    let os = find_in_iter(OS_VEC.iter(), name).or_else(|| {
-        score -= 1;
-        default_os
-    })?;
+    let os = find_in_iter(OS_VEC.iter(), name)
+        .or_else(|| {
+            get_file_extension(name)
+                .filter(|ext| LINUX_PACKAGE_EXTENSIONS.contains(ext))
+                .map(|_| Os::Linux)
+        })
+        .or_else(|| {
+            score -= 1;
+            default_os
+        })?;
     Some((ArchOs::new(arch, os), score))
 }
 
@@ -286,6 +336,9 @@ fn get_lname(url: &str) -> Result<String> {
 }
 
 fn is_supported_name(name: &str) -> bool {
+    if is_checksums_file_name(name) {
+        return false;
+    }
     let ext = match get_file_extension(name) {
         Some(x) => x,
         None => return true,
@@ -354,9 +407,53 @@ mod tests {
             "rclone-v1.61.1-osx-arm64.zip",
             Some(ArchOs::new(Arch::Aarch64, Os::MacOs)),
         );
+        check_extract_arch_os(
+            "foo-armv7-unknown-linux-gnueabihf.tar.gz",
+            Some(ArchOs::new(Arch::Armv7, Os::Linux)),
+        );
+        check_extract_arch_os(
+            "foo_linux_armhf.tar.gz",
+            Some(ArchOs::new(Arch::Armv7, Os::Linux)),
+        );
+        check_extract_arch_os(
+            "foo-riscv64gc-unknown-linux-gnu.tar.gz",
+            Some(ArchOs::new(Arch::Riscv64, Os::Linux)),
+        );
+        check_extract_arch_os(
+            "foo_linux_ppc64le.tar.gz",
+            Some(ArchOs::new(Arch::Ppc64le, Os::Linux)),
+        );
+        check_extract_arch_os(
+            "foo_linux_s390x.tar.gz",
+            Some(ArchOs::new(Arch::S390x, Os::Linux)),
+        );
+        check_extract_arch_os(
+            "foo-x86_64-unknown-freebsd.tar.gz",
+            Some(ArchOs::new(Arch::X86_64, Os::FreeBsd)),
+        );
         check_extract_arch_os("bar-3.14.tar.gz", None);
     }
 
+    #[test]
+    fn extract_arch_os_should_assume_linux_for_linux_packages() {
+        check_extract_arch_os(
+            "foo_1.2_amd64.deb",
+            Some(ArchOs::new(Arch::X86_64, Os::Linux)),
+        );
+        check_extract_arch_os(
+            "foo_1.2_ppc64el.deb",
+            Some(ArchOs::new(Arch::Ppc64le, Os::Linux)),
+        );
+        check_extract_arch_os(
+            "foo-1.2-1.aarch64.rpm",
+            Some(ArchOs::new(Arch::Aarch64, Os::Linux)),
+        );
+        check_extract_arch_os(
+            "foo-1.2-x86_64.appimage",
+            Some(ArchOs::new(Arch::X86_64, Os::Linux)),
+        );
+    }
+
     #[test]
     fn test_extract_arch_os_default_values() {
         let result = extract_arch_os("ninja-windows.zip", Some(Arch::X86_64), None);
@@ -375,9 +472,10 @@ mod tests {
         assert!(is_supported_name("foo.gz"));
         assert!(is_supported_name("foo.exe.xz"));
         assert!(is_supported_name("foo.bz2"));
+        assert!(is_supported_name("foo.deb"));
+        assert!(is_supported_name("foo.rpm"));
+        assert!(is_supported_name("foo.appimage"));
 
-        assert!(!is_supported_name("foo.deb"));
-        assert!(!is_supported_name("foo.rpm"));
         assert!(!is_supported_name("foo.msi"));
     }
 
@@ -436,6 +534,75 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn select_best_urls_should_record_the_libc_of_linux_assets() {
+        let ui = Ui::default();
+
+        // GIVEN gnu and musl builds for Linux, and a Windows build
+        let urls = [
+            "https://example.com/foo-x86_64-unknown-linux-gnu.tar.gz".to_string(),
+            "https://example.com/foo-x86_64-unknown-linux-musl.tar.gz".to_string(),
+            "https://example.com/foo-aarch64-unknown-linux-gnu.tar.gz".to_string(),
+            "https://example.com/foo-x86_64-pc-windows-msvc.zip".to_string(),
+        ];
+
+        // WHEN calling select_best_urls() on them
+        let result = select_best_urls(&ui, &urls, BestUrlOptions::default()).unwrap();
+
+        // THEN the musl build is preferred, and the libc is part of the arch-os of Linux assets
+        let expected = HashMap::from([
+            (
+                ArchOs::new(Arch::X86_64, Os::Linux).with_libc(Libc::Musl),
+                urls[1].clone(),
+            ),
+            (
+                ArchOs::new(Arch::Aarch64, Os::Linux).with_libc(Libc::Gnu),
+                urls[2].clone(),
+            ),
+            (ArchOs::new(Arch::X86_64, Os::Windows), urls[3].clone()),
+        ]);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn select_best_urls_should_prefer_compressed_tar_archives() {
+        let ui = Ui::default();
+
+        // GIVEN the same Linux build, as a plain tar, a .tar.zst and a .7z archive
+        let urls = [
+            "https://example.com/foo-x86_64-linux.tar".to_string(),
+            "https://example.com/foo-x86_64-linux.tar.zst".to_string(),
+            "https://example.com/foo-x86_64-linux.7z".to_string(),
+        ];
+
+        // WHEN calling select_best_urls() on them
+        let result = select_best_urls(&ui, &urls, BestUrlOptions::default()).unwrap();
+
+        // THEN the .tar.zst archive is selected
+        let expected = HashMap::from([(ArchOs::new(Arch::X86_64, Os::Linux), urls[1].clone())]);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn select_best_urls_should_prefer_archives_to_linux_packages() {
+        let ui = Ui::default();
+
+        // GIVEN the same Linux build, as a .deb, an .rpm, an AppImage and a .tar.gz archive
+        let urls = [
+            "https://example.com/foo_1.2_amd64.deb".to_string(),
+            "https://example.com/foo-1.2-1.x86_64.rpm".to_string(),
+            "https://example.com/Foo-1.2-x86_64.AppImage".to_string(),
+            "https://example.com/foo-1.2-x86_64-linux.tar.gz".to_string(),
+        ];
+
+        // WHEN calling select_best_urls() on them
+        let result = select_best_urls(&ui, &urls, BestUrlOptions::default()).unwrap();
+
+        // THEN the .tar.gz archive is selected
+        let expected = HashMap::from([(ArchOs::new(Arch::X86_64, Os::Linux), urls[3].clone())]);
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn test_select_best_urls_prefers_not_using_default_arch_os() {
         let ui = Ui::default();
@@ -462,4 +629,32 @@ mod tests {
         )]);
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn find_signature_url_should_find_url_with_signature_extension() {
+        let urls = [
+            "https://example.com/foo/foo-x86_64-linux.tar.gz".to_string(),
+            "https://example.com/foo/foo-x86_64-linux.tar.gz.sig".to_string(),
+            "https://example.com/foo/foo-x86_64-linux.tar.gz.asc".to_string(),
+            "https://example.com/foo/foo-aarch64-linux.tar.gz".to_string(),
+        ];
+
+        // Extensions are tried in order
+        assert_eq!(
+            find_signature_url(
+                "https://example.com/foo/foo-x86_64-linux.tar.gz",
+                &urls,
+                &["asc", "sig"]
+            ),
+            Some("https://example.com/foo/foo-x86_64-linux.tar.gz.asc".to_string())
+        );
+        assert_eq!(
+            find_signature_url(
+                "https://example.com/foo/foo-aarch64-linux.tar.gz",
+                &urls,
+                &["asc", "sig"]
+            ),
+            None
+        );
+    }
 }
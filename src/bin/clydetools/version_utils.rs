@@ -4,7 +4,8 @@
 
 use anyhow::{anyhow, Result};
 use regex::{Regex, RegexBuilder};
-use semver::Version;
+
+use clyde::version::{Version, VersionScheme};
 
 lazy_static! {
     static ref VERSION_RX: Regex = RegexBuilder::new(
@@ -24,14 +25,30 @@ lazy_static! {
     .build()
     .unwrap();
     static ref LEADING_0_RX: Regex = Regex::new(r"(^|\.)0+([0-9])").unwrap();
+    static ref CALVER_RX: Regex = Regex::new(r"[0-9]+([-._][0-9]+)*$").unwrap();
+    static ref DOTTED_RX: Regex = Regex::new(r"[0-9]+(\.[0-9]+)*$").unwrap();
 }
 
 fn count_chars(txt: &str, wanted: char) -> usize {
     txt.chars().filter(|&x| x == wanted).count()
 }
 
-/// Extract a version from a tag
-pub fn version_from_tag(tag: &str) -> Result<Version> {
+/// Extract a version from a tag. Semver versions are normalized, versions using other schemes
+/// are kept as is. For the opaque scheme, the whole tag is the version.
+pub fn version_from_tag(tag: &str, scheme: VersionScheme) -> Result<Version> {
+    let rx: &Regex = match scheme {
+        VersionScheme::Semver => return semver_from_tag(tag),
+        VersionScheme::Calver => &CALVER_RX,
+        VersionScheme::Dotted => &DOTTED_RX,
+        VersionScheme::Opaque => return Version::parse_with_scheme(tag, scheme),
+    };
+    let version_str = rx
+        .find(tag)
+        .ok_or_else(|| anyhow!("Can't find version number in '{}'", tag))?;
+    Version::parse_with_scheme(version_str.as_str(), scheme)
+}
+
+fn semver_from_tag(tag: &str) -> Result<Version> {
     // Keep only the version suffix
     let version_str = VERSION_RX
         .captures(tag)
@@ -74,11 +91,31 @@ mod tests {
     )]
     fn check_version_from_tag_success(tag: &str, expected_str: &str) {
         let expected = Version::parse(expected_str).unwrap();
-        assert_eq!(version_from_tag(tag).unwrap(), expected);
+        assert_eq!(
+            version_from_tag(tag, VersionScheme::Semver).unwrap(),
+            expected
+        );
+    }
+
+    #[parameterized(
+        calver = { "v2024.01.05", VersionScheme::Calver, "2024.01.05" },
+        calver_dashes = { "release-2024-01-05", VersionScheme::Calver, "2024-01-05" },
+        dotted = { "foo-1.02.3.4", VersionScheme::Dotted, "1.02.3.4" },
+        opaque = { "r1234", VersionScheme::Opaque, "r1234" },
+    )]
+    fn version_from_tag_should_keep_non_semver_versions_intact(
+        tag: &str,
+        scheme: VersionScheme,
+        expected_str: &str,
+    ) {
+        let version = version_from_tag(tag, scheme).unwrap();
+        assert_eq!(version.to_string(), expected_str);
+        assert_eq!(version.scheme(), scheme);
     }
 
     #[test]
     fn check_version_from_tag_fails() {
-        assert!(version_from_tag("foo").is_err());
+        assert!(version_from_tag("foo", VersionScheme::Semver).is_err());
+        assert!(version_from_tag("foo", VersionScheme::Calver).is_err());
     }
 }
@@ -2,31 +2,295 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::fmt;
 use std::fs::File;
-use std::io;
+use std::io::{self, Read};
 use std::path::Path;
+use std::str::FromStr;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Error, Result};
 use hex;
-use sha2::{digest::DynDigest, Sha256};
+use sha2::{digest::DynDigest, Sha256, Sha512};
 
-pub fn compute_checksum(path: &Path) -> Result<String> {
+/// The hash algorithms supported in package files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// The length of the hex digest
+    fn hex_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 64,
+            HashAlgorithm::Sha512 => 128,
+            HashAlgorithm::Blake3 => 64,
+        }
+    }
+
+    fn create_hasher(&self) -> Hasher {
+        match self {
+            HashAlgorithm::Sha256 => Hasher::Digest(Box::<Sha256>::default()),
+            HashAlgorithm::Sha512 => Hasher::Digest(Box::<Sha512>::default()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::default()),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            _ => Err(anyhow!("Unsupported hash algorithm '{name}'")),
+        }
+    }
+}
+
+/// A checksum, serialized as `<algorithm>:<hex digest>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Checksum {
+    pub algorithm: HashAlgorithm,
+    pub value: String,
+}
+
+impl Checksum {
+    pub fn new(algorithm: HashAlgorithm, value: &str) -> Self {
+        Checksum {
+            algorithm,
+            value: value.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm, self.value)
+    }
+}
+
+impl FromStr for Checksum {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let (name, value) = text
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Checksum '{text}' must be of the form <algorithm>:<value>"))?;
+        let algorithm = HashAlgorithm::from_str(name)?;
+        if value.is_empty() {
+            return Err(anyhow!("Checksum '{text}' has no value"));
+        }
+        if !value.chars().all(|x| x.is_ascii_hexdigit()) {
+            return Err(anyhow!("Checksum '{text}' is not an hexadecimal value"));
+        }
+        if value.len() != algorithm.hex_len() {
+            return Err(anyhow!(
+                "Checksum '{text}' has an invalid length: {algorithm} checksums are {} characters long",
+                algorithm.hex_len()
+            ));
+        }
+        Ok(Checksum::new(algorithm, value))
+    }
+}
+
+/// Wraps the hasher implementations so that they can all be fed from the same read loop
+enum Hasher {
+    Digest(Box<dyn DynDigest>),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Digest(x) => x.update(data),
+            Hasher::Blake3(x) => {
+                x.update(data);
+            }
+        }
+    }
+
+    fn finalize(self) -> String {
+        match self {
+            Hasher::Digest(mut x) => hex::encode(x.finalize_reset()),
+            Hasher::Blake3(x) => x.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// Compute the checksums of `path` for all `algorithms`, reading the file only once
+pub fn compute_checksums(path: &Path, algorithms: &[HashAlgorithm]) -> Result<Vec<Checksum>> {
     let mut file = File::open(path)?;
-    let mut hasher = Sha256::default();
-    io::copy(&mut file, &mut hasher)?;
-    let checksum = hex::encode(hasher.finalize_reset());
-    Ok(checksum)
+    let mut hashers: Vec<_> = algorithms.iter().map(|x| x.create_hasher()).collect();
+
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let count = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(x) => x,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        for hasher in hashers.iter_mut() {
+            hasher.update(&buffer[..count]);
+        }
+    }
+
+    Ok(algorithms
+        .iter()
+        .zip(hashers)
+        .map(|(algorithm, hasher)| Checksum::new(*algorithm, &hasher.finalize()))
+        .collect())
 }
 
-pub fn verify_checksum(path: &Path, expected: &str) -> Result<()> {
-    let actual = compute_checksum(path)?;
+/// Compute the SHA-256 checksum of `path`
+pub fn compute_checksum(path: &Path) -> Result<String> {
+    let checksums = compute_checksums(path, &[HashAlgorithm::Sha256])?;
+    Ok(checksums[0].value.clone())
+}
 
-    if actual != expected {
-        return Err(anyhow!(
-            "Checksums do not match.\nExpected: {}\nReceived: {}",
-            expected,
-            actual
-        ));
+/// Verify `path` matches all the `expected` checksums
+pub fn verify_checksums(path: &Path, expected: &[Checksum]) -> Result<()> {
+    if expected.is_empty() {
+        return Err(anyhow!("No checksum to verify {}", path.display()));
+    }
+    let algorithms: Vec<_> = expected.iter().map(|x| x.algorithm).collect();
+    let actual = compute_checksums(path, &algorithms)?;
+
+    for (expected, actual) in expected.iter().zip(actual) {
+        if !expected.value.eq_ignore_ascii_case(&actual.value) {
+            return Err(anyhow!(
+                "{} checksums do not match.\nExpected: {}\nReceived: {}",
+                expected.algorithm,
+                expected.value,
+                actual.value
+            ));
+        }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    // Checksums of "hello\n"
+    const HELLO_SHA256: &str = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+    const HELLO_SHA512: &str = "e7c22b994c59d9cf2b48e549b1e24666636045930d3da7c1acb299d1c3b7f931f94aae41edda2c2b207a36e10f8bcb8d45223e54878f5b316e7ce3b6bc019629";
+    const HELLO_BLAKE3: &str = "8e4c7c1b99dbfd50e7a95185fead5ee1448fa904a2fdd778eaf5f2dbfd629a99";
+
+    fn create_hello_file(dir: &Path) -> std::path::PathBuf {
+        let path = dir.join("hello");
+        fs::write(&path, "hello\n").unwrap();
+        path
+    }
+
+    #[test]
+    fn checksum_from_str_should_parse_typed_checksums() {
+        assert_eq!(
+            Checksum::from_str(&format!("sha512:{HELLO_SHA512}")).unwrap(),
+            Checksum::new(HashAlgorithm::Sha512, HELLO_SHA512)
+        );
+        assert_eq!(
+            Checksum::from_str(&format!("blake3:{HELLO_BLAKE3}")).unwrap(),
+            Checksum::new(HashAlgorithm::Blake3, HELLO_BLAKE3)
+        );
+    }
+
+    #[test]
+    fn checksum_from_str_should_reject_invalid_checksums() {
+        assert!(Checksum::from_str("abcd").is_err());
+        assert!(Checksum::from_str("md5:abcd").is_err());
+        assert!(Checksum::from_str("sha256:").is_err());
+    }
+
+    #[test]
+    fn checksum_from_str_should_reject_invalid_values() {
+        // Not hexadecimal
+        let not_hex = format!("{}xyz", &HELLO_SHA256[3..]);
+        assert!(Checksum::from_str(&format!("sha256:{not_hex}")).is_err());
+
+        // Too short
+        assert!(Checksum::from_str("sha256:abcd").is_err());
+
+        // Length of another algorithm
+        assert!(Checksum::from_str(&format!("sha512:{HELLO_SHA256}")).is_err());
+    }
+
+    #[test]
+    fn compute_checksums_should_compute_all_algorithms() {
+        // GIVEN a file
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = create_hello_file(&dir);
+
+        // WHEN compute_checksums() is called with all algorithms
+        let checksums = compute_checksums(
+            &path,
+            &[
+                HashAlgorithm::Sha256,
+                HashAlgorithm::Sha512,
+                HashAlgorithm::Blake3,
+            ],
+        )
+        .unwrap();
+
+        // THEN all checksums are correct
+        assert_eq!(
+            checksums,
+            vec![
+                Checksum::new(HashAlgorithm::Sha256, HELLO_SHA256),
+                Checksum::new(HashAlgorithm::Sha512, HELLO_SHA512),
+                Checksum::new(HashAlgorithm::Blake3, HELLO_BLAKE3),
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_checksums_should_fail_if_one_checksum_does_not_match() {
+        // GIVEN a file
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = create_hello_file(&dir);
+
+        // WHEN verify_checksums() is called with a valid sha256 and an invalid sha512
+        let result = verify_checksums(
+            &path,
+            &[
+                Checksum::new(HashAlgorithm::Sha256, HELLO_SHA256),
+                Checksum::new(HashAlgorithm::Sha512, "1234"),
+            ],
+        );
+
+        // THEN it fails
+        assert!(result.is_err());
+
+        // AND it succeeds if both are valid
+        let result = verify_checksums(
+            &path,
+            &[
+                Checksum::new(HashAlgorithm::Sha256, HELLO_SHA256),
+                Checksum::new(HashAlgorithm::Sha512, HELLO_SHA512),
+            ],
+        );
+        assert!(result.is_ok(), "{:?}", result);
+    }
+}
@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 /// This file must build standalone because it's used by `build.rs` to generate shell
 /// auto-completion files
@@ -10,17 +10,49 @@ use clap::{Parser, Subcommand};
 #[derive(Debug, Parser)]
 #[command(name = "clyde", version, about)]
 pub struct Cli {
+    /// Output format. The `json` format prints a stream of JSON events on stdout, one per line.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Shells Clyde can create activation scripts for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Sh,
+    Bash,
+    Zsh,
+    Fish,
+    #[value(alias = "nushell")]
+    Nu,
+    #[value(name = "powershell", alias = "pwsh")]
+    PowerShell,
+    Elvish,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Command {
     /// Setup Clyde
     Setup {
-        /// Update the activation scripts of an existing installation.
+        /// Update the activation scripts of an existing installation, for all supported shells.
         #[arg(short, long)]
         update_scripts: bool,
+        /// Add a block loading the activation script to the startup scripts of the shells you use.
+        /// Can be run again on an existing installation.
+        #[arg(long, conflicts_with = "unmodify_rc")]
+        modify_rc: bool,
+        /// Remove the block added by `--modify-rc` from the startup scripts of all shells
+        #[arg(long)]
+        unmodify_rc: bool,
         /// URL of the Git repository to use for the store.
         #[arg(long = "url")]
         store_url: Option<String>,
@@ -32,6 +64,18 @@ pub enum Command {
         /// Uninstall then reinstall already installed packages
         #[arg(short, long)]
         reinstall: bool,
+        /// Allow installing pre-releases. Upgrades of the applications will also consider
+        /// pre-releases.
+        #[arg(long, conflicts_with = "no_pre")]
+        pre: bool,
+        /// Stop considering pre-releases for already installed applications. Without `--pre` or
+        /// `--no-pre`, installed applications keep their setting.
+        #[arg(long)]
+        no_pre: bool,
+        /// Install applications for another arch-os, like `aarch64-linux`, in a separate Clyde
+        /// home. Defaults to $CLYDE_ARCH_OS if set.
+        #[arg(long, value_name = "ARCH_OS")]
+        arch_os: Option<String>,
         /// Application name, optionally suffixed with @version
         ///
         /// @version must follow Cargo's interpretation of Semantic Versioning:
@@ -39,6 +83,22 @@ pub enum Command {
         #[arg(required = true, value_name = "APPLICATION_NAME")]
         package_names: Vec<String>,
     },
+    /// Download the assets of applications without installing them, and print their paths
+    Fetch {
+        /// Allow fetching pre-releases
+        #[arg(long)]
+        pre: bool,
+        /// Fetch assets for another arch-os, like `aarch64-linux`. Defaults to $CLYDE_ARCH_OS if
+        /// set.
+        #[arg(long, value_name = "ARCH_OS")]
+        arch_os: Option<String>,
+        /// Copy the assets to this directory
+        #[arg(short, long, value_name = "DIR")]
+        output_dir: Option<std::path::PathBuf>,
+        /// Application name, optionally suffixed with @version
+        #[arg(required = true, value_name = "APPLICATION_NAME")]
+        package_names: Vec<String>,
+    },
     /// Uninstall applications (alias: remove)
     #[command(alias("remove"))]
     Uninstall {
@@ -54,6 +114,10 @@ pub enum Command {
         /// Use JSON output
         #[arg(short, long)]
         json: bool,
+        /// Show the installed version and available assets for another arch-os. Defaults to
+        /// $CLYDE_ARCH_OS if set.
+        #[arg(long, value_name = "ARCH_OS")]
+        arch_os: Option<String>,
         /// Application name
         package_name: String,
     },
@@ -73,6 +137,77 @@ pub enum Command {
         #[arg(short, long)]
         json: bool,
     },
-    /// Upgrade all installed applications, enforcing pinning
-    Upgrade {},
+    /// Upgrade installed applications, enforcing pinning
+    Upgrade {
+        /// Do not upgrade this application. Can be used multiple times.
+        #[arg(short = 'x', long = "exclude", value_name = "APPLICATION_NAME")]
+        excludes: Vec<String>,
+        /// Upgrade pinned applications to their latest version, removing their pin
+        #[arg(long)]
+        ignore_pins: bool,
+        /// Applications to upgrade. If not set, upgrade all installed applications.
+        #[arg(value_name = "APPLICATION_NAME")]
+        package_names: Vec<String>,
+    },
+    /// List installed applications which can be upgraded
+    ///
+    /// Exits with code 100 if at least one application can be upgraded.
+    Outdated {
+        /// Use JSON output
+        #[arg(short, long)]
+        json: bool,
+    },
+    /// Pin an installed application, so that `upgrade` only installs matching versions
+    Pin {
+        /// Application name, optionally suffixed with @version. If @version is not set, the
+        /// application is pinned to its installed version.
+        #[arg(value_name = "APPLICATION_NAME")]
+        package_name: String,
+    },
+    /// Remove the pin of installed applications
+    Unpin {
+        /// Application name
+        #[arg(required = true, value_name = "APPLICATION_NAME")]
+        package_names: Vec<String>,
+    },
+    /// Downgrade an application to the version preceding the installed one, and pin it
+    Downgrade {
+        /// Application name, optionally suffixed with @version to select the version to downgrade
+        /// to
+        #[arg(value_name = "APPLICATION_NAME")]
+        package_name: String,
+    },
+    /// Print the environment variables defined by installed applications, as shell commands
+    Env {
+        /// Shell to print the commands for
+        #[arg(long, value_enum, default_value_t = Shell::Sh)]
+        shell: Shell,
+    },
+    /// Print the activation script for a shell
+    ///
+    /// For example, add `eval "$(clyde shell-init bash)"` to your `.bashrc`, or
+    /// `clyde shell-init fish | source` to your `config.fish`.
+    ShellInit {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Check the health of the Clyde installation
+    ///
+    /// Exits with an error if at least one check failed.
+    Doctor {
+        /// Fix the problems which can be fixed
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Show the installed application owning a file or a command
+    Owns {
+        /// Path of the file, or name of a command installed by Clyde
+        #[arg(value_name = "PATH_OR_COMMAND")]
+        path_or_command: String,
+    },
+    /// List the applications providing a command
+    Provides {
+        /// Command name
+        command: String,
+    },
 }
@@ -14,6 +14,7 @@ use shell_words;
 use crate::app::App;
 use crate::ctrlcutils::{disable_ctrlc_handler, CursorRestorer};
 use crate::pager::find_pager;
+use crate::ui::Ui;
 
 #[derive(Debug, Clone, Copy)]
 enum DocApp {
@@ -147,7 +148,10 @@ fn open_doc_file(doc_file: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn doc_cmd(app: &App, package_name: &str) -> Result<()> {
+pub fn doc_cmd(app: &App, ui: &Ui, package_name: &str) -> Result<()> {
+    if ui.is_json() {
+        return Err(anyhow!("The doc command does not support JSON output"));
+    }
     disable_ctrlc_handler();
     let _cursor_restorer = CursorRestorer::new();
     let db = &app.database;
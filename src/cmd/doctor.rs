@@ -0,0 +1,755 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashSet;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use which::which;
+
+use crate::app::{App, DATABASE_FILE_NAME};
+use crate::arch_os::ArchOs;
+use crate::cmd::{install_package, InstallRequest};
+use crate::db::{Database, SCHEMA_VERSION};
+use crate::file_utils::path_exists;
+use crate::shell::ACTIVATION_SHELLS;
+use crate::ui::{Event, Ui};
+use crate::version::VersionReq;
+
+const PARTIAL_EXTENSION: &str = "partial";
+
+/// Name of the dir, in Clyde home, where `clyde doctor --fix` moves untracked files
+const QUARANTINE_DIR_NAME: &str = "quarantine";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// The result of a check. `fixable` is true if `clyde doctor --fix` can fix the problem.
+struct CheckResult {
+    name: &'static str,
+    status: Status,
+    message: String,
+    details: Vec<String>,
+    fixable: bool,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, message: &str) -> Self {
+        CheckResult {
+            name,
+            status: Status::Pass,
+            message: message.to_string(),
+            details: vec![],
+            fixable: false,
+        }
+    }
+
+    fn warn(name: &'static str, message: &str) -> Self {
+        CheckResult {
+            status: Status::Warn,
+            ..CheckResult::pass(name, message)
+        }
+    }
+
+    fn fail(name: &'static str, message: &str) -> Self {
+        CheckResult {
+            status: Status::Fail,
+            ..CheckResult::pass(name, message)
+        }
+    }
+
+    fn with_details<T: AsRef<Path>>(mut self, paths: &[T]) -> Self {
+        self.details = paths
+            .iter()
+            .map(|x| x.as_ref().display().to_string())
+            .collect();
+        self
+    }
+
+    fn with_fixable(mut self) -> Self {
+        self.fixable = true;
+        self
+    }
+}
+
+/// Returns true if `dir` is one of the directories of `path_var`
+fn is_dir_in_path_var(dir: &Path, path_var: &OsStr) -> bool {
+    let dir = dir.canonicalize().unwrap_or(dir.to_path_buf());
+    env::split_paths(path_var).any(|x| x.canonicalize().unwrap_or(x) == dir)
+}
+
+fn check_path(app: &App) -> CheckResult {
+    const NAME: &str = "path";
+    let bin_dir = app.install_dir.join("bin");
+    let path_var = env::var_os("PATH").unwrap_or_default();
+    if is_dir_in_path_var(&bin_dir, &path_var) {
+        CheckResult::pass(NAME, &format!("{bin_dir:?} is in PATH"))
+    } else {
+        CheckResult::fail(
+            NAME,
+            &format!("{bin_dir:?} is not in PATH. Make sure your shell startup script loads the activation script, for example with `clyde setup --modify-rc`."),
+        )
+    }
+}
+
+fn check_activation_scripts(app: &App, fix: bool) -> Result<CheckResult> {
+    const NAME: &str = "activation-scripts";
+    let scripts_dir = app.home.join("scripts");
+    let mut outdated = Vec::new();
+    for shell in ACTIVATION_SHELLS {
+        let path = scripts_dir.join(shell.activation_script_name());
+        let expected = shell.create_activation_script(&app.home)?;
+        if fs::read_to_string(&path).ok().as_ref() == Some(&expected) {
+            continue;
+        }
+        if fix {
+            fs::create_dir_all(&scripts_dir)?;
+            fs::write(&path, expected)?;
+        } else {
+            outdated.push(path);
+        }
+    }
+    let result = if outdated.is_empty() {
+        CheckResult::pass(NAME, "Activation scripts are up to date")
+    } else {
+        CheckResult::warn(NAME, "Some activation scripts are missing or outdated")
+            .with_details(&outdated)
+            .with_fixable()
+    };
+    Ok(result)
+}
+
+fn check_git() -> CheckResult {
+    const NAME: &str = "git";
+    match which("git") {
+        Ok(path) => CheckResult::pass(NAME, &format!("git is available ({path:?})")),
+        Err(_) => CheckResult::fail(
+            NAME,
+            "git is not installed, it's required to update the store",
+        ),
+    }
+}
+
+fn check_store(app: &App) -> CheckResult {
+    const NAME: &str = "store";
+    if !app.store_dir.join(".git").exists() {
+        return CheckResult::fail(
+            NAME,
+            &format!(
+                "{:?} is not a Git repository. Remove it and clone the store again.",
+                app.store_dir
+            ),
+        );
+    }
+    let is_valid = Command::new("git")
+        .arg("rev-parse")
+        .arg("--verify")
+        .arg("--quiet")
+        .arg("HEAD")
+        .current_dir(&app.store_dir)
+        .output()
+        .is_ok_and(|x| x.status.success());
+    if is_valid {
+        CheckResult::pass(NAME, "The store is a valid Git clone")
+    } else {
+        CheckResult::fail(
+            NAME,
+            &format!(
+                "{:?} is not a valid Git clone. Remove it and clone the store again.",
+                app.store_dir
+            ),
+        )
+    }
+}
+
+/// The state of the database, read before creating the app, since `App::new()` upgrades it
+enum DatabaseState {
+    NotCreated,
+    Unreadable(String),
+    Corrupted(Vec<String>),
+    Valid { schema_version: i32 },
+}
+
+/// Reads the state of the database at `db_path`, without modifying it
+fn read_database_state(db_path: &Path) -> DatabaseState {
+    if !db_path.exists() {
+        return DatabaseState::NotCreated;
+    }
+    let read_state = || -> Result<DatabaseState> {
+        let database = Database::new_read_only(db_path)?;
+        if !database.is_created()? {
+            return Ok(DatabaseState::NotCreated);
+        }
+        let problems = database.check_integrity()?;
+        if !problems.is_empty() {
+            return Ok(DatabaseState::Corrupted(problems));
+        }
+        Ok(DatabaseState::Valid {
+            schema_version: database.get_schema_version()?,
+        })
+    };
+    read_state().unwrap_or_else(|err| DatabaseState::Unreadable(format!("{err:#}")))
+}
+
+/// Checks the database, using its state before `app` was created
+fn check_database(state: &DatabaseState, app: &Result<App>) -> CheckResult {
+    const NAME: &str = "database";
+    let version = match state {
+        DatabaseState::NotCreated => {
+            // The database of a foreign arch-os home is created with the app
+            return match app {
+                Ok(app) if app.database.is_created().unwrap_or(false) => {
+                    CheckResult::pass(NAME, "Database has been created")
+                }
+                _ => CheckResult::fail(
+                    NAME,
+                    "The database has not been created. Remove Clyde home and run `clyde setup` again.",
+                ),
+            };
+        }
+        DatabaseState::Unreadable(err) => {
+            return CheckResult::fail(NAME, &format!("Failed to read the database: {err}"));
+        }
+        DatabaseState::Corrupted(problems) => {
+            return CheckResult::fail(NAME, "The database is corrupted").with_details(problems);
+        }
+        DatabaseState::Valid { schema_version } => *schema_version,
+    };
+    if version == SCHEMA_VERSION {
+        CheckResult::pass(NAME, &format!("Database schema is up to date ({version})"))
+    } else if version > SCHEMA_VERSION {
+        CheckResult::fail(
+            NAME,
+            &format!("Database schema ({version}) is newer than the one of this version of Clyde ({SCHEMA_VERSION}). Upgrade Clyde."),
+        )
+    } else {
+        match app {
+            Ok(_) => CheckResult::pass(
+                NAME,
+                &format!("Database schema has been upgraded from {version} to {SCHEMA_VERSION}"),
+            ),
+            Err(err) => CheckResult::fail(
+                NAME,
+                &format!("Database schema ({version}) is older than the one of this version of Clyde ({SCHEMA_VERSION}), and could not be upgraded: {err:#}"),
+            ),
+        }
+    }
+}
+
+/// Creates the app, like the other commands do
+fn create_app(home: &Path, arch_os: ArchOs) -> Result<App> {
+    App::new(home)?.with_arch_os(arch_os)
+}
+
+/// Returns the installed packages which have missing files, with the missing files
+fn find_missing_files(app: &App) -> Result<Vec<(String, Vec<PathBuf>)>> {
+    let mut missing = Vec::new();
+    for info in app.database.get_installed_packages()? {
+        let mut files: Vec<PathBuf> = app
+            .database
+            .get_package_files(&info.name)?
+            .into_iter()
+            .filter(|x| !path_exists(&app.install_dir.join(x)))
+            .collect();
+        if !files.is_empty() {
+            files.sort();
+            missing.push((info.name, files));
+        }
+    }
+    Ok(missing)
+}
+
+/// Reinstalls the installed version of `package_name`, keeping its requested version
+fn reinstall_package(app: &App, ui: &Ui, package_name: &str) -> Result<()> {
+    let info = app
+        .database
+        .get_installed_packages()?
+        .into_iter()
+        .find(|x| x.name == package_name)
+        .ok_or_else(|| anyhow!("Package {package_name} is not installed"))?;
+    let request = InstallRequest::new(&info.name, VersionReq::exact(&info.installed_version))
+        .with_allow_prereleases(info.allow_prereleases);
+    install_package(app, ui, true /* reinstall */, &request)?;
+    app.database
+        .set_requested_version(&info.name, &info.requested_version)
+}
+
+fn check_installed_files(app: &App, ui: &Ui, fix: bool) -> Result<CheckResult> {
+    const NAME: &str = "installed-files";
+    let mut missing = find_missing_files(app)?;
+    if fix {
+        let mut still_missing = Vec::new();
+        for (package_name, files) in missing {
+            ui.info(&format!("Reinstalling {package_name}"));
+            if let Err(err) = reinstall_package(app, &ui.nest(), &package_name) {
+                ui.error(&format!("Failed to reinstall {package_name}: {err:#}"));
+                still_missing.push((package_name, files));
+            }
+        }
+        missing = still_missing;
+    }
+    if missing.is_empty() {
+        return Ok(CheckResult::pass(
+            NAME,
+            "All the files of the installed packages exist",
+        ));
+    }
+    let names: Vec<&str> = missing.iter().map(|(name, _)| name.as_str()).collect();
+    let paths: Vec<PathBuf> = missing
+        .iter()
+        .flat_map(|(_, files)| files.iter().map(|x| app.install_dir.join(x)))
+        .collect();
+    Ok(CheckResult::fail(
+        NAME,
+        &format!(
+            "Some files of these packages are missing: {}",
+            names.join(", ")
+        ),
+    )
+    .with_details(&paths)
+    .with_fixable())
+}
+
+/// Returns the paths of all the files and symlinks in `dir`, relative to `root`
+fn list_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to list {dir:?}"))? {
+        let path = entry?.path();
+        if path.is_dir() && !path.is_symlink() {
+            list_files(root, &path, files)?;
+        } else {
+            files.push(path.strip_prefix(root)?.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the files of the install dir which do not belong to any installed package
+fn find_untracked_files(app: &App) -> Result<Vec<PathBuf>> {
+    if !app.install_dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut tracked = HashSet::new();
+    for info in app.database.get_installed_packages()? {
+        tracked.extend(app.database.get_package_files(&info.name)?);
+    }
+    let mut files = Vec::new();
+    list_files(&app.install_dir, &app.install_dir, &mut files)?;
+    let mut untracked: Vec<PathBuf> = files.into_iter().filter(|x| !tracked.contains(x)).collect();
+    untracked.sort();
+    Ok(untracked)
+}
+
+/// Removes the parent dirs of `path` if they are empty, stopping at `root`
+fn remove_empty_parents(root: &Path, path: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == root || fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// Moves the untracked files of the install dir to a new quarantine dir, keeping their
+/// relative path, and returns this dir. The files could have been created by the user, so they
+/// must not be deleted.
+fn quarantine_untracked_files(app: &App, untracked: &[PathBuf]) -> Result<PathBuf> {
+    let timestamp = Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string();
+    let quarantine_dir = app.home.join(QUARANTINE_DIR_NAME).join(timestamp);
+    for relative_path in untracked {
+        let src = app.install_dir.join(relative_path);
+        let dst = quarantine_dir.join(relative_path);
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {parent:?}"))?;
+        }
+        fs::rename(&src, &dst).with_context(|| format!("Failed to move {src:?} to {dst:?}"))?;
+        remove_empty_parents(&app.install_dir, &src);
+    }
+    Ok(quarantine_dir)
+}
+
+fn check_untracked_files(app: &App, fix: bool) -> Result<CheckResult> {
+    const NAME: &str = "untracked-files";
+    let untracked = find_untracked_files(app)?;
+    if untracked.is_empty() {
+        return Ok(CheckResult::pass(
+            NAME,
+            &format!("All files in {:?} belong to a package", app.install_dir),
+        ));
+    }
+    if fix {
+        let quarantine_dir = quarantine_untracked_files(app, &untracked)?;
+        return Ok(CheckResult::pass(
+            NAME,
+            &format!(
+                "Moved {} untracked file(s) to {quarantine_dir:?}",
+                untracked.len()
+            ),
+        ));
+    }
+    let paths: Vec<PathBuf> = untracked.iter().map(|x| app.install_dir.join(x)).collect();
+    Ok(CheckResult::warn(
+        NAME,
+        &format!(
+            "Some files in {:?} do not belong to any package",
+            app.install_dir
+        ),
+    )
+    .with_details(&paths)
+    .with_fixable())
+}
+
+/// Returns the leftovers of interrupted commands: the content of the tmp dir, and partial
+/// downloads
+fn find_leftovers(app: &App) -> Result<Vec<PathBuf>> {
+    let mut leftovers = Vec::new();
+    if app.tmp_dir.exists() {
+        for entry in fs::read_dir(&app.tmp_dir)? {
+            leftovers.push(entry?.path());
+        }
+    }
+    let download_dir = app.home.join("download");
+    if download_dir.exists() {
+        let mut files = Vec::new();
+        list_files(&download_dir, &download_dir, &mut files)?;
+        leftovers.extend(
+            files
+                .into_iter()
+                .filter(|x| x.extension() == Some(OsStr::new(PARTIAL_EXTENSION)))
+                .map(|x| download_dir.join(x)),
+        );
+    }
+    leftovers.sort();
+    Ok(leftovers)
+}
+
+fn check_leftovers(app: &App, fix: bool) -> Result<CheckResult> {
+    const NAME: &str = "leftovers";
+    let leftovers = find_leftovers(app)?;
+    if leftovers.is_empty() {
+        return Ok(CheckResult::pass(
+            NAME,
+            "No temporary files or partial downloads",
+        ));
+    }
+    if fix {
+        for path in &leftovers {
+            if path.is_dir() && !path.is_symlink() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            }
+            .with_context(|| format!("Failed to remove {path:?}"))?;
+        }
+        return Ok(CheckResult::pass(
+            NAME,
+            &format!(
+                "Removed {} temporary file(s) and partial download(s)",
+                leftovers.len()
+            ),
+        ));
+    }
+    Ok(CheckResult::warn(
+        NAME,
+        "Found temporary files or partial downloads left by interrupted commands",
+    )
+    .with_details(&leftovers)
+    .with_fixable())
+}
+
+fn print_result(ui: &Ui, result: &CheckResult) {
+    match result.status {
+        Status::Pass => ui.success(&result.message),
+        Status::Warn => ui.warn(&result.message),
+        Status::Fail => ui.error(&result.message),
+    }
+    let detail_ui = ui.nest();
+    for detail in &result.details {
+        detail_ui.println(detail);
+    }
+}
+
+/// Checks the health of the Clyde installation in `home`. If `fix` is true, fixes the problems
+/// which can be fixed.
+pub fn doctor_cmd(home: &Path, ui: &Ui, fix: bool) -> Result<()> {
+    let arch_os = App::read_arch_os(None)?.unwrap_or_else(ArchOs::current);
+    // Read the database before creating the app: App::new() upgrades it
+    let db_path = App::get_arch_os_home(home, &arch_os).join(DATABASE_FILE_NAME);
+    let database_state = read_database_state(&db_path);
+    let app = create_app(home, arch_os);
+    let database_result = check_database(&database_state, &app);
+
+    let app = match app {
+        Ok(app) => app,
+        Err(err) => {
+            let mut results = vec![database_result];
+            if results[0].status == Status::Pass {
+                results.push(CheckResult::fail(
+                    "home",
+                    &format!("Failed to open Clyde home: {err:#}"),
+                ));
+            }
+            return report_results(ui, fix, &results);
+        }
+    };
+
+    let mut results = vec![check_path(&app), check_activation_scripts(&app, fix)?];
+    let git_result = check_git();
+    let has_git = git_result.status == Status::Pass;
+    results.push(git_result);
+    if has_git {
+        results.push(check_store(&app));
+    }
+    let has_database = database_result.status == Status::Pass;
+    results.push(database_result);
+    // These checks need the list of installed files
+    if has_database {
+        results.push(check_installed_files(&app, ui, fix)?);
+        results.push(check_untracked_files(&app, fix)?);
+    }
+    results.push(check_leftovers(&app, fix)?);
+
+    report_results(ui, fix, &results)
+}
+
+/// Prints `results`, and returns an error if one of them failed
+fn report_results(ui: &Ui, fix: bool, results: &[CheckResult]) -> Result<()> {
+    for result in results {
+        print_result(ui, result);
+    }
+
+    if ui.is_json() {
+        let data = results
+            .iter()
+            .map(|x| {
+                json!({
+                    "check": x.name,
+                    "status": x.status,
+                    "message": x.message,
+                    "details": x.details,
+                })
+            })
+            .collect();
+        ui.emit(Event::Data { data });
+    }
+
+    if !fix && results.iter().any(|x| x.fixable) {
+        ui.info("Run `clyde doctor --fix` to fix the problems which can be fixed");
+    }
+
+    let failed = results.iter().filter(|x| x.status == Status::Fail).count();
+    if failed > 0 {
+        return Err(anyhow!("{failed} check(s) failed"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_file_utils::{create_tree, pathbufset_from_strings};
+    use crate::version::Version;
+
+    fn create_app_with_package(dir: &Path) -> App {
+        let app = App::new(dir).unwrap();
+        app.database.create().unwrap();
+        app.database
+            .add_package(
+                "foo",
+                &Version::new(1, 0, 0),
+                &VersionReq::STAR,
+                &pathbufset_from_strings(&["bin/foo", "share/man/man1/foo.1"]),
+            )
+            .unwrap();
+        app
+    }
+
+    #[test]
+    fn find_missing_files_should_list_missing_package_files() {
+        // GIVEN a package whose man page has been removed
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = create_app_with_package(&dir);
+        create_tree(&app.install_dir, &["bin/foo"]);
+
+        // WHEN find_missing_files() is called
+        let missing = find_missing_files(&app).unwrap();
+
+        // THEN it returns the man page
+        assert_eq!(
+            missing,
+            vec![(
+                "foo".to_string(),
+                vec![PathBuf::from("share/man/man1/foo.1")]
+            )]
+        );
+    }
+
+    #[test]
+    fn check_untracked_files_should_quarantine_untracked_files_on_fix() {
+        // GIVEN an install dir containing the files of a package and an untracked file
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = create_app_with_package(&dir);
+        create_tree(
+            &app.install_dir,
+            &["bin/foo", "share/man/man1/foo.1", "share/bar/bar.txt"],
+        );
+
+        // WHEN check_untracked_files() is called without fixing
+        let result = check_untracked_files(&app, false).unwrap();
+
+        // THEN the untracked file is reported
+        assert_eq!(result.status, Status::Warn);
+        assert!(result.fixable);
+        assert_eq!(
+            find_untracked_files(&app).unwrap(),
+            vec![PathBuf::from("share/bar/bar.txt")]
+        );
+
+        // WHEN check_untracked_files() is called with fixing
+        let result = check_untracked_files(&app, true).unwrap();
+
+        // THEN the untracked file and its empty parent dir are removed
+        assert_eq!(result.status, Status::Pass);
+        assert!(!app.install_dir.join("share/bar").exists());
+        assert!(app.install_dir.join("share/man/man1/foo.1").exists());
+
+        // AND the untracked file has been moved to a quarantine dir
+        let quarantine_dirs: Vec<PathBuf> = fs::read_dir(app.home.join(QUARANTINE_DIR_NAME))
+            .unwrap()
+            .map(|x| x.unwrap().path())
+            .collect();
+        assert_eq!(quarantine_dirs.len(), 1);
+        assert!(quarantine_dirs[0].join("share/bar/bar.txt").exists());
+    }
+
+    /// Creates a database using schema version 1, which also contains the column added by
+    /// version 2 if `with_version_2_column` is true, making the upgrade fail
+    fn create_version_1_database(home: &Path, with_version_2_column: bool) {
+        let conn = rusqlite::Connection::open(home.join(DATABASE_FILE_NAME)).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE installed_package (
+                name TEXT PRIMARY KEY,
+                installed_version TEXT,
+                requested_version TEXT,
+                allow_prereleases INTEGER NOT NULL DEFAULT 0
+            ) STRICT;
+            PRAGMA user_version = 1;",
+        )
+        .unwrap();
+        if with_version_2_column {
+            conn.execute_batch(
+                "ALTER TABLE installed_package
+                    ADD COLUMN version_scheme TEXT NOT NULL DEFAULT 'semver';",
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn check_database_should_report_upgraded_databases() {
+        // GIVEN an old database
+        let dir = assert_fs::TempDir::new().unwrap();
+        create_version_1_database(&dir, false);
+
+        // WHEN its state is read, then the app is created
+        let state = read_database_state(&dir.join(DATABASE_FILE_NAME));
+        let app = create_app(&dir, ArchOs::current());
+
+        // THEN the check reports the database has been upgraded
+        let result = check_database(&state, &app);
+        assert_eq!(result.status, Status::Pass);
+        assert!(
+            result.message.contains("upgraded from 1"),
+            "{}",
+            result.message
+        );
+    }
+
+    #[test]
+    fn check_database_should_report_databases_which_could_not_be_upgraded() {
+        // GIVEN an old database which cannot be upgraded
+        let dir = assert_fs::TempDir::new().unwrap();
+        create_version_1_database(&dir, true);
+
+        // WHEN its state is read, then the app is created
+        let state = read_database_state(&dir.join(DATABASE_FILE_NAME));
+        let app = create_app(&dir, ArchOs::current());
+
+        // THEN creating the app fails
+        assert!(app.is_err());
+
+        // AND the check reports the database could not be upgraded
+        let result = check_database(&state, &app);
+        assert_eq!(result.status, Status::Fail);
+        assert!(
+            result.message.contains("could not be upgraded"),
+            "{}",
+            result.message
+        );
+    }
+
+    #[test]
+    fn check_database_should_report_corrupted_databases() {
+        // GIVEN a database file which is not a database
+        let dir = assert_fs::TempDir::new().unwrap();
+        let db_path = dir.join(DATABASE_FILE_NAME);
+        fs::write(&db_path, "not a database".repeat(100)).unwrap();
+
+        // WHEN its state is read
+        let state = read_database_state(&db_path);
+
+        // THEN the check fails
+        let app = create_app(&dir, ArchOs::current());
+        let result = check_database(&state, &app);
+        assert_eq!(result.status, Status::Fail);
+    }
+
+    #[test]
+    fn check_leftovers_should_remove_tmp_files_and_partial_downloads_on_fix() {
+        // GIVEN a tmp dir with an unpacked asset, and a partial download
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = create_app_with_package(&dir);
+        create_tree(&app.tmp_dir, &["foo/bin/foo"]);
+        let download_dir = app.home.join("download");
+        create_tree(
+            &download_dir,
+            &["foo/1.0.0/foo.tar.gz", "foo/1.1.0/foo.tar.gz.partial"],
+        );
+
+        // WHEN check_leftovers() is called with fixing
+        let result = check_leftovers(&app, true).unwrap();
+
+        // THEN the leftovers are removed
+        assert_eq!(result.status, Status::Pass);
+        assert!(!app.tmp_dir.join("foo").exists());
+        assert!(!download_dir.join("foo/1.1.0/foo.tar.gz.partial").exists());
+
+        // AND complete downloads are kept
+        assert!(download_dir.join("foo/1.0.0/foo.tar.gz").exists());
+    }
+
+    #[test]
+    fn is_dir_in_path_var_should_find_dir() {
+        let path_var = env::join_paths(["/usr/bin", "/opt/clyde/inst/bin"]).unwrap();
+        assert!(is_dir_in_path_var(
+            Path::new("/opt/clyde/inst/bin"),
+            &path_var
+        ));
+        assert!(!is_dir_in_path_var(Path::new("/opt/clyde/bin"), &path_var));
+    }
+}
@@ -0,0 +1,198 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{anyhow, Result};
+
+use crate::app::App;
+use crate::cmd::install::parse_package_name_arg;
+use crate::cmd::{install_package, InstallRequest};
+use crate::package::Package;
+use crate::ui::Ui;
+use crate::version::{Version, VersionReq};
+
+/// Returns the most recent version of `package` older than `installed_version` and matching
+/// `version_req`. Pre-releases are only considered if `allow_prereleases` is true.
+fn get_previous_version<'a>(
+    package: &'a Package,
+    installed_version: &Version,
+    version_req: &VersionReq,
+    allow_prereleases: bool,
+) -> Option<&'a Version> {
+    package.releases.keys().rev().find(|&version| {
+        version < installed_version
+            && if allow_prereleases {
+                version_req.matches_prerelease(version)
+            } else {
+                version_req.matches(version)
+            }
+    })
+}
+
+/// Install the version preceding the installed one (or the most recent older version matching the
+/// requested one) and pin the package to it, so that `clyde upgrade` does not bring back the
+/// version it replaced.
+pub fn downgrade_cmd(app: &App, ui: &Ui, package_name_arg: &str) -> Result<()> {
+    let request = parse_package_name_arg(package_name_arg)?;
+    let installed_version = app
+        .database
+        .get_package_version(&request.name)?
+        .ok_or_else(|| anyhow!("Package {} is not installed", request.name))?;
+
+    let allow_prereleases = app.database.get_allow_prereleases(&request.name)?;
+
+    let package = app.store.get_package(&request.name)?;
+    let version = get_previous_version(
+        &package,
+        &installed_version,
+        &request.version,
+        allow_prereleases,
+    )
+    .ok_or_else(|| {
+        anyhow!(
+            "No version of {} older than {installed_version} matching '{}' available",
+            request.name,
+            request.version
+        )
+    })?;
+
+    let version_req = VersionReq::exact(version);
+    install_package(
+        app,
+        ui,
+        false, /* reinstall */
+        &InstallRequest::new(&request.name, version_req.clone()),
+    )?;
+    ui.info(&format!(
+        "{} has been pinned to {version_req}. Use `clyde unpin {}` to upgrade it again.",
+        request.name, request.name
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::checksum::compute_checksum;
+    use crate::cmd::install_cmd;
+    use crate::test_file_utils::get_fixture_path;
+
+    #[test]
+    fn get_previous_version_should_return_the_version_before_the_installed_one() {
+        // GIVEN a package with versions 1.0.0, 1.1.0, 1.2.0 and 2.0.0
+        let package = Package::from_yaml_str(
+            "
+            name: foo
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+              1.1.0:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+              1.2.0:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+              2.0.0:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+            installs: {}
+            ",
+        )
+        .unwrap();
+        let installed_version = Version::new(1, 2, 0);
+
+        // WHEN get_previous_version() is called without a requested version
+        // THEN it returns 1.1.0
+        assert_eq!(
+            get_previous_version(&package, &installed_version, &VersionReq::STAR, false),
+            Some(&Version::new(1, 1, 0))
+        );
+
+        // WHEN get_previous_version() is called with a requested version
+        // THEN it returns the most recent older version matching it
+        assert_eq!(
+            get_previous_version(
+                &package,
+                &installed_version,
+                &VersionReq::parse("=1.0.0").unwrap(),
+                false
+            ),
+            Some(&Version::new(1, 0, 0))
+        );
+
+        // WHEN there is no older version
+        // THEN it returns None
+        assert_eq!(
+            get_previous_version(&package, &Version::new(1, 0, 0), &VersionReq::STAR, false),
+            None
+        );
+    }
+
+    #[test]
+    fn downgrade_cmd_should_keep_allowing_prereleases() {
+        // GIVEN a `foo` package whose 2 latest versions are pre-releases
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = App::new(&dir).unwrap();
+        app.database.create().unwrap();
+        let asset_path = get_fixture_path("test_archive.tar.gz");
+        let url = format!("file://{}", asset_path.display()).replace('\\', "/");
+        let sha256 = compute_checksum(&asset_path).unwrap();
+        let package_dir = app.store_dir.join("foo");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(
+            package_dir.join("index.yaml"),
+            format!(
+                "
+            name: foo
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                any:
+                  url: {url}
+                  sha256: {sha256}
+              1.1.0-rc.1:
+                any:
+                  url: {url}
+                  sha256: {sha256}
+              1.2.0-rc.1:
+                any:
+                  url: {url}
+                  sha256: {sha256}
+            installs:
+              1.0.0:
+                any:
+                  strip: 1
+                  files:
+                    bin/hello: bin/
+            "
+            ),
+        )
+        .unwrap();
+
+        // AND the latest pre-release of `foo` has been installed with `--pre`
+        let ui = Ui::default();
+        install_cmd(&app, &ui, false, Some(true), &["foo".to_string()]).unwrap();
+
+        // WHEN `foo` is downgraded
+        downgrade_cmd(&app, &ui, "foo").unwrap();
+
+        // THEN the previous pre-release is installed, and pre-releases are still allowed
+        let info = &app.database.get_installed_packages().unwrap()[0];
+        assert_eq!(
+            info.installed_version,
+            Version::parse("1.1.0-rc.1").unwrap()
+        );
+        assert!(info.allow_prereleases);
+    }
+}
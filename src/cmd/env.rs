@@ -0,0 +1,232 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::app::DATABASE_FILE_NAME;
+use crate::db::{Database, SCHEMA_VERSION};
+use crate::package::{is_valid_env_name, EnvValue};
+use crate::shell::Shell;
+use crate::ui::{Event, Ui};
+
+/// Separator used when prepending to a variable, in non-POSIX shells
+const PATH_LIST_SEPARATOR: char = if cfg!(windows) { ';' } else { ':' };
+
+/// Returns POSIX shell commands defining the variables of `env`. Prepending is done by the
+/// shell.
+fn create_posix_env_script(env: &[(String, EnvValue)]) -> String {
+    let mut script = String::new();
+    for (name, value) in env {
+        let quoted = Shell::Sh.quote(value.value());
+        let line = match value {
+            EnvValue::Set(_) => format!("export {name}={quoted}\n"),
+            EnvValue::Prepend { .. } => {
+                format!("export {name}={quoted}\"${{{name}:+:${name}}}\"\n")
+            }
+        };
+        script.push_str(&line);
+    }
+    script
+}
+
+/// Returns the final values of the variables of `env`, using `get_var` to read the current value
+/// of variables to prepend to
+fn resolve_env(
+    env: &[(String, EnvValue)],
+    get_var: impl Fn(&str) -> Option<String>,
+) -> Vec<(String, String)> {
+    let mut resolved: Vec<(String, String)> = Vec::new();
+    for (name, value) in env {
+        let index = resolved.iter().position(|(x, _)| x == name);
+        let new_value = match value {
+            EnvValue::Set(value) => value.clone(),
+            EnvValue::Prepend { prepend } => {
+                let current = match index {
+                    Some(index) => Some(resolved[index].1.clone()),
+                    None => get_var(name),
+                };
+                match current {
+                    Some(current) if !current.is_empty() => {
+                        format!("{prepend}{PATH_LIST_SEPARATOR}{current}")
+                    }
+                    _ => prepend.clone(),
+                }
+            }
+        };
+        match index {
+            Some(index) => resolved[index].1 = new_value,
+            None => resolved.push((name.clone(), new_value)),
+        }
+    }
+    resolved
+}
+
+/// Returns commands defining the variables of `env` for `shell`. Except for POSIX shells,
+/// prepending uses the current values of the environment of Clyde, which is the one of the
+/// shell.
+fn create_env_script(shell: Shell, env: &[(String, EnvValue)]) -> String {
+    if shell.is_posix() {
+        return create_posix_env_script(env);
+    }
+    let resolved = resolve_env(env, |name| std::env::var(name).ok());
+    if shell == Shell::Nu {
+        // Nushell cannot evaluate code at runtime, the activation script loads this with
+        // `from json | load-env`
+        let map: serde_json::Map<_, _> = resolved
+            .into_iter()
+            .map(|(name, value)| (name, value.into()))
+            .collect();
+        return format!("{}\n", serde_json::Value::Object(map));
+    }
+    let mut script = String::new();
+    for (name, value) in resolved {
+        let quoted = shell.quote(&value);
+        let line = match shell {
+            Shell::Fish => format!("set -gx {name} {quoted}\n"),
+            Shell::PowerShell => format!("$env:{name} = {quoted}\n"),
+            Shell::Elvish => format!("set-env {name} {quoted}\n"),
+            Shell::Sh | Shell::Bash | Shell::Zsh | Shell::Nu => unreachable!(),
+        };
+        script.push_str(&line);
+    }
+    script
+}
+
+/// Prints the environment variables defined by the installed packages, as shell commands. The
+/// activation script evaluates the output of this command, from each new shell, so this must not
+/// modify Clyde home: the database is opened in read-only mode, and nothing is printed if it has
+/// not been created yet or must be upgraded.
+pub fn env_cmd(home: &Path, ui: &Ui, shell: Shell) -> Result<()> {
+    let db_path = home.join(DATABASE_FILE_NAME);
+    if !db_path.exists() {
+        return Ok(());
+    }
+    let database = Database::new_read_only(&db_path)?;
+    if database.get_schema_version()? < SCHEMA_VERSION {
+        return Ok(());
+    }
+    let env: Vec<_> = database
+        .get_env()?
+        .into_iter()
+        .filter(|(name, _)| {
+            let valid = is_valid_env_name(name);
+            if !valid {
+                ui.warn(&format!(
+                    "Ignoring invalid environment variable name '{name}'"
+                ));
+            }
+            valid
+        })
+        .collect();
+    if ui.is_json() {
+        let data = env
+            .iter()
+            .map(|(name, value)| {
+                json!({
+                    "name": name,
+                    "action": value.action().to_string(),
+                    "value": value.value(),
+                })
+            })
+            .collect();
+        ui.emit(Event::Data { data });
+    } else {
+        print!("{}", create_env_script(shell, &env));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use which::which;
+
+    use super::*;
+    use crate::package::EnvAction;
+
+    #[test]
+    fn create_posix_env_script_should_set_and_prepend() {
+        if which("sh").is_err() {
+            // Skip test, can't be run in this environment
+            println!("Skipped");
+            return;
+        }
+        // GIVEN variables to set and to prepend to
+        let env = vec![
+            (
+                "FOO_HOME".to_string(),
+                EnvValue::Set("/opt/it's foo".into()),
+            ),
+            (
+                "FOO_PATH".to_string(),
+                EnvValue::new(EnvAction::Prepend, "/opt/foo/plugins"),
+            ),
+            (
+                "BAR_PATH".to_string(),
+                EnvValue::new(EnvAction::Prepend, "/opt/bar/plugins"),
+            ),
+        ];
+
+        // WHEN the script is evaluated by a shell where FOO_PATH is already set
+        let script = create_posix_env_script(&env);
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!(
+                "{script}echo \"$FOO_HOME\"; echo \"$FOO_PATH\"; echo \"$BAR_PATH\""
+            ))
+            .env("FOO_PATH", "/usr/lib/foo")
+            .env_remove("BAR_PATH")
+            .output()
+            .unwrap();
+
+        // THEN the variables are defined
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "/opt/it's foo\n/opt/foo/plugins:/usr/lib/foo\n/opt/bar/plugins\n"
+        );
+    }
+
+    #[test]
+    fn resolve_env_should_prepend_to_current_and_previous_values() {
+        // GIVEN variables, two of them prepending to the same variable
+        let env = vec![
+            ("FOO_HOME".to_string(), EnvValue::Set("/opt/foo".into())),
+            (
+                "FOO_PATH".to_string(),
+                EnvValue::new(EnvAction::Prepend, "/opt/foo/plugins"),
+            ),
+            (
+                "FOO_PATH".to_string(),
+                EnvValue::new(EnvAction::Prepend, "/opt/bar/plugins"),
+            ),
+            (
+                "BAR_PATH".to_string(),
+                EnvValue::new(EnvAction::Prepend, "/opt/bar/lib"),
+            ),
+        ];
+
+        // WHEN they are resolved in an environment where only FOO_PATH is set
+        let resolved = resolve_env(&env, |name| {
+            (name == "FOO_PATH").then(|| "/usr/lib/foo".to_string())
+        });
+
+        // THEN each variable appears once, with all the prepended values
+        let sep = PATH_LIST_SEPARATOR;
+        assert_eq!(
+            resolved,
+            vec![
+                ("FOO_HOME".to_string(), "/opt/foo".to_string()),
+                (
+                    "FOO_PATH".to_string(),
+                    format!("/opt/bar/plugins{sep}/opt/foo/plugins{sep}/usr/lib/foo")
+                ),
+                ("BAR_PATH".to_string(), "/opt/bar/lib".to_string()),
+            ]
+        );
+    }
+}
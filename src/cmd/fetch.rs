@@ -0,0 +1,95 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::json;
+
+use crate::app::App;
+use crate::cmd::install::{download_asset, parse_package_name_arg, InstallRequest};
+use crate::file_utils::get_file_name;
+use crate::ui::{Event, Ui};
+
+/// Download and verify the asset matching `request`, without installing it. If `output_dir` is
+/// set, the asset is copied there. Returns the path of the asset.
+fn fetch_package(
+    app: &App,
+    ui: &Ui,
+    request: &InstallRequest,
+    output_dir: Option<&Path>,
+) -> Result<PathBuf> {
+    let arch_os = app.arch_os;
+    let package = app.store.get_package(&request.name)?;
+
+    let version = package
+        .get_version_matching(&request.version, request.allow_prereleases.unwrap_or(false))
+        .ok_or_else(|| {
+            anyhow!(
+                "No version matching '{}' available for {}",
+                &request.version,
+                &package.name
+            )
+        })?;
+
+    let asset = package.get_asset(version, &arch_os).ok_or_else(|| {
+        anyhow!(
+            "No {arch_os} asset available for {} {version}",
+            &package.name
+        )
+    })?;
+
+    ui.info(&format!(
+        "Fetching {} {} for {arch_os}",
+        &package.name, &version
+    ));
+    ui.emit(Event::Resolved {
+        package: package.name.clone(),
+        version: version.to_string(),
+        arch_os: arch_os.to_str(),
+    });
+    let asset_path = download_asset(app, &ui.nest(), &package, version, asset)?;
+
+    let Some(output_dir) = output_dir else {
+        return Ok(asset_path);
+    };
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create directory {}", output_dir.display()))?;
+    let output_path = output_dir.join(get_file_name(&asset_path)?);
+    fs::copy(&asset_path, &output_path).with_context(|| {
+        format!(
+            "Failed to copy {} to {}",
+            asset_path.display(),
+            output_path.display()
+        )
+    })?;
+    Ok(output_path)
+}
+
+/// Download the assets of packages for the app arch-os, without installing them. Prints the paths
+/// of the downloaded assets.
+pub fn fetch_cmd(
+    app: &App,
+    ui: &Ui,
+    allow_prereleases: bool,
+    output_dir: Option<&Path>,
+    package_name_args: &[String],
+) -> Result<()> {
+    let mut paths = Vec::new();
+    for arg in package_name_args {
+        let request = parse_package_name_arg(arg)?.with_allow_prereleases(allow_prereleases);
+        paths.push(fetch_package(app, ui, &request, output_dir)?);
+    }
+
+    if ui.is_json() {
+        let paths: Vec<_> = paths.iter().map(|x| x.display().to_string()).collect();
+        ui.emit(Event::Data { data: json!(paths) });
+    } else {
+        for path in paths {
+            println!("{}", path.display());
+        }
+    }
+    Ok(())
+}
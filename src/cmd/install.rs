@@ -4,28 +4,59 @@
 
 use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context, Result};
-use semver::VersionReq;
+use glob_match::glob_match;
+use tempfile::TempDir;
 
 use crate::app::App;
-use crate::arch_os::ArchOs;
-use crate::checksum::verify_checksum;
+use crate::arch_os::{ArchOs, Os};
+use crate::checksum::verify_checksums;
 use crate::cmd::uninstall_package;
-use crate::package::EXTRA_FILES_DIR_NAME;
-use crate::ui::Ui;
-use crate::unpacker::get_unpacker;
+#[cfg(unix)]
+use crate::file_utils::set_file_executable;
+use crate::file_utils::{path_exists, prepend_dir_to_path};
+use crate::package::{
+    is_valid_env_name, Asset, EnvValue, Install, Launcher, Package, EXTRA_FILES_DIR_NAME,
+};
+use crate::signature::verify_signature;
+use crate::ui::{Event, Ui};
+use crate::unpacker::{get_unpacker, SkippedHardLinksError, UnpackFilter, UnpackOptions};
 use crate::vars::{expand_vars, VarsMap};
+use crate::version::{Version, VersionReq};
 
 const CLYDE_PACKAGE_NAME: &str = "clyde";
 
+/// How long commands from the `generate` section can run
+const GENERATE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many bytes of the error output of a failing `generate` command are reported
+const MAX_ERROR_OUTPUT_SIZE: u64 = 2048;
+
+/// Sources of the `files` mapping starting with this prefix do not have to exist. It is not a
+/// character, such as `?`, which could be part of a glob pattern.
+const OPTIONAL_SOURCE_PREFIX: &str = "optional:";
+
+/// Characters making a source of the `files` mapping a glob pattern, unless escaped with `\`
+const GLOB_CHARS: [char; 4] = ['*', '?', '[', '{'];
+
+/// Characters which can be escaped with `\` in a source of the `files` mapping
+const ESCAPABLE_CHARS: [char; 7] = ['*', '?', '[', ']', '{', '}', '\\'];
+
 #[derive(Debug, PartialEq)]
 /// Store the details of an install, used by install_package()
 /// and install_packages()
 pub struct InstallRequest {
     pub name: String,
     pub version: VersionReq,
+    /// If true, pre-releases can be installed, now and when upgrading the package. If None, the
+    /// setting of the installed package is kept. New installs do not allow pre-releases.
+    pub allow_prereleases: Option<bool>,
 }
 
 impl InstallRequest {
@@ -33,13 +64,78 @@ impl InstallRequest {
         InstallRequest {
             name: package_name.into(),
             version,
+            allow_prereleases: None,
         }
     }
+
+    pub fn with_allow_prereleases(mut self, allow_prereleases: bool) -> Self {
+        self.allow_prereleases = Some(allow_prereleases);
+        self
+    }
 }
 
-fn unpack(archive: &Path, pkg_dir: &Path, strip: u32) -> Result<Option<String>> {
-    let unpacker = get_unpacker(archive)?;
-    unpacker.unpack(pkg_dir, strip)
+fn unpack(
+    archive: &Path,
+    pkg_dir: &Path,
+    install: &Install,
+    host_only: bool,
+    options: &UnpackOptions,
+) -> Result<Option<String>> {
+    let unpacker = get_unpacker(archive, install.format, host_only)?;
+    unpacker.unpack(pkg_dir, options)
+}
+
+/// Returns the expanded sources of the `files` mapping of `install`, or None if they cannot be
+/// known before unpacking (for example if they use `${asset_name}` or are glob patterns)
+fn get_unpack_sources(install: &Install, vars: &VarsMap) -> Option<Vec<String>> {
+    let sources = install
+        .files
+        .keys()
+        .map(|x| expand_vars(parse_source(x).0, vars))
+        .collect::<Result<Vec<_>>>()
+        .ok()?;
+    if sources.iter().any(|x| is_glob(x)) {
+        return None;
+    }
+    Some(sources.iter().map(|x| unescape_source(x)).collect())
+}
+
+/// Unpacks `archive` in `unpack_dir`. Only the sources of the `files` mapping are unpacked if
+/// they can be known in advance. If one of them is missing after unpacking, or if hard links could
+/// not be created because their target has been skipped, the whole archive is unpacked.
+fn unpack_asset(
+    app: &App,
+    archive: &Path,
+    unpack_dir: &Path,
+    install: &Install,
+    package_name: &str,
+    version: &Version,
+) -> Result<Option<String>> {
+    let host_only = !app.is_foreign_arch_os();
+    let options = UnpackOptions::default()
+        .with_strip(install.strip)
+        .with_limits(app.unpack_limits);
+
+    let vars = create_vars_map(&None, package_name, version, &app.arch_os);
+    let Some(sources) = get_unpack_sources(install, &vars) else {
+        return unpack(archive, unpack_dir, install, host_only, &options);
+    };
+    let filtered_options = options
+        .clone()
+        .with_filter(Some(UnpackFilter::new(&sources)));
+    match unpack(archive, unpack_dir, install, host_only, &filtered_options) {
+        Ok(asset_name) => {
+            if asset_name.is_some() || sources.iter().all(|x| unpack_dir.join(x).exists()) {
+                return Ok(asset_name);
+            }
+        }
+        // Hard links to entries outside the filter could not be created
+        Err(err) if err.downcast_ref::<SkippedHardLinksError>().is_some() => (),
+        Err(err) => return Err(err),
+    }
+
+    fs::remove_dir_all(unpack_dir)?;
+    unpack(archive, unpack_dir, install, host_only, &options)
 }
 
 /// Create dir containing `path` and all its parents, if necessary
@@ -119,6 +215,71 @@ fn install_file_entry(
     Ok(())
 }
 
+/// Splits the optional marker from a source of a `files` mapping. Returns the source without the
+/// marker, and true if it was there.
+fn parse_source(src: &str) -> (&str, bool) {
+    match src.strip_prefix(OPTIONAL_SOURCE_PREFIX) {
+        Some(src) => (src, true),
+        None => (src, false),
+    }
+}
+
+/// Returns true if the source `src`, once expanded, is a glob pattern: if it contains glob
+/// characters which are not escaped with `\`
+fn is_glob(src: &str) -> bool {
+    let mut chars = src.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            chars.next();
+        } else if GLOB_CHARS.contains(&ch) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns the path a source which is not a glob pattern refers to, by removing the `\` escaping
+/// glob characters
+fn unescape_source(src: &str) -> String {
+    let mut path = String::with_capacity(src.len());
+    let mut chars = src.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            if let Some(next) = chars.next_if(|x| ESCAPABLE_CHARS.contains(x)) {
+                path.push(next);
+                continue;
+            }
+        }
+        path.push(ch);
+    }
+    path
+}
+
+/// Returns the paths of the files and directories of `pkg_dir` matching `pattern`, relative to
+/// `pkg_dir` and sorted. The content of matching directories is not looked at: installing the
+/// directory installs it.
+fn find_glob_matches(pkg_dir: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut matches = vec![];
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(rel_dir) = dirs.pop() {
+        let dir = pkg_dir.join(&rel_dir);
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let entry = entry?;
+            let rel_path = rel_dir.join(entry.file_name());
+            let rel_path_str = rel_path.to_string_lossy().replace('\\', "/");
+            if glob_match(pattern, &rel_path_str) {
+                matches.push(rel_path);
+            } else if entry.file_type()?.is_dir() {
+                dirs.push(rel_path);
+            }
+        }
+    }
+    matches.sort();
+    Ok(matches)
+}
+
 /// Install all files from a `${arch_os}.files` mapping.
 /// Add the installed files to `installed_files`.
 fn install_files(
@@ -131,15 +292,36 @@ fn install_files(
 ) -> Result<()> {
     fs::create_dir_all(install_dir)?;
     for (src, dst) in file_map.iter() {
+        let (src, optional) = parse_source(src);
         let src = expand_vars(src, vars)?;
+        let dst = expand_vars(dst, vars)?;
+
+        // Sources from before glob patterns were supported can contain glob characters: keep
+        // installing them as is if they exist
+        if is_glob(&src) && !pkg_dir.join(&src).exists() {
+            install_glob_entry(
+                install_mode,
+                installed_files,
+                pkg_dir,
+                install_dir,
+                &src,
+                &dst,
+                optional,
+            )?;
+            continue;
+        }
+
+        let src = unescape_source(&src);
+        let src_path = pkg_dir.join(&src);
+        if optional && !src_path.exists() {
+            continue;
+        }
         let dst = if dst.is_empty() {
             // If dst is empty it means the destination is the same as the source
-            src.clone()
+            src
         } else {
-            expand_vars(dst, vars)?
+            dst
         };
-
-        let src_path = pkg_dir.join(src);
         install_file_entry(
             install_mode,
             installed_files,
@@ -151,7 +333,316 @@ fn install_files(
     Ok(())
 }
 
-fn parse_package_name_arg(arg: &str) -> Result<InstallRequest> {
+/// Install the files and directories of `pkg_dir` matching the glob `pattern`. If `dst` is empty,
+/// they keep their path, otherwise `dst` must be a directory and they are installed inside it.
+fn install_glob_entry(
+    install_mode: InstallMode,
+    installed_files: &mut HashSet<PathBuf>,
+    pkg_dir: &Path,
+    install_dir: &Path,
+    pattern: &str,
+    dst: &str,
+    optional: bool,
+) -> Result<()> {
+    if !dst.is_empty() && !dst.ends_with('/') {
+        return Err(anyhow!(
+            "The destination of '{pattern}' must be a directory, ending with '/', since it is a glob pattern"
+        ));
+    }
+    let matches = find_glob_matches(pkg_dir, pattern)?;
+    if matches.is_empty() && !optional {
+        return Err(anyhow!("No files match '{pattern}'"));
+    }
+    for rel_path in matches {
+        let entry_dst = if dst.is_empty() {
+            rel_path.clone()
+        } else {
+            // file_name() cannot fail: rel_path comes from read_dir()
+            Path::new(dst).join(rel_path.file_name().unwrap())
+        };
+        install_file_entry(
+            install_mode,
+            installed_files,
+            &pkg_dir.join(&rel_path),
+            install_dir,
+            &entry_dst,
+        )?;
+    }
+    Ok(())
+}
+
+/// Parses `path`, a path relative to the install dir. Fails if it is absolute or goes up.
+fn parse_install_path(path: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(path);
+    if path.as_os_str().is_empty()
+        || !path
+            .components()
+            .all(|x| matches!(x, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(anyhow!(
+            "'{}' must be a relative path inside the install dir",
+            path.display()
+        ));
+    }
+    Ok(path)
+}
+
+/// Returns the target to give to a symlink at `link` to make it point to `target`. Both are
+/// relative to the install dir, the returned target is relative to the dir of `link`, so that
+/// the install dir can be moved.
+fn get_relative_symlink_target(link: &Path, target: &Path) -> PathBuf {
+    let mut relative_target = PathBuf::new();
+    if let Some(link_dir) = link.parent() {
+        for component in link_dir.components() {
+            if matches!(component, Component::Normal(_)) {
+                relative_target.push("..");
+            }
+        }
+    }
+    relative_target.join(target)
+}
+
+/// Creates the symlinks of an install in `install_dir`.
+/// Add the created symlinks to `installed_files`.
+fn install_symlinks(
+    installed_files: &mut HashSet<PathBuf>,
+    install_dir: &Path,
+    symlinks: &BTreeMap<String, String>,
+    vars: &VarsMap,
+) -> Result<()> {
+    for (link, target) in symlinks.iter() {
+        let link = parse_install_path(&expand_vars(link, vars)?)?;
+        let target = parse_install_path(&expand_vars(target, vars)?)?;
+
+        if !install_dir.join(&target).exists() {
+            return Err(anyhow!(
+                "Cannot create symlink {}: its target, {}, does not exist",
+                link.display(),
+                target.display()
+            ));
+        }
+        let link_path = install_dir.join(&link);
+        if path_exists(&link_path) {
+            return Err(anyhow!("{} already exists", link_path.display()));
+        }
+        create_parent_dir(&link_path)?;
+        let relative_target = get_relative_symlink_target(&link, &target);
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&relative_target, &link_path)
+            .with_context(|| format!("Failed to create symlink {}", link_path.display()))?;
+        #[cfg(not(unix))]
+        return Err(anyhow!(
+            "Cannot create symlink {}: symlinks are not supported on this OS",
+            link_path.display()
+        ));
+        installed_files.insert(link);
+    }
+    Ok(())
+}
+
+/// Generates the launcher scripts of an install in `install_dir`. On Windows, the scripts are
+/// batch files and get a `.cmd` extension.
+/// Add the generated scripts to `installed_files`.
+fn install_launchers(
+    installed_files: &mut HashSet<PathBuf>,
+    install_dir: &Path,
+    launchers: &BTreeMap<String, Launcher>,
+    os: Os,
+    vars: &VarsMap,
+) -> Result<()> {
+    for (path, launcher) in launchers.iter() {
+        let mut path = expand_vars(path, vars)?;
+        let target = parse_install_path(&expand_vars(&launcher.target, vars)?)?;
+        let launcher = Launcher {
+            target: target.to_string_lossy().to_string(),
+            args: launcher
+                .args
+                .iter()
+                .map(|x| expand_vars(x, vars))
+                .collect::<Result<_>>()?,
+            env: launcher
+                .env
+                .iter()
+                .map(|(name, value)| Ok((name.clone(), expand_vars(value, vars)?)))
+                .collect::<Result<_>>()?,
+        };
+
+        if !install_dir.join(&target).exists() {
+            return Err(anyhow!(
+                "Cannot create launcher {path}: its target, {}, does not exist",
+                target.display()
+            ));
+        }
+        if os == Os::Windows {
+            path.push_str(".cmd");
+        }
+        let path = parse_install_path(&path)?;
+        let relative_target = get_relative_symlink_target(&path, &target);
+        let script = if os == Os::Windows {
+            launcher.to_cmd_script(&relative_target)?
+        } else {
+            launcher.to_posix_script(&relative_target)?
+        };
+
+        let script_path = install_dir.join(&path);
+        if path_exists(&script_path) {
+            return Err(anyhow!("{} already exists", script_path.display()));
+        }
+        create_parent_dir(&script_path)?;
+        fs::write(&script_path, script)
+            .with_context(|| format!("Failed to write {}", script_path.display()))?;
+        installed_files.insert(path);
+        #[cfg(unix)]
+        set_file_executable(&script_path)?;
+    }
+    Ok(())
+}
+
+/// Runs `command`, a command line starting with an executable installed in `install_dir`, and
+/// writes its output to `dst`. `dst` and `command` must have been expanded. The command runs in a
+/// new directory created in `tmp_dir`. Returns the path of the generated file, relative to
+/// `install_dir`.
+fn generate_file(install_dir: &Path, tmp_dir: &Path, dst: &str, command: &str) -> Result<PathBuf> {
+    let path = parse_install_path(dst)?;
+    let words = shell_words::split(command)?;
+    let (program, args) = words
+        .split_first()
+        .ok_or_else(|| anyhow!("Empty command"))?;
+    // Only run installed executables
+    let bin_dir = install_dir.join("bin");
+    let program_path = if program.contains('/') || program.contains('\\') {
+        install_dir.join(parse_install_path(program)?)
+    } else {
+        which::which_in(program, Some(&bin_dir), install_dir)
+            .with_context(|| format!("Cannot find {program} in {}", bin_dir.display()))?
+    };
+
+    let dst_path = install_dir.join(&path);
+    if path_exists(&dst_path) {
+        return Err(anyhow!("{} already exists", dst_path.display()));
+    }
+    fs::create_dir_all(tmp_dir)?;
+    let work_dir = TempDir::new_in(tmp_dir)?;
+    let mut stderr_file = tempfile::tempfile_in(tmp_dir)?;
+    create_parent_dir(&dst_path)?;
+    let dst_file = fs::File::create(&dst_path)
+        .with_context(|| format!("Failed to create {}", dst_path.display()))?;
+
+    let result = run_generate_command(
+        Command::new(&program_path)
+            .args(args)
+            .current_dir(work_dir.path())
+            .env("PATH", prepend_dir_to_path(&bin_dir)?)
+            .stdin(Stdio::null())
+            .stdout(dst_file)
+            .stderr(stderr_file.try_clone()?),
+    )
+    .and_then(|()| match fs::metadata(&dst_path)?.len() {
+        0 => Err(anyhow!("Command produced no output")),
+        _ => Ok(()),
+    });
+    if let Err(err) = result {
+        fs::remove_file(&dst_path)?;
+        return Err(add_error_output(err, &mut stderr_file));
+    }
+    Ok(path)
+}
+
+/// Adds the end of the error output of a command, stored in `stderr_file`, to `err`
+fn add_error_output(err: anyhow::Error, stderr_file: &mut fs::File) -> anyhow::Error {
+    let mut stderr = Vec::new();
+    let start = stderr_file
+        .metadata()
+        .map_or(0, |x| x.len().saturating_sub(MAX_ERROR_OUTPUT_SIZE));
+    let _ = stderr_file
+        .seek(SeekFrom::Start(start))
+        .and_then(|_| stderr_file.read_to_end(&mut stderr));
+    let stderr = String::from_utf8_lossy(&stderr);
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        err
+    } else {
+        anyhow!("{err}. Error output:\n{stderr}")
+    }
+}
+
+/// Runs `command`, killing it if it does not finish within GENERATE_TIMEOUT
+fn run_generate_command(command: &mut Command) -> Result<()> {
+    let mut child = command.spawn()?;
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return match status.code() {
+                Some(0) => Ok(()),
+                Some(x) => Err(anyhow!("Command failed with exit code {x}")),
+                None => Err(anyhow!("Command terminated by signal")),
+            };
+        }
+        if start.elapsed() > GENERATE_TIMEOUT {
+            child.kill()?;
+            child.wait()?;
+            return Err(anyhow!(
+                "Command did not finish after {} seconds",
+                GENERATE_TIMEOUT.as_secs()
+            ));
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Generates the files of the `generate` section of an install. Failures are reported as
+/// warnings: the package is usable without these files.
+/// Add the generated files to `installed_files`.
+fn generate_files(
+    app: &App,
+    ui: &Ui,
+    installed_files: &mut HashSet<PathBuf>,
+    generate: &BTreeMap<String, String>,
+    vars: &VarsMap,
+) {
+    if generate.is_empty() {
+        return;
+    }
+    if app.is_foreign_arch_os() {
+        ui.info("Not generating files: the package is installed for another arch-os");
+        return;
+    }
+    ui.info("Generating files");
+    for (dst, command) in generate.iter() {
+        let result = expand_vars(dst, vars).and_then(|dst| {
+            let command = expand_vars(command, vars)?;
+            generate_file(&app.install_dir, &app.tmp_dir, &dst, &command)
+        });
+        match result {
+            Ok(path) => {
+                installed_files.insert(path);
+            }
+            Err(err) => ui.warn(&format!("Failed to generate {dst}: {err:#}")),
+        }
+    }
+}
+
+/// Expands the values of the `env` mapping of an install. In addition to the usual variables,
+/// `${install_dir}` expands to the absolute path of the install dir.
+fn expand_env(
+    install_dir: &Path,
+    env: &BTreeMap<String, EnvValue>,
+    vars: &VarsMap,
+) -> Result<Vec<(String, EnvValue)>> {
+    let mut vars = vars.clone();
+    vars.insert("install_dir".into(), install_dir.display().to_string());
+    env.iter()
+        .map(|(name, value)| {
+            if !is_valid_env_name(name) {
+                return Err(anyhow!("Invalid environment variable name '{name}'"));
+            }
+            let expanded = expand_vars(value.value(), &vars)?;
+            Ok((name.clone(), EnvValue::new(value.action(), &expanded)))
+        })
+        .collect()
+}
+
+pub fn parse_package_name_arg(arg: &str) -> Result<InstallRequest> {
     let split = arg.split_once('@');
     match split {
         None => Ok(InstallRequest::new(arg, VersionReq::STAR)),
@@ -173,12 +664,64 @@ fn remove_installed_files(ui: &Ui, install_dir: &Path, files: &HashSet<PathBuf>)
     Ok(())
 }
 
-fn create_vars_map(asset_name: &Option<String>, package_name: &str) -> VarsMap {
+/// Verify the upstream signature of the asset. Fails if the package defines how to verify
+/// signatures but the asset has none: otherwise removing the signature would be enough to bypass
+/// the verification.
+fn verify_asset_signature(
+    app: &App,
+    ui: &Ui,
+    package: &Package,
+    version: &Version,
+    asset: &Asset,
+    asset_path: &Path,
+) -> Result<()> {
+    let Some(signature_url) = &asset.signature else {
+        if package.signing.is_some() {
+            return Err(anyhow!(
+                "{} defines how to verify signatures, but this asset has no signature",
+                package.name
+            ));
+        }
+        return Ok(());
+    };
+    let signing = package.signing.as_ref().ok_or_else(|| {
+        anyhow!(
+            "Asset has a signature, but {} does not define how to verify it",
+            package.name
+        )
+    })?;
+
+    ui.info("Verifying asset signature");
+    ui.emit(Event::Verifying {
+        package: package.name.clone(),
+        method: "signature".to_string(),
+    });
+    let signature_path = app
+        .download_cache
+        .download(ui, &package.name, version, signature_url)?;
+    if let Err(err) = verify_signature(signing, asset_path, &signature_path) {
+        fs::remove_file(&signature_path)?;
+        return Err(err);
+    }
+    Ok(())
+}
+
+fn create_vars_map(
+    asset_name: &Option<String>,
+    package_name: &str,
+    version: &Version,
+    arch_os: &ArchOs,
+) -> VarsMap {
     let mut map = VarsMap::new();
 
+    map.insert("name".into(), package_name.into());
+    map.insert("version".into(), version.to_string());
+    map.insert("arch".into(), arch_os.arch.to_string());
+    map.insert("os".into(), arch_os.os.to_string());
+
     map.insert(
         "exe_ext".into(),
-        if cfg!(windows) {
+        if arch_os.os == Os::Windows {
             ".exe".into()
         } else {
             "".into()
@@ -186,6 +729,8 @@ fn create_vars_map(asset_name: &Option<String>, package_name: &str) -> VarsMap {
     );
 
     map.insert("doc_dir".into(), format!("share/doc/{package_name}/"));
+    map.insert("man_dir".into(), "share/man/".into());
+    map.insert("opt_dir".into(), format!("opt/{package_name}/"));
     map.insert(
         "bash_comp_dir".into(),
         // The extra "/completions/" is required by bash-completions
@@ -203,15 +748,105 @@ fn create_vars_map(asset_name: &Option<String>, package_name: &str) -> VarsMap {
     map
 }
 
+/// Returns the path of `dst` once `src` is installed there, without looking at the asset. Returns
+/// None if it cannot be known without unpacking the asset, for glob patterns for example.
+fn get_install_destination(src: &str, dst: &str, vars: &VarsMap) -> Option<String> {
+    let dst = expand_vars(dst, vars).ok()?;
+    if !dst.is_empty() && !dst.ends_with('/') {
+        return Some(dst);
+    }
+    let (src, _) = parse_source(src);
+    let src = expand_vars(src, vars).ok()?;
+    if is_glob(&src) {
+        return None;
+    }
+    let src = unescape_source(&src);
+    if dst.is_empty() {
+        return Some(src);
+    }
+    let file_name = Path::new(&src).file_name()?.to_str()?;
+    Some(format!("{dst}{file_name}"))
+}
+
+/// Returns the paths, relative to the install dir, of the files installing `version` of `package`
+/// for `arch_os` would create, and the sources whose destination cannot be known without the
+/// asset. Directories installed recursively are returned as is, since their content depends on
+/// the asset.
+pub fn get_install_destinations(
+    package: &Package,
+    version: &Version,
+    arch_os: &ArchOs,
+) -> (Vec<PathBuf>, Vec<String>) {
+    let Some(install) = package.get_install(version, arch_os) else {
+        return (vec![], vec![]);
+    };
+    let vars = create_vars_map(&None, &package.name, version, arch_os);
+    let mut destinations = Vec::new();
+    let mut unknown_sources = Vec::new();
+    for (src, dst) in install.files.iter().chain(&install.extra_files) {
+        match get_install_destination(src, dst, &vars) {
+            Some(destination) => destinations.push(PathBuf::from(destination)),
+            None => unknown_sources.push(src.clone()),
+        }
+    }
+    let generated_paths = install
+        .symlinks
+        .keys()
+        .chain(install.launchers.keys())
+        .chain(install.generate.keys());
+    for path in generated_paths {
+        if let Ok(path) = expand_vars(path, &vars) {
+            destinations.push(PathBuf::from(path));
+        }
+    }
+    (destinations, unknown_sources)
+}
+
+/// Download the asset of `package` and verify its checksums and signature. Returns the path of the
+/// downloaded asset.
+pub fn download_asset(
+    app: &App,
+    ui: &Ui,
+    package: &Package,
+    version: &Version,
+    asset: &Asset,
+) -> Result<PathBuf> {
+    let asset_path = app
+        .download_cache
+        .download(ui, &package.name, version, &asset.url)?;
+
+    ui.info("Verifying asset integrity");
+    ui.emit(Event::Verifying {
+        package: package.name.clone(),
+        method: "checksum".to_string(),
+    });
+    match verify_checksums(&asset_path, &asset.checksums) {
+        Ok(()) => {}
+        Err(err) => {
+            fs::remove_file(&asset_path)?;
+            return Err(err);
+        }
+    };
+
+    verify_asset_signature(app, ui, package, version, asset, &asset_path)?;
+    Ok(asset_path)
+}
+
 pub fn install_cmd(
     app: &App,
     ui: &Ui,
     reinstall: bool,
+    allow_prereleases: Option<bool>,
     package_name_args: &[String],
 ) -> Result<()> {
     let install_requests = package_name_args
         .iter()
-        .map(|name| parse_package_name_arg(name))
+        .map(|name| {
+            parse_package_name_arg(name).map(|x| InstallRequest {
+                allow_prereleases,
+                ..x
+            })
+        })
         .collect::<Result<Vec<InstallRequest>>>()?;
     install_packages(app, ui, reinstall, &install_requests)
 }
@@ -254,12 +889,17 @@ pub fn install_package(
 ) -> Result<()> {
     let db = &app.database;
 
-    let arch_os = ArchOs::current();
+    let arch_os = app.arch_os;
 
     let package = app.store.get_package(&install_request.name)?;
 
+    let allow_prereleases = match install_request.allow_prereleases {
+        Some(allow_prereleases) => allow_prereleases,
+        None => db.get_allow_prereleases(&package.name)?,
+    };
+
     let version = package
-        .get_version_matching(&install_request.version)
+        .get_version_matching(&install_request.version, allow_prereleases)
         .ok_or_else(|| {
             anyhow!(
                 "No version matching '{}' available for {}",
@@ -287,21 +927,22 @@ pub fn install_package(
             version
         ));
     }
-    ui.info(&format!("Installing {} {}", &package.name, &version));
+    if app.is_foreign_arch_os() {
+        ui.info(&format!(
+            "Installing {} {} for {arch_os}",
+            &package.name, &version
+        ));
+    } else {
+        ui.info(&format!("Installing {} {}", &package.name, &version));
+    }
+    ui.emit(Event::Resolved {
+        package: package.name.clone(),
+        version: version.to_string(),
+        arch_os: arch_os.to_str(),
+    });
 
     let ui = ui.nest();
-    let asset_path = app
-        .download_cache
-        .download(&ui, &package.name, version, &build.url)?;
-
-    ui.info("Verifying asset integrity");
-    match verify_checksum(&asset_path, &build.sha256) {
-        Ok(()) => {}
-        Err(err) => {
-            fs::remove_file(&asset_path)?;
-            return Err(err);
-        }
-    };
+    let asset_path = download_asset(app, &ui, &package, version, build)?;
 
     let unpack_dir = app.tmp_dir.join(&package.name);
     if unpack_dir.exists() {
@@ -309,7 +950,14 @@ pub fn install_package(
     }
 
     ui.info("Unpacking asset");
-    let asset_name = unpack(&asset_path, &unpack_dir, install.strip)?;
+    let asset_name = unpack_asset(
+        app,
+        &asset_path,
+        &unpack_dir,
+        install,
+        &package.name,
+        version,
+    )?;
 
     if installed_version.is_some() {
         // The package is already installed: either it's a different version, or we were called
@@ -318,7 +966,7 @@ pub fn install_package(
     }
 
     ui.info("Installing files");
-    let map = create_vars_map(&asset_name, &package.name);
+    let map = create_vars_map(&asset_name, &package.name, version, &arch_os);
     let mut installed_files = HashSet::<PathBuf>::new();
     if let Err(err) = install_files(
         InstallMode::Move,
@@ -348,12 +996,37 @@ pub fn install_package(
         }
     }
 
-    if let Err(err) = db.add_package(
-        &package.name,
-        version,
-        &install_request.version,
-        &installed_files,
-    ) {
+    if let Err(err) = install_symlinks(
+        &mut installed_files,
+        &app.install_dir,
+        &install.symlinks,
+        &map,
+    )
+    .and_then(|()| {
+        install_launchers(
+            &mut installed_files,
+            &app.install_dir,
+            &install.launchers,
+            arch_os.os,
+            &map,
+        )
+    }) {
+        remove_installed_files(&ui, &app.install_dir, &installed_files)?;
+        return Err(err);
+    }
+
+    generate_files(app, &ui, &mut installed_files, &install.generate, &map);
+
+    if let Err(err) = expand_env(&app.install_dir, &install.env, &map).and_then(|env| {
+        db.add_package(
+            &package.name,
+            version,
+            &install_request.version,
+            &installed_files,
+        )?;
+        db.set_allow_prereleases(&package.name, allow_prereleases)?;
+        db.set_package_env(&package.name, &env)
+    }) {
         remove_installed_files(&ui, &app.install_dir, &installed_files)?;
         return Err(err);
     }
@@ -362,6 +1035,17 @@ pub fn install_package(
     fs::remove_dir_all(&unpack_dir)
         .with_context(|| format!("Failed to delete {}", unpack_dir.display()))?;
 
+    let mut files: Vec<_> = installed_files
+        .iter()
+        .map(|x| x.display().to_string())
+        .collect();
+    files.sort();
+    ui.emit(Event::Installed {
+        package: package.name.clone(),
+        version: version.to_string(),
+        files,
+    });
+
     Ok(())
 }
 
@@ -371,7 +1055,12 @@ mod tests {
     use std::collections::HashMap;
     use std::path::PathBuf;
 
-    use crate::test_file_utils::{create_tree, list_tree, pathbufset_from_strings};
+    use yare::parameterized;
+
+    use crate::checksum::compute_checksum;
+    use crate::test_file_utils::{
+        create_tree, get_fixture_path, list_tree, pathbufset_from_strings,
+    };
 
     #[test]
     fn test_parse_package_name_arg() {
@@ -389,6 +1078,169 @@ mod tests {
         );
     }
 
+    /// Creates an app whose store contains a `foo` package with a final release and two
+    /// pre-releases
+    fn create_app_with_prereleases(dir: &Path) -> App {
+        let app = App::new(dir).unwrap();
+        app.database.create().unwrap();
+        let asset_path = get_fixture_path("test_archive.tar.gz");
+        let url = format!("file://{}", asset_path.display()).replace('\\', "/");
+        let sha256 = compute_checksum(&asset_path).unwrap();
+        let releases: String = ["1.0.0", "1.1.0-rc.1", "1.2.0-rc.1"]
+            .iter()
+            .map(|version| {
+                format!(
+                    "
+              {version}:
+                any:
+                  url: {url}
+                  sha256: {sha256}"
+                )
+            })
+            .collect();
+        let package_dir = app.store_dir.join("foo");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(
+            package_dir.join("index.yaml"),
+            format!(
+                "
+            name: foo
+            description: desc
+            homepage:
+            releases:{releases}
+            installs:
+              1.0.0:
+                any:
+                  strip: 1
+                  files:
+                    bin/hello: bin/
+            "
+            ),
+        )
+        .unwrap();
+        app
+    }
+
+    /// Installs `foo` and returns its installed version and whether it allows pre-releases
+    fn install_foo(app: &App, allow_prereleases: Option<bool>) -> (Version, bool) {
+        install_cmd(
+            app,
+            &Ui::default(),
+            true, /* reinstall */
+            allow_prereleases,
+            &["foo".to_string()],
+        )
+        .unwrap();
+        let info = &app.database.get_installed_packages().unwrap()[0];
+        (info.installed_version.clone(), info.allow_prereleases)
+    }
+
+    #[test]
+    fn install_cmd_should_keep_the_prerelease_setting_unless_asked_to_change_it() {
+        // GIVEN a package with pre-releases
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = create_app_with_prereleases(&dir);
+
+        // WHEN it is installed with `--pre`
+        // THEN the latest pre-release is installed and pre-releases are allowed
+        let prerelease = Version::parse("1.2.0-rc.1").unwrap();
+        assert_eq!(install_foo(&app, Some(true)), (prerelease.clone(), true));
+
+        // WHEN it is reinstalled without `--pre` nor `--no-pre`
+        // THEN pre-releases are still allowed
+        assert_eq!(install_foo(&app, None), (prerelease, true));
+
+        // WHEN it is reinstalled with `--no-pre`
+        // THEN the final release is installed and pre-releases are no longer allowed
+        assert_eq!(
+            install_foo(&app, Some(false)),
+            (Version::new(1, 0, 0), false)
+        );
+
+        // WHEN it is reinstalled without `--pre` nor `--no-pre`
+        // THEN pre-releases are still not allowed
+        assert_eq!(install_foo(&app, None), (Version::new(1, 0, 0), false));
+    }
+
+    fn create_install(files: &[(&str, &str)]) -> Install {
+        Install {
+            strip: 0,
+            format: None,
+            files: files
+                .iter()
+                .map(|(src, dst)| (src.to_string(), dst.to_string()))
+                .collect(),
+            extra_files: BTreeMap::new(),
+            symlinks: BTreeMap::new(),
+            launchers: BTreeMap::new(),
+            generate: BTreeMap::new(),
+            env: BTreeMap::new(),
+            tests: vec![],
+        }
+    }
+
+    #[parameterized(
+        file = { "foo-1.0/foo", "bin/foo", Some("bin/foo") },
+        dir_dst = { "foo-1.0/foo${exe_ext}", "bin/", Some("bin/foo.exe") },
+        empty_dst = { "bin/foo${exe_ext}", "", Some("bin/foo.exe") },
+        optional = { "optional:bin/foo", "", Some("bin/foo") },
+        unknown_src = { "${asset_name}", "bin/foo", Some("bin/foo") },
+        unknown_src_name = { "${asset_name}", "bin/", None },
+        glob = { "completions/*.bash", "${bash_comp_dir}", None },
+        escaped_glob = { "doc/foo\\[1\\].txt", "share/doc/", Some("share/doc/foo[1].txt") },
+    )]
+    fn get_install_destination_should_return_known_destinations(
+        src: &str,
+        dst: &str,
+        expected: Option<&str>,
+    ) {
+        let vars = HashMap::from([
+            ("exe_ext".to_string(), ".exe".to_string()),
+            (
+                "bash_comp_dir".to_string(),
+                "share/bash-completions/completions/".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            get_install_destination(src, dst, &vars),
+            expected.map(|x| x.to_string())
+        );
+    }
+
+    #[test]
+    fn get_unpack_sources_should_expand_vars() {
+        // GIVEN an install using vars in its sources
+        let install = create_install(&[("bin/foo${exe_ext}", "bin/"), ("README.md", "${doc_dir}")]);
+        let vars = HashMap::from([("exe_ext".to_string(), ".exe".to_string())]);
+
+        // WHEN get_unpack_sources() is called
+        let sources = get_unpack_sources(&install, &vars);
+
+        // THEN the sources are expanded
+        assert_eq!(
+            sources,
+            Some(vec!["README.md".to_string(), "bin/foo.exe".to_string()])
+        );
+    }
+
+    #[test]
+    fn get_unpack_sources_should_return_none_for_glob_sources() {
+        let install = create_install(&[("bin/foo", ""), ("optional:man/*.1", "share/man/man1/")]);
+        assert_eq!(get_unpack_sources(&install, &HashMap::new()), None);
+    }
+
+    #[test]
+    fn get_unpack_sources_should_return_none_if_sources_are_not_known_yet() {
+        // GIVEN an install whose source depends on the unpacked asset
+        let install = create_install(&[("${asset_name}", "bin/foo")]);
+
+        // WHEN get_unpack_sources() is called before unpacking
+        let sources = get_unpack_sources(&install, &HashMap::new());
+
+        // THEN it returns None
+        assert_eq!(sources, None);
+    }
+
     #[test]
     fn install_files_should_copy_files() {
         // GIVEN an unpacked package with files:
@@ -475,6 +1327,427 @@ mod tests {
         );
     }
 
+    /// Calls install_files() with `files` on a package containing `pkg_files`. Returns the
+    /// result and the content of the install dir.
+    fn install_files_from_tree(
+        pkg_files: &[&str],
+        files: &[(&str, &str)],
+    ) -> (Result<()>, HashSet<PathBuf>) {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let pkg_dir = dir.join("pkg");
+        let inst_dir = dir.join("inst");
+        create_tree(&pkg_dir, pkg_files);
+        let files: BTreeMap<String, String> = files
+            .iter()
+            .map(|(src, dst)| (src.to_string(), dst.to_string()))
+            .collect();
+
+        let mut installed_files = HashSet::<PathBuf>::new();
+        let result = install_files(
+            InstallMode::Move,
+            &mut installed_files,
+            &pkg_dir,
+            &inst_dir,
+            &files,
+            &HashMap::new(),
+        );
+        if result.is_ok() {
+            assert_eq!(installed_files, list_tree(&inst_dir).unwrap());
+        }
+        (result, installed_files)
+    }
+
+    #[test]
+    fn install_files_should_install_glob_matches_in_the_destination_dir() {
+        // GIVEN a package containing man pages and completions
+        // WHEN install_files() is called with glob sources
+        let (result, installed_files) = install_files_from_tree(
+            &[
+                "man/foo.1",
+                "man/foo-bar.1",
+                "man/foo.5",
+                "completions/foo.bash",
+                "completions/foo.fish",
+                "completions/_foo",
+            ],
+            &[
+                ("man/*.1", "share/man/man1/"),
+                ("completions/*.{bash,fish}", "share/completions/"),
+            ],
+        );
+
+        // THEN the matching files are installed in the destination dirs
+        assert!(result.is_ok());
+        assert_eq!(
+            installed_files,
+            pathbufset_from_strings(&[
+                "share/man/man1/foo.1",
+                "share/man/man1/foo-bar.1",
+                "share/completions/foo.bash",
+                "share/completions/foo.fish",
+            ])
+        );
+    }
+
+    #[test]
+    fn install_files_should_keep_the_path_of_glob_matches_if_there_is_no_destination() {
+        // GIVEN a package containing license files in different dirs
+        // WHEN install_files() is called with a recursive glob and no destination
+        let (result, installed_files) = install_files_from_tree(
+            &["LICENSE", "vendor/bar/LICENSE-MIT", "vendor/bar/README.md"],
+            &[("**/LICENSE*", "")],
+        );
+
+        // THEN the matching files are installed with the same path
+        assert!(result.is_ok());
+        assert_eq!(
+            installed_files,
+            pathbufset_from_strings(&["LICENSE", "vendor/bar/LICENSE-MIT"])
+        );
+    }
+
+    #[test]
+    fn install_files_should_install_glob_matching_dirs_recursively() {
+        // GIVEN a package containing a versioned dir
+        // WHEN install_files() is called with a glob matching this dir
+        let (result, installed_files) = install_files_from_tree(
+            &["foo-1.2/bin/foo", "foo-1.2/lib/libfoo.so"],
+            &[("foo-*", "opt/")],
+        );
+
+        // THEN the content of the dir is installed
+        assert!(result.is_ok());
+        assert_eq!(
+            installed_files,
+            pathbufset_from_strings(&["opt/foo-1.2/bin/foo", "opt/foo-1.2/lib/libfoo.so"])
+        );
+    }
+
+    #[test]
+    fn install_files_should_fail_if_a_glob_matches_nothing() {
+        let (result, _) = install_files_from_tree(&["bin/foo"], &[("man/*.1", "share/man/man1/")]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn install_files_should_skip_optional_sources_matching_nothing() {
+        // GIVEN a package without man pages
+        // WHEN install_files() is called with optional sources for the man pages
+        let (result, installed_files) = install_files_from_tree(
+            &["bin/foo"],
+            &[
+                ("bin/foo", ""),
+                ("optional:man/*.1", "share/man/man1/"),
+                ("optional:README.md", "share/doc/foo/"),
+            ],
+        );
+
+        // THEN the other files are installed
+        assert!(result.is_ok());
+        assert_eq!(installed_files, pathbufset_from_strings(&["bin/foo"]));
+    }
+
+    #[test]
+    fn install_files_should_install_sources_with_escaped_glob_chars_as_is() {
+        // GIVEN a package containing files whose names contain glob characters
+        // WHEN install_files() is called with sources escaping these characters
+        let (result, installed_files) = install_files_from_tree(
+            &["doc/foo[1].txt", "doc/foo1.txt", "doc/what?.txt"],
+            &[("doc/foo\\[1\\].txt", ""), ("doc/what\\?.txt", "")],
+        );
+
+        // THEN only these files are installed
+        assert!(result.is_ok());
+        assert_eq!(
+            installed_files,
+            pathbufset_from_strings(&["doc/foo[1].txt", "doc/what?.txt"])
+        );
+    }
+
+    #[test]
+    fn install_files_should_install_existing_sources_with_glob_chars_as_is() {
+        // GIVEN a package containing a file whose name contains glob characters
+        // WHEN install_files() is called with the unescaped name of this file
+        let (result, installed_files) = install_files_from_tree(
+            &["doc/foo[1].txt", "doc/foo1.txt"],
+            &[("doc/foo[1].txt", "")],
+        );
+
+        // THEN the file is installed, as it was before glob patterns were supported
+        assert!(result.is_ok());
+        assert_eq!(
+            installed_files,
+            pathbufset_from_strings(&["doc/foo[1].txt"])
+        );
+    }
+
+    #[test]
+    fn install_files_should_fail_if_the_destination_of_a_glob_is_not_a_dir() {
+        let (result, _) =
+            install_files_from_tree(&["man/foo.1"], &[("man/*.1", "share/man/man1/foo.1")]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn get_relative_symlink_target_should_go_up_to_the_install_dir() {
+        assert_eq!(
+            get_relative_symlink_target(Path::new("bin/foo"), Path::new("opt/foo/foo")),
+            PathBuf::from("../opt/foo/foo")
+        );
+        assert_eq!(
+            get_relative_symlink_target(Path::new("foo"), Path::new("opt/foo/foo")),
+            PathBuf::from("opt/foo/foo")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn install_symlinks_should_create_relative_symlinks() {
+        // GIVEN an install dir containing opt/foo/foo
+        let dir = assert_fs::TempDir::new().unwrap();
+        create_tree(&dir, &["opt/foo/foo"]);
+
+        // WHEN install_symlinks() is called to create bin/foo, pointing to opt/foo/foo
+        let symlinks = BTreeMap::from([("bin/foo".to_string(), "opt/foo/foo".to_string())]);
+        let mut installed_files = HashSet::<PathBuf>::new();
+        install_symlinks(&mut installed_files, &dir, &symlinks, &HashMap::new()).unwrap();
+
+        // THEN the symlink is created, with a relative target
+        let link_path = dir.join("bin/foo");
+        assert_eq!(
+            fs::read_link(&link_path).unwrap(),
+            PathBuf::from("../opt/foo/foo")
+        );
+        assert_eq!(
+            link_path.canonicalize().unwrap(),
+            dir.join("opt/foo/foo").canonicalize().unwrap()
+        );
+
+        // AND it is added to the installed files
+        assert_eq!(installed_files, pathbufset_from_strings(&["bin/foo"]));
+    }
+
+    #[parameterized(
+        missing_target = { "bin/foo", "opt/foo/bar" },
+        link_going_up = { "../foo", "opt/foo/foo" },
+        target_going_up = { "bin/foo", "../opt/foo/foo" },
+        absolute_target = { "bin/foo", "/bin/sh" },
+    )]
+    fn install_symlinks_should_reject_invalid_symlinks(link: &str, target: &str) {
+        let dir = assert_fs::TempDir::new().unwrap();
+        create_tree(&dir, &["opt/foo/foo"]);
+
+        let symlinks = BTreeMap::from([(link.to_string(), target.to_string())]);
+        let mut installed_files = HashSet::<PathBuf>::new();
+        let result = install_symlinks(&mut installed_files, &dir, &symlinks, &HashMap::new());
+
+        assert!(result.is_err());
+        assert!(installed_files.is_empty());
+    }
+
+    #[parameterized(
+        linux = { Os::Linux, "bin/foo", "../opt/foo/foo" },
+        windows = { Os::Windows, "bin/foo.cmd", "..\\opt\\foo\\foo" },
+    )]
+    fn install_launchers_should_generate_scripts(
+        os: Os,
+        expected_path: &str,
+        expected_target: &str,
+    ) {
+        // GIVEN a launcher for opt/foo/foo
+        let dir = assert_fs::TempDir::new().unwrap();
+        create_tree(&dir, &["opt/foo/foo"]);
+        let launchers = BTreeMap::from([(
+            "bin/foo".to_string(),
+            Launcher {
+                target: "opt/foo/foo${exe_ext}".into(),
+                args: vec!["--data-dir".into()],
+                env: BTreeMap::new(),
+            },
+        )]);
+        let vars = HashMap::from([("exe_ext".to_string(), "".to_string())]);
+
+        // WHEN install_launchers() is called
+        let mut installed_files = HashSet::<PathBuf>::new();
+        install_launchers(&mut installed_files, &dir, &launchers, os, &vars).unwrap();
+
+        // THEN the script is generated, with a target relative to its dir
+        let script_path = dir.join(expected_path);
+        let script = fs::read_to_string(&script_path).unwrap();
+        assert!(script.contains(expected_target), "{script}");
+        assert!(script.contains("--data-dir"));
+
+        // AND it is added to the installed files
+        assert_eq!(installed_files, pathbufset_from_strings(&[expected_path]));
+
+        // AND it is executable
+        #[cfg(unix)]
+        assert!(crate::test_file_utils::is_file_executable(&script_path));
+    }
+
+    #[test]
+    fn install_launchers_should_fail_if_the_target_does_not_exist() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let launchers = BTreeMap::from([(
+            "bin/foo".to_string(),
+            Launcher {
+                target: "opt/foo/foo".into(),
+                ..Default::default()
+            },
+        )]);
+
+        let mut installed_files = HashSet::<PathBuf>::new();
+        let result = install_launchers(
+            &mut installed_files,
+            &dir,
+            &launchers,
+            Os::Linux,
+            &HashMap::new(),
+        );
+
+        assert!(result.is_err());
+        assert!(!dir.join("bin/foo").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn install_launchers_should_keep_working_when_the_install_dir_is_moved() {
+        // GIVEN a launcher for an executable printing its arguments
+        let dir = assert_fs::TempDir::new().unwrap();
+        let install_dir = dir.join("inst");
+        let target_path = install_dir.join("opt/foo/foo");
+        fs::create_dir_all(target_path.parent().unwrap()).unwrap();
+        fs::write(&target_path, "#!/bin/sh\necho \"$FOO $*\"\n").unwrap();
+        set_file_executable(&target_path).unwrap();
+        let launchers = BTreeMap::from([(
+            "bin/foo".to_string(),
+            Launcher {
+                target: "opt/foo/foo".into(),
+                args: vec!["--server".into()],
+                env: BTreeMap::from([("FOO".into(), "bar".into())]),
+            },
+        )]);
+        let mut installed_files = HashSet::<PathBuf>::new();
+        install_launchers(
+            &mut installed_files,
+            &install_dir,
+            &launchers,
+            Os::Linux,
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        // WHEN the install dir is moved
+        let new_install_dir = dir.join("moved");
+        fs::rename(&install_dir, &new_install_dir).unwrap();
+
+        // THEN the launcher still starts the executable
+        let output = Command::new(new_install_dir.join("bin/foo"))
+            .arg("baz")
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "bar --server baz\n"
+        );
+    }
+
+    /// Creates an install dir containing a `bin/foo` shell script running `script`
+    #[cfg(unix)]
+    fn create_install_dir_with_script(script: &str) -> assert_fs::TempDir {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let script_path = dir.join("inst/bin/foo");
+        fs::create_dir_all(script_path.parent().unwrap()).unwrap();
+        fs::write(&script_path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        set_file_executable(&script_path).unwrap();
+        fs::create_dir(dir.join("tmp")).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn generate_file_should_write_the_output_of_the_command() {
+        // GIVEN an installed executable printing its arguments
+        let dir = create_install_dir_with_script("echo \"complete $*\"");
+
+        // WHEN generate_file() is called with a command running it
+        let path = generate_file(
+            &dir.join("inst"),
+            &dir.join("tmp"),
+            "share/completions/foo.bash",
+            "foo completions bash",
+        )
+        .unwrap();
+
+        // THEN the output of the command is written to the destination
+        assert_eq!(path, PathBuf::from("share/completions/foo.bash"));
+        assert_eq!(
+            fs::read_to_string(dir.join("inst/share/completions/foo.bash")).unwrap(),
+            "complete completions bash\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[parameterized(
+        failing_command = { "echo partial; exit 1" },
+        no_output = { "true" },
+    )]
+    fn generate_file_should_fail_and_remove_the_file_if_the_command_fails(script: &str) {
+        // GIVEN an installed executable which fails
+        let dir = create_install_dir_with_script(script);
+
+        // WHEN generate_file() is called with a command running it
+        let result = generate_file(&dir.join("inst"), &dir.join("tmp"), "share/foo.1", "foo");
+
+        // THEN it fails
+        assert!(result.is_err());
+
+        // AND the destination is not left behind
+        assert!(!dir.join("inst/share/foo.1").exists());
+    }
+
+    #[parameterized(
+        not_installed = { "cargo --version" },
+        absolute = { "/bin/sh -c 'echo evil'" },
+        parent = { "../../bin/sh -c 'echo evil'" },
+    )]
+    fn generate_file_should_only_run_installed_executables(command: &str) {
+        let dir = assert_fs::TempDir::new().unwrap();
+        fs::create_dir_all(dir.join("inst/bin")).unwrap();
+
+        let result = generate_file(&dir.join("inst"), &dir.join("tmp"), "share/foo.1", command);
+
+        assert!(result.is_err());
+        assert!(!dir.join("inst/share/foo.1").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn generate_file_should_report_the_error_output_of_the_command() {
+        // GIVEN an installed executable which fails with an error message
+        let dir = create_install_dir_with_script("echo 'Unknown option' >&2; exit 1");
+
+        // WHEN generate_file() is called with a command running it
+        let result = generate_file(&dir.join("inst"), &dir.join("tmp"), "share/foo.1", "foo");
+
+        // THEN the error contains the error message
+        let err = result.unwrap_err();
+        assert!(format!("{err:#}").contains("Unknown option"), "{err:#}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn generate_file_should_run_the_command_in_a_new_dir() {
+        // GIVEN an installed executable creating a file in the current dir
+        let dir = create_install_dir_with_script("touch leftover; echo output");
+
+        // WHEN generate_file() is called with a command running it
+        generate_file(&dir.join("inst"), &dir.join("tmp"), "share/foo.1", "foo").unwrap();
+
+        // THEN the command ran in a dir which has been removed
+        assert_eq!(fs::read_dir(dir.join("tmp")).unwrap().count(), 0);
+    }
+
     #[test]
     fn install_files_should_merge_dirs() {
         // GIVEN a prefix with a `share/man/f1` file
@@ -515,4 +1788,91 @@ mod tests {
             HashSet::from([PathBuf::from("share/man/f2")])
         );
     }
+
+    #[test]
+    fn verify_asset_signature_should_fail_if_a_signed_package_asset_has_no_signature() {
+        // GIVEN a package defining a signing key
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = App::new(&dir).unwrap();
+        let package = Package::from_yaml_str(
+            "
+            name: foo
+            description: desc
+            homepage:
+            signing: !Minisign
+              public_key: RWQSNFZ4mrze8AOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4
+            releases:
+              1.0.0:
+                any:
+                  url: https://example.com/foo-1.0.0.tar.gz
+                  sha256: 0000000000000000000000000000000000000000000000000000000000000000
+            installs:
+              1.0.0:
+                any:
+                  files:
+                    foo: bin/
+            ",
+        )
+        .unwrap();
+
+        // AND an asset without signature
+        let version = Version::new(1, 0, 0);
+        let asset = Asset::new("https://example.com/foo-1.0.0.tar.gz", vec![]);
+
+        // WHEN verify_asset_signature() is called
+        let result = verify_asset_signature(
+            &app,
+            &Ui::default(),
+            &package,
+            &version,
+            &asset,
+            &dir.join("foo-1.0.0.tar.gz"),
+        );
+
+        // THEN it fails
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn unpack_asset_should_unpack_everything_if_hard_link_targets_are_skipped() {
+        // GIVEN a tar archive containing a `lib/foo` file, and a `bin/foo` hard link to it
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = App::new(&dir).unwrap();
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, "lib/foo", &b"foo"[..])
+            .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Link);
+        header.set_size(0);
+        builder
+            .append_link(&mut header, "bin/foo", "lib/foo")
+            .unwrap();
+        let archive_path = dir.join("foo.tar");
+        fs::write(&archive_path, builder.into_inner().unwrap()).unwrap();
+
+        // AND an install only installing the `bin` dir
+        let install = create_install(&[("bin", "bin")]);
+
+        // WHEN unpack_asset() is called
+        let unpack_dir = dir.join("unpack");
+        unpack_asset(
+            &app,
+            &archive_path,
+            &unpack_dir,
+            &install,
+            "foo",
+            &Version::new(1, 0, 0),
+        )
+        .unwrap();
+
+        // THEN the hard link has been created
+        assert_eq!(
+            fs::read_to_string(unpack_dir.join("bin/foo")).unwrap(),
+            "foo"
+        );
+    }
 }
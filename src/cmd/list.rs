@@ -7,14 +7,19 @@ use std::vec::Vec;
 use crate::app::App;
 use crate::db::PackageInfo;
 use crate::table::Table;
+use crate::ui::{Event, Ui};
 
 use anyhow::Result;
 use serde_json::{json, Value};
 
-pub fn list_cmd(app: &App, json: bool) -> Result<()> {
+pub fn list_cmd(app: &App, ui: &Ui, json: bool) -> Result<()> {
     let packages = app.database.get_installed_packages()?;
-    if json {
-        list_as_json(&packages);
+    if ui.is_json() {
+        ui.emit(Event::Data {
+            data: get_packages_json(&packages),
+        });
+    } else if json {
+        println!("{}", get_packages_json(&packages));
     } else {
         list_as_text(&packages);
     }
@@ -34,7 +39,7 @@ fn list_as_text(packages: &[PackageInfo]) {
     }
 }
 
-fn list_as_json(packages: &[PackageInfo]) {
+fn get_packages_json(packages: &[PackageInfo]) -> Value {
     let names: Vec<Value> = packages
         .iter()
         .map(|x| {
@@ -42,9 +47,9 @@ fn list_as_json(packages: &[PackageInfo]) {
                 "name": x.name.clone(),
                 "installed_version": x.installed_version.to_string(),
                 "requested_version": x.requested_version.to_string(),
+                "allow_prereleases": x.allow_prereleases,
             })
         })
         .collect();
-    let array = json!(names);
-    println!("{}", array);
+    json!(names)
 }
@@ -3,10 +3,19 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 mod doc;
+mod doctor;
+mod downgrade;
+mod env;
+mod fetch;
 mod install;
 mod list;
+mod outdated;
+mod owns;
+mod pin;
+mod provides;
 mod search;
 mod setup;
+mod shell_init;
 mod show;
 mod uninstall;
 mod update;
@@ -14,13 +23,33 @@ mod upgrade;
 
 pub use doc::doc_cmd;
 
-pub use install::{install_cmd, install_package, install_packages, InstallRequest};
+pub use doctor::doctor_cmd;
+
+pub use downgrade::downgrade_cmd;
+
+pub use env::env_cmd;
+
+pub use fetch::fetch_cmd;
+
+pub use install::{
+    get_install_destinations, install_cmd, install_package, install_packages, InstallRequest,
+};
 
 pub use list::list_cmd;
 
+pub use outdated::outdated_cmd;
+
+pub use owns::owns_cmd;
+
+pub use pin::{pin_cmd, unpin_cmd};
+
+pub use provides::provides_cmd;
+
 pub use search::search_cmd;
 
-pub use setup::setup_cmd;
+pub use setup::{setup_cmd, RcAction};
+
+pub use shell_init::shell_init_cmd;
 
 pub use show::show_cmd;
 
@@ -0,0 +1,68 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::app::App;
+use crate::cmd::upgrade::{get_upgrades, Upgrade};
+use crate::table::Table;
+use crate::ui::{Event, Ui};
+
+fn outdated_as_text(upgrades: &[Upgrade], blocked_upgrades: &[Upgrade]) {
+    let table = Table::new(&[30, 12, 12, 12, 8]);
+    table.add_row(&["Package", "Installed", "Available", "Requested", "Blocked"]);
+    table.add_separator();
+    let rows = upgrades
+        .iter()
+        .map(|x| (x, ""))
+        .chain(blocked_upgrades.iter().map(|x| (x, "yes")));
+    for (upgrade, blocked) in rows {
+        table.add_row(&[
+            &upgrade.package_info.name,
+            &upgrade.package_info.installed_version.to_string(),
+            &upgrade.available_version.to_string(),
+            &upgrade.package_info.requested_version.to_string(),
+            blocked,
+        ]);
+    }
+}
+
+fn get_outdated_json(upgrades: &[Upgrade], blocked_upgrades: &[Upgrade]) -> Value {
+    let rows = upgrades
+        .iter()
+        .map(|x| (x, false))
+        .chain(blocked_upgrades.iter().map(|x| (x, true)));
+    let packages: Vec<Value> = rows
+        .map(|(upgrade, blocked)| {
+            json!({
+                "name": upgrade.package_info.name,
+                "installed_version": upgrade.package_info.installed_version.to_string(),
+                "available_version": upgrade.available_version.to_string(),
+                "requested_version": upgrade.package_info.requested_version.to_string(),
+                "blocked": blocked,
+            })
+        })
+        .collect();
+    json!(packages)
+}
+
+/// List the installed packages which have a newer version available. Returns true if at least
+/// one of them can be upgraded without changing its requested version.
+pub fn outdated_cmd(app: &App, ui: &Ui, json: bool) -> Result<bool> {
+    let (upgrades, blocked_upgrades) = get_upgrades(ui, &*app.store, &app.database, false)?;
+
+    if ui.is_json() {
+        ui.emit(Event::Data {
+            data: get_outdated_json(&upgrades, &blocked_upgrades),
+        });
+    } else if json {
+        println!("{}", get_outdated_json(&upgrades, &blocked_upgrades));
+    } else if upgrades.is_empty() && blocked_upgrades.is_empty() {
+        ui.info("All packages are up-to-date");
+    } else {
+        outdated_as_text(&upgrades, &blocked_upgrades);
+    }
+    Ok(!upgrades.is_empty())
+}
@@ -0,0 +1,128 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde_json::json;
+
+use crate::app::App;
+use crate::ui::{Event, Ui};
+
+/// Returns true if `arg` looks like a command name rather than a path
+fn is_command_name(arg: &str) -> bool {
+    let has_separator = arg.contains('/') || (cfg!(windows) && arg.contains('\\'));
+    !has_separator && arg != "." && arg != ".."
+}
+
+/// Returns `path` relative to `install_dir` if it's inside it. The last component of `path` is not
+/// resolved, since it can be a symlink installed by a package.
+fn strip_install_dir(install_dir: &Path, path: &Path) -> Option<PathBuf> {
+    if let Ok(rel_path) = path.strip_prefix(install_dir) {
+        return Some(rel_path.to_path_buf());
+    }
+    let install_dir = install_dir.canonicalize().ok()?;
+    let parent = path.parent()?.canonicalize().ok()?;
+    let path = parent.join(path.file_name()?);
+    path.strip_prefix(install_dir).ok().map(|x| x.to_path_buf())
+}
+
+/// Returns the path, relative to `install_dir`, designated by `arg`. `arg` can be the name of a
+/// command installed in `install_dir/bin`, a path relative to the current directory, an absolute
+/// path or a path relative to `install_dir`.
+fn find_install_path(install_dir: &Path, cwd: &Path, arg: &str) -> Result<PathBuf> {
+    let bin_dir = install_dir.join("bin");
+    if is_command_name(arg) {
+        if let Ok(path) = which::which_in(arg, Some(&bin_dir), cwd) {
+            if let Some(rel_path) = strip_install_dir(install_dir, &path) {
+                return Ok(rel_path);
+            }
+        }
+    }
+    let path = cwd.join(arg);
+    if path.exists() || path.is_symlink() {
+        return strip_install_dir(install_dir, &path)
+            .ok_or_else(|| anyhow!("{path:?} is not in Clyde install dir ({install_dir:?})"));
+    }
+    if install_dir.join(arg).exists() {
+        return Ok(PathBuf::from(arg));
+    }
+    Err(anyhow!("Can't find a file or a command named '{arg}'"))
+}
+
+/// Shows the installed package owning a file or a command
+pub fn owns_cmd(app: &App, ui: &Ui, path_or_command: &str) -> Result<()> {
+    let cwd = env::current_dir()?;
+    let rel_path = find_install_path(&app.install_dir, &cwd, path_or_command)?;
+    let package_name = app
+        .database
+        .get_file_owner(&rel_path)?
+        .ok_or_else(|| anyhow!("No installed package owns {:?}", rel_path))?;
+    let version = app
+        .database
+        .get_package_version(&package_name)?
+        .map(|x| x.to_string())
+        .unwrap_or_default();
+    if ui.is_json() {
+        ui.emit(Event::Data {
+            data: json!({
+                "path": rel_path,
+                "package": package_name,
+                "version": version,
+            }),
+        });
+    } else {
+        println!(
+            "{} is owned by {package_name} {version}",
+            rel_path.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_file_utils::create_tree;
+
+    #[test]
+    fn find_install_path_should_accept_commands_and_paths() {
+        // GIVEN an install dir containing a file in bin and a man page
+        let dir = assert_fs::TempDir::new().unwrap();
+        let install_dir = dir.join("inst");
+        create_tree(&install_dir, &["bin/foo", "share/man/man1/foo.1"]);
+        #[cfg(unix)]
+        crate::file_utils::set_file_executable(&install_dir.join("bin/foo")).unwrap();
+        let cwd = dir.join("inst/share");
+
+        // WHEN find_install_path() is called with a command
+        // THEN it finds the command in bin
+        #[cfg(unix)]
+        assert_eq!(
+            find_install_path(&install_dir, &cwd, "foo").unwrap(),
+            PathBuf::from("bin/foo")
+        );
+
+        // WHEN find_install_path() is called with a path relative to the current dir
+        // THEN it returns it relative to the install dir
+        assert_eq!(
+            find_install_path(&install_dir, &cwd, "man/man1/foo.1").unwrap(),
+            PathBuf::from("share/man/man1/foo.1")
+        );
+
+        // WHEN find_install_path() is called with a path relative to the install dir
+        // THEN it returns it as is
+        assert_eq!(
+            find_install_path(&install_dir, &dir, "share/man/man1/foo.1").unwrap(),
+            PathBuf::from("share/man/man1/foo.1")
+        );
+
+        // WHEN find_install_path() is called with a path outside the install dir
+        // THEN it fails
+        create_tree(&dir, &["other/foo"]);
+        assert!(find_install_path(&install_dir, &dir, "other/foo").is_err());
+    }
+}
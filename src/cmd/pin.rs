@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{anyhow, Result};
+
+use crate::app::App;
+use crate::cmd::install::parse_package_name_arg;
+use crate::db::Database;
+use crate::ui::Ui;
+use crate::version::VersionReq;
+
+/// Set the requested version of `package_name`. Warn if the installed version does not match it.
+fn set_pin(ui: &Ui, db: &Database, package_name: &str, version_req: &VersionReq) -> Result<()> {
+    let installed_version = db
+        .get_package_version(package_name)?
+        .ok_or_else(|| anyhow!("Package {} is not installed", package_name))?;
+
+    db.set_requested_version(package_name, version_req)?;
+
+    if !version_req.matches(&installed_version) {
+        ui.warn(&format!(
+            "Installed version of {package_name} ({installed_version}) does not match {version_req}. Use `clyde upgrade {package_name}` or `clyde downgrade {package_name}` to install a matching version."
+        ));
+    }
+    Ok(())
+}
+
+/// Pin `package_name_arg` to the version requirement following its `@`, or to its installed
+/// version if there is no `@`
+pub fn pin_cmd(app: &App, ui: &Ui, package_name_arg: &str) -> Result<()> {
+    let request = parse_package_name_arg(package_name_arg)?;
+    let version_req = if package_name_arg.contains('@') {
+        request.version
+    } else {
+        let installed_version = app
+            .database
+            .get_package_version(&request.name)?
+            .ok_or_else(|| anyhow!("Package {} is not installed", request.name))?;
+        VersionReq::exact(&installed_version)
+    };
+    set_pin(ui, &app.database, &request.name, &version_req)?;
+    ui.info(&format!("Pinned {} to {version_req}", request.name));
+    Ok(())
+}
+
+pub fn unpin_cmd(app: &App, ui: &Ui, package_names: &[String]) -> Result<()> {
+    for name in package_names {
+        set_pin(ui, &app.database, name, &VersionReq::STAR)?;
+        ui.info(&format!("Unpinned {name}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    use crate::version::Version;
+
+    #[test]
+    fn exact_version_req_should_only_match_the_version() {
+        let version = Version::parse("1.2.3").unwrap();
+        let version_req = VersionReq::exact(&version);
+        assert_eq!(version_req.to_string(), "=1.2.3");
+        assert!(version_req.matches(&version));
+        assert!(!version_req.matches(&Version::new(1, 2, 4)));
+    }
+
+    #[test]
+    fn set_pin_should_accept_requirements_not_matching_installed_version() {
+        // GIVEN a database with foo 1.2.0 installed
+        let db = Database::new_in_memory().unwrap();
+        db.create().unwrap();
+        db.add_package(
+            "foo",
+            &Version::new(1, 2, 0),
+            &VersionReq::STAR,
+            &HashSet::new(),
+        )
+        .unwrap();
+
+        // WHEN pinning foo to a requirement which does not match 1.2.0
+        let version_req = VersionReq::parse("1.3.*").unwrap();
+        let result = set_pin(&Ui::default(), &db, "foo", &version_req);
+
+        // THEN it succeeds (only a warning is printed)
+        assert!(result.is_ok(), "{:?}", result);
+
+        // AND the requirement is stored
+        let packages = db.get_installed_packages().unwrap();
+        assert_eq!(packages[0].requested_version, version_req);
+    }
+}
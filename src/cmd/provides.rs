@@ -0,0 +1,350 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::app::App;
+use crate::arch_os::{ArchOs, Os};
+use crate::cmd::get_install_destinations;
+use crate::package::Package;
+use crate::ui::{Event, Ui};
+
+const INDEX_FILE_NAME: &str = "provides-index.json";
+
+/// Maps the commands provided by the packages of the store to the names of these packages. It is
+/// stored in Clyde home, and rebuilt when one of the other fields changes.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+struct ProvidesIndex {
+    clyde_version: String,
+    store_revision: String,
+    cooldown_days: usize,
+    arch_os: String,
+    /// The installed version of each installed package: the commands of installed packages are
+    /// read from their installed files
+    installed_packages: BTreeMap<String, String>,
+    commands: BTreeMap<String, Vec<String>>,
+}
+
+impl ProvidesIndex {
+    /// Creates an empty index for the current state of `app`
+    fn new(app: &App) -> Result<ProvidesIndex> {
+        let installed_packages = app
+            .database
+            .get_installed_packages()?
+            .into_iter()
+            .map(|x| (x.name, x.installed_version.to_string()))
+            .collect();
+        Ok(ProvidesIndex {
+            clyde_version: env!("CARGO_PKG_VERSION").to_string(),
+            store_revision: app.store.get_revision()?,
+            cooldown_days: app.store.cooldown_days(),
+            arch_os: app.arch_os.to_str(),
+            installed_packages,
+            commands: BTreeMap::new(),
+        })
+    }
+
+    /// True if `self` has been built for the same state as `other`
+    fn has_same_key(&self, other: &ProvidesIndex) -> bool {
+        self.clyde_version == other.clyde_version
+            && self.store_revision == other.store_revision
+            && self.cooldown_days == other.cooldown_days
+            && self.arch_os == other.arch_os
+            && self.installed_packages == other.installed_packages
+    }
+}
+
+/// Returns the name of the command installed at `path`, a path relative to the install dir, if
+/// it's an executable of the `bin` dir
+fn get_command_name(path: &Path, os: Os) -> Option<String> {
+    let mut components = path.components().filter(|x| *x != Component::CurDir);
+    if components.next()? != Component::Normal("bin".as_ref()) {
+        return None;
+    }
+    let Component::Normal(name) = components.next()? else {
+        return None;
+    };
+    if components.next().is_some() {
+        return None;
+    }
+    let name = name.to_str()?;
+    if os == Os::Windows {
+        let lower_name = name.to_lowercase();
+        for ext in [".exe", ".cmd", ".bat"] {
+            if lower_name.ends_with(ext) {
+                return Some(name[..name.len() - ext.len()].to_string());
+            }
+        }
+    }
+    Some(name.to_string())
+}
+
+/// Returns the commands found in `paths`, sorted and without duplicates
+fn get_command_names<'a>(paths: impl Iterator<Item = &'a PathBuf>, os: Os) -> Vec<String> {
+    let mut commands: Vec<String> = paths.filter_map(|x| get_command_name(x, os)).collect();
+    commands.sort();
+    commands.dedup();
+    commands
+}
+
+/// Returns true if `path` is a directory installed recursively which could contain commands
+fn may_contain_commands(path: &Path) -> bool {
+    let mut components = path.components().filter(|x| *x != Component::CurDir);
+    match components.next() {
+        None => true,
+        Some(first) => first == Component::Normal("bin".as_ref()) && components.next().is_none(),
+    }
+}
+
+/// Returns the commands the latest version of `package` installs for `arch_os`, and the
+/// entries whose commands cannot be known without the asset: globs, and directories installed
+/// recursively
+fn get_provided_commands(package: &Package, arch_os: &ArchOs) -> (Vec<String>, Vec<String>) {
+    let Some(version) = package.get_latest_version() else {
+        return (vec![], vec![]);
+    };
+    let (destinations, mut unknown_entries) = get_install_destinations(package, version, arch_os);
+    unknown_entries.extend(
+        destinations
+            .iter()
+            .filter(|x| may_contain_commands(x))
+            .map(|x| x.display().to_string()),
+    );
+    (
+        get_command_names(destinations.iter(), arch_os.os),
+        unknown_entries,
+    )
+}
+
+fn build_index(app: &App, ui: &Ui) -> Result<ProvidesIndex> {
+    let (packages, errors) = app.store.list_packages()?;
+    for error in errors {
+        ui.warn(&format!("{error:#}"));
+    }
+    let mut index = ProvidesIndex::new(app)?;
+    for package in &packages {
+        let commands = if index.installed_packages.contains_key(&package.name) {
+            let files = app.database.get_package_files(&package.name)?;
+            get_command_names(files.iter(), app.arch_os.os)
+        } else {
+            let (commands, unknown_entries) = get_provided_commands(package, &app.arch_os);
+            if !unknown_entries.is_empty() {
+                ui.warn(&format!(
+                    "Some commands of {} may be missing: the content of {} is only known once it is installed",
+                    package.name,
+                    unknown_entries.join(", ")
+                ));
+            }
+            commands
+        };
+        for command in commands {
+            index
+                .commands
+                .entry(command)
+                .or_default()
+                .push(package.name.clone());
+        }
+    }
+    for names in index.commands.values_mut() {
+        names.sort();
+        names.dedup();
+    }
+    Ok(index)
+}
+
+/// Loads the index, rebuilding it if it's missing or outdated
+fn load_index(app: &App, ui: &Ui) -> Result<ProvidesIndex> {
+    let index_path = app.home.join(INDEX_FILE_NAME);
+    let key = ProvidesIndex::new(app)?;
+    if let Ok(content) = fs::read_to_string(&index_path) {
+        if let Ok(index) = serde_json::from_str::<ProvidesIndex>(&content) {
+            if index.has_same_key(&key) {
+                return Ok(index);
+            }
+        }
+    }
+    ui.info("Indexing the commands provided by packages");
+    let index = build_index(app, ui)?;
+    fs::write(&index_path, serde_json::to_string(&index)?)?;
+    Ok(index)
+}
+
+/// Shows the packages of the store which provide `command`
+pub fn provides_cmd(app: &App, ui: &Ui, command: &str) -> Result<()> {
+    let index = load_index(app, ui)?;
+    let package_names = index
+        .commands
+        .get(command)
+        .ok_or_else(|| anyhow!("No package provides '{command}'"))?;
+
+    let mut packages = Vec::new();
+    for name in package_names {
+        let installed_version = app.database.get_package_version(name)?;
+        packages.push((name, installed_version));
+    }
+
+    if ui.is_json() {
+        let data = packages
+            .iter()
+            .map(|(name, installed_version)| {
+                json!({
+                    "package": name,
+                    "installed_version": installed_version.as_ref().map(|x| x.to_string()),
+                })
+            })
+            .collect();
+        ui.emit(Event::Data { data });
+    } else {
+        for (name, installed_version) in packages {
+            match installed_version {
+                Some(version) => println!("{name} (installed: {version})"),
+                None => println!("{name}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    use yare::parameterized;
+
+    use crate::version::{Version, VersionReq};
+
+    #[parameterized(
+        bin = { "bin/foo", Os::Linux, Some("foo") },
+        cur_dir = { "./bin/foo", Os::Linux, Some("foo") },
+        windows_exe = { "bin/foo.exe", Os::Windows, Some("foo") },
+        windows_cmd = { "bin/foo.cmd", Os::Windows, Some("foo") },
+        linux_exe = { "bin/foo.exe", Os::Linux, Some("foo.exe") },
+        sub_dir = { "bin/foo/bar", Os::Linux, None },
+        bin_dir = { "bin", Os::Linux, None },
+        not_bin = { "share/man/man1/foo.1", Os::Linux, None },
+    )]
+    fn get_command_name_should_only_accept_executables_in_bin(
+        path: &str,
+        os: Os,
+        expected: Option<&str>,
+    ) {
+        assert_eq!(
+            get_command_name(Path::new(path), os),
+            expected.map(|x| x.to_string())
+        );
+    }
+
+    #[test]
+    fn get_provided_commands_should_list_installed_commands() {
+        // GIVEN a package installing an executable, a launcher and a man page
+        let package = Package::from_yaml_str(
+            "
+            name: foo
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                any:
+                  url: https://example.com/foo-1.0.0.tar.gz
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+            installs:
+              1.0.0:
+                any:
+                  files:
+                    foo-1.0.0/foo${exe_ext}: bin/
+                    foo-1.0.0/foo.1: ${man_dir}man1/
+                  launchers:
+                    bin/foo-server:
+                      target: bin/foo${exe_ext}
+            ",
+        )
+        .unwrap();
+
+        // WHEN get_provided_commands() is called
+        let (commands, unknown_entries) =
+            get_provided_commands(&package, &ArchOs::parse("x86_64-linux").unwrap());
+
+        // THEN it returns the executable and the launcher
+        assert_eq!(commands, vec!["foo".to_string(), "foo-server".to_string()]);
+        assert!(unknown_entries.is_empty());
+    }
+
+    /// The YAML of a package installing its executables using a glob and a directory copy
+    const UNKNOWN_COMMANDS_PACKAGE: &str = "
+        name: foo
+        description: desc
+        homepage:
+        releases:
+          1.0.0:
+            any:
+              url: https://example.com/foo-1.0.0.tar.gz
+              sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+        installs:
+          1.0.0:
+            any:
+              files:
+                foo-1.0.0/foo-*: bin/
+                foo-1.0.0/bin: bin
+        ";
+
+    #[test]
+    fn get_provided_commands_should_report_globs_and_directory_copies() {
+        // GIVEN a package installing its executables using a glob and a directory copy
+        let package = Package::from_yaml_str(UNKNOWN_COMMANDS_PACKAGE).unwrap();
+
+        // WHEN get_provided_commands() is called
+        let (commands, unknown_entries) =
+            get_provided_commands(&package, &ArchOs::parse("x86_64-linux").unwrap());
+
+        // THEN no command is returned, but the glob and the directory are reported
+        assert!(commands.is_empty());
+        assert_eq!(
+            unknown_entries,
+            vec!["foo-1.0.0/foo-*".to_string(), "bin".to_string()]
+        );
+    }
+
+    #[test]
+    fn load_index_should_use_the_files_of_installed_packages() {
+        // GIVEN a store containing a package whose commands cannot be known without installing it
+        let dir = assert_fs::TempDir::new().unwrap();
+        let app = App::new(&dir).unwrap();
+        app.database.create().unwrap();
+        let package_dir = app.store_dir.join("foo");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(package_dir.join("index.yaml"), UNKNOWN_COMMANDS_PACKAGE).unwrap();
+        let ui = Ui::default();
+
+        // WHEN the index is loaded
+        let index = load_index(&app, &ui).unwrap();
+
+        // THEN it does not contain commands
+        assert!(index.commands.is_empty());
+
+        // WHEN the package is installed
+        app.database
+            .add_package(
+                "foo",
+                &Version::new(1, 0, 0),
+                &VersionReq::STAR,
+                &HashSet::from([PathBuf::from("bin/foo-cli"), PathBuf::from("bin/foo-gui")]),
+            )
+            .unwrap();
+
+        // THEN the index is rebuilt, using the installed files
+        let index = load_index(&app, &ui).unwrap();
+        assert_eq!(
+            index.commands.keys().collect::<Vec<_>>(),
+            vec!["foo-cli", "foo-gui"]
+        );
+    }
+}
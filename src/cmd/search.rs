@@ -3,13 +3,20 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use anyhow::Result;
+use serde_json::json;
 
 use crate::app::App;
-use crate::ui::Ui;
+use crate::ui::{Event, Ui};
 
 pub fn search_cmd(app: &App, ui: &Ui, query: &str) -> Result<()> {
     let (results, errors) = app.store.search(query)?;
-    if results.is_empty() {
+    if ui.is_json() {
+        let data = results
+            .iter()
+            .map(|x| json!({"name": x.name, "description": x.description}))
+            .collect();
+        ui.emit(Event::Data { data });
+    } else if results.is_empty() {
         eprintln!("No packages found matching '{query}'");
     } else {
         for result in results {
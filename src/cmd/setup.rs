@@ -3,79 +3,121 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::fs;
-use std::include_str;
-use std::io;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 
-use anyhow::{anyhow, Result};
-use semver::VersionReq;
-use shell_words::quote;
+use anyhow::{anyhow, Context, Result};
 
 use crate::app::App;
 use crate::cmd::{install_package, InstallRequest};
+use crate::shell::rc_file::{
+    add_block, create_block, find_user_shells, get_rc_file_path, remove_block, RC_SHELLS,
+};
+use crate::shell::{Shell, ACTIVATION_SHELLS};
 use crate::ui::Ui;
-
-const SH_INIT: &str = include_str!("activate.sh.tmpl");
+use crate::version::VersionReq;
 
 const CLYDE_STORE_URL: &str = "https://github.com/agateau/clyde-store";
 
-fn posix_shell_path_from_path(path: &Path) -> String {
-    quote(path.to_str().unwrap()).to_string()
-}
-
-fn shell_path_from_path(path: &Path) -> Result<String> {
-    if cfg!(not(target_os = "windows")) {
-        return Ok(posix_shell_path_from_path(path));
-    }
+/// Creates the activation scripts of all supported shells, returns the path of the POSIX one
+fn create_activation_scripts(app: &App) -> Result<PathBuf> {
+    let scripts_dir = app.home.join("scripts");
+    fs::create_dir_all(&scripts_dir)?;
 
-    let output = match Command::new("cygpath").arg(path).output() {
-        Ok(x) => x,
-        Err(e) => {
-            if e.kind() != io::ErrorKind::NotFound {
-                // TODO Try to use anyhow context() here?
-                return Err(anyhow!("Failed to run cygpath"));
-            }
-            // No cygpath executable, ignore the error, assume we are in an environment which does
-            // not need it
-            return Ok(posix_shell_path_from_path(path));
-        }
-    };
-    if !output.status.success() {
-        let code = output.status.code().unwrap();
-        return Err(anyhow!(
-            "cygpath exited with code {code}:\n{}",
-            output.status.code().unwrap()
-        ));
+    for shell in ACTIVATION_SHELLS {
+        let content = shell.create_activation_script(&app.home)?;
+        fs::write(scripts_dir.join(shell.activation_script_name()), content)?;
     }
-    let cygpath_output = String::from_utf8(output.stdout).unwrap();
-    Ok(quote(cygpath_output.trim()).to_string())
+    Ok(scripts_dir.join(Shell::Sh.activation_script_name()))
 }
 
-fn create_activate_script(app: &App) -> Result<String> {
-    let clyde_home = shell_path_from_path(&app.home)?;
-    let content = SH_INIT.replace("@CLYDE_HOME@", &clyde_home);
-
-    let scripts_dir = app.home.join("scripts");
-    let script_path = scripts_dir.join("activate.sh");
+fn update_activation_scripts(ui: &Ui, home: &Path) -> Result<()> {
+    let app = App::new(home)?;
+    ui.info("Updating activation scripts");
+    create_activation_scripts(&app)?;
+    Ok(())
+}
 
-    fs::create_dir_all(&scripts_dir)?;
-    fs::write(&script_path, content)?;
+/// What to do with the startup scripts of the shells of the user
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RcAction {
+    Modify,
+    Unmodify,
+}
 
-    let shell_script_path = shell_path_from_path(&script_path)?;
-    Ok(shell_script_path)
+/// Adds a block loading the activation script to the startup scripts of the shells of the user
+fn modify_rc_files(ui: &Ui, home: &Path) -> Result<()> {
+    let shells = find_user_shells();
+    if shells.is_empty() {
+        ui.warn("Could not find the startup scripts of your shells, you have to add the activation script yourself");
+        return Ok(());
+    }
+    let scripts_dir = home.join("scripts");
+    let mut modified = false;
+    for (shell, rc_path) in shells {
+        let script_path = scripts_dir.join(shell.activation_script_name());
+        let block = create_block(&shell.source_command(&script_path)?);
+        let content = if rc_path.exists() {
+            fs::read_to_string(&rc_path)?
+        } else {
+            String::new()
+        };
+        let new_content = add_block(&content, &block, shell)
+            .with_context(|| format!("Failed to modify {rc_path:?}"))?;
+        if new_content == content {
+            ui.info(&format!("{rc_path:?} already loads the activation script"));
+            continue;
+        }
+        ui.info(&format!("Adding activation script to {rc_path:?}"));
+        if let Some(parent) = rc_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&rc_path, new_content)?;
+        modified = true;
+    }
+    if modified {
+        eprintln!("\nRestart your shell to activate your Clyde installation.");
+    }
+    Ok(())
 }
 
-fn update_activate_script(ui: &Ui, home: &Path) -> Result<()> {
-    let app = App::new(home)?;
-    ui.info("Updating activation script");
-    create_activate_script(&app)?;
+/// Removes the blocks added by `modify_rc_files()`
+fn unmodify_rc_files(ui: &Ui) -> Result<()> {
+    for shell in RC_SHELLS {
+        let Some(rc_path) = get_rc_file_path(shell) else {
+            continue;
+        };
+        if !rc_path.exists() {
+            continue;
+        }
+        let content = fs::read_to_string(&rc_path)?;
+        let new_content =
+            remove_block(&content).with_context(|| format!("Failed to modify {rc_path:?}"))?;
+        if let Some(new_content) = new_content {
+            ui.info(&format!("Removing activation script from {rc_path:?}"));
+            fs::write(&rc_path, new_content)?;
+        }
+    }
     Ok(())
 }
 
-pub fn setup_cmd(ui: &Ui, home: &Path, update_scripts: bool, url: Option<&str>) -> Result<()> {
-    if update_scripts {
-        return update_activate_script(ui, home);
+pub fn setup_cmd(
+    ui: &Ui,
+    home: &Path,
+    update_scripts: bool,
+    rc_action: Option<RcAction>,
+    url: Option<&str>,
+) -> Result<()> {
+    if rc_action == Some(RcAction::Unmodify) {
+        return unmodify_rc_files(ui);
+    }
+    // Modifying the startup scripts of an existing installation must be possible, so that the
+    // command can safely be run more than once
+    if update_scripts || (rc_action == Some(RcAction::Modify) && home.exists()) {
+        update_activation_scripts(ui, home)?;
+        if rc_action == Some(RcAction::Modify) {
+            modify_rc_files(ui, home)?;
+        }
+        return Ok(());
     }
 
     let url = url.unwrap_or(CLYDE_STORE_URL);
@@ -103,59 +145,16 @@ pub fn setup_cmd(ui: &Ui, home: &Path, update_scripts: bool, url: Option<&str>)
         &InstallRequest::new("clyde", VersionReq::STAR),
     )?;
 
-    ui.info("Creating activation script");
-    let shell_script_path = create_activate_script(&app)?;
+    ui.info("Creating activation scripts");
+    let script_path = create_activation_scripts(&app)?;
+    if rc_action == Some(RcAction::Modify) {
+        return modify_rc_files(ui, home);
+    }
+    let source_command = Shell::Sh.source_command(&script_path)?;
 
     eprintln!("\nAll set! To activate your Clyde installation, add this line to your shell startup script:\n\n\
-              . {shell_script_path}");
+              {source_command}\n\n\
+              For fish, Nushell, PowerShell or Elvish, load the matching `activate.*` script from the same directory instead.");
 
     Ok(())
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use which::which;
-
-    #[test]
-    fn shell_path_from_path_should_quote_correctly() {
-        if which("sh").is_err() {
-            // Skip test, can't be run in this environment
-            println!("Skipped");
-            return;
-        }
-        for input_path in ["/foo bar", "/it's quoted"] {
-            // GIVEN a path with characters requiring quoting
-            let shell_path = shell_path_from_path(Path::new(input_path)).unwrap();
-
-            // WHEN the result path is passed to a shell to run "echo -n $result_path"
-            let echo_output = Command::new("sh")
-                .arg("-c")
-                .arg(format!("echo {shell_path}"))
-                .output()
-                .unwrap();
-
-            // THEN the output of the shell is input_path
-            let echo_path = String::from_utf8(echo_output.stdout).unwrap();
-            let echo_path = echo_path.trim();
-            assert_eq!(input_path, echo_path);
-        }
-    }
-
-    #[test]
-    #[cfg(windows)]
-    fn shell_path_from_path_should_use_cygpath() {
-        if which("cygpath").is_err() {
-            // Skip test, can't be run in this environment
-            println!("Skipped");
-            return;
-        }
-        for (input, expected) in [
-            (r"C:\foo", "/c/foo"),
-            (r"C:\foo with spaces", "'/c/foo with spaces'"),
-        ] {
-            let output = shell_path_from_path(Path::new(input)).unwrap();
-            assert_eq!(output, expected.to_string());
-        }
-    }
-}
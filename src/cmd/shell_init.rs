@@ -0,0 +1,16 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::shell::Shell;
+
+/// Prints the activation script for `shell`, so that it can be evaluated from the startup script
+/// of the shell
+pub fn shell_init_cmd(home: &Path, shell: Shell) -> Result<()> {
+    print!("{}", shell.create_activation_script(home)?);
+    Ok(())
+}
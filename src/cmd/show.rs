@@ -3,9 +3,10 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use anyhow::Result;
-use serde_json::json;
+use serde_json::{json, Value};
 
 use crate::app::App;
+use crate::ui::{Event, Ui};
 
 fn get_file_list(app: &App, package_name: &str) -> Result<Vec<String>> {
     let fileset = app.database.get_package_files(package_name)?;
@@ -24,6 +25,10 @@ fn show_details(app: &App, package_name: &str) -> Result<()> {
     println!("Description: {}", package.description);
     println!("Homepage: {}", package.homepage);
     println!("Repository: {}", package.repository);
+    println!("Version scheme: {}", package.version_scheme);
+    if app.is_foreign_arch_os() {
+        println!("Arch-OS: {}", app.arch_os);
+    }
 
     if let Some(installed_version) = db.get_package_version(&package.name)? {
         println!("Installed version: {installed_version}");
@@ -39,7 +44,17 @@ fn show_details(app: &App, package_name: &str) -> Result<()> {
         let mut arch_os_list = Vec::from_iter(release.assets.keys().map(|x| format!("{x}")));
         arch_os_list.sort();
         let arch_os_str = arch_os_list.join(", ");
-        println!("- {version} ({arch_os_str}){added_at}");
+        let prerelease = if version.is_prerelease() {
+            " [pre-release]"
+        } else {
+            ""
+        };
+        let unavailable = if package.get_asset(version, &app.arch_os).is_some() {
+            "".to_string()
+        } else {
+            format!(" [no {} asset]", app.arch_os)
+        };
+        println!("- {version}{prerelease}{unavailable} ({arch_os_str}){added_at}");
     }
     Ok(())
 }
@@ -52,7 +67,7 @@ fn show_files(app: &App, package_name: &str) -> Result<()> {
     Ok(())
 }
 
-fn show_as_json(app: &App, package_name: &str, list: bool) -> Result<()> {
+fn get_package_json(app: &App, package_name: &str, list: bool) -> Result<Value> {
     let db = &app.database;
     let package = app.store.get_package(package_name)?;
     let installed_version = db
@@ -67,6 +82,8 @@ fn show_as_json(app: &App, package_name: &str, list: bool) -> Result<()> {
             arch_os_list.sort();
             json!({
                 "version": version.to_string(),
+                "prerelease": version.is_prerelease(),
+                "available": package.get_asset(version, &app.arch_os).is_some(),
                 "arch_os": arch_os_list,
                 "added_at": release.added_at,
             })
@@ -78,19 +95,24 @@ fn show_as_json(app: &App, package_name: &str, list: bool) -> Result<()> {
         "description": package.description,
         "homepage": package.homepage,
         "repository": package.repository,
+        "version_scheme": package.version_scheme,
         "installed_version": installed_version,
         "available_versions": available_versions,
     });
     if list {
         value["files"] = get_file_list(app, &package.name)?.into()
     }
-    println!("{}", value);
-    Ok(())
+    Ok(value)
 }
 
-pub fn show_cmd(app: &App, app_name: &str, json: bool, list: bool) -> Result<()> {
-    if json {
-        show_as_json(app, app_name, list)
+pub fn show_cmd(app: &App, ui: &Ui, app_name: &str, json: bool, list: bool) -> Result<()> {
+    if ui.is_json() {
+        let data = get_package_json(app, app_name, list)?;
+        ui.emit(Event::Data { data });
+        Ok(())
+    } else if json {
+        println!("{}", get_package_json(app, app_name, list)?);
+        Ok(())
     } else if list {
         show_files(app, app_name)
     } else {
@@ -8,7 +8,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::app::App;
-use crate::ui::Ui;
+use crate::file_utils::path_exists;
+use crate::ui::{Event, Ui};
 
 use anyhow::{anyhow, Context, Result};
 
@@ -21,11 +22,6 @@ fn prepend_underscore(path: &Path) -> PathBuf {
     path.with_file_name(dst_file_name)
 }
 
-/// Like Path::exists(), but returns true if the argument is a broken symbolic link
-fn path_exists(path: &Path) -> bool {
-    path.is_symlink() || path.exists()
-}
-
 pub fn uninstall_cmd(app: &App, ui: &Ui, package_names: &Vec<String>) -> Result<()> {
     for package_name in package_names {
         uninstall_package(app, ui, package_name)?;
@@ -72,6 +68,10 @@ pub fn uninstall_package(app: &App, ui: &Ui, package_name: &str) -> Result<()> {
         }
     }
     db.remove_package(package_name)?;
+    ui.emit(Event::Uninstalled {
+        package: package_name.to_string(),
+        version: installed_version.to_string(),
+    });
     Ok(())
 }
 
@@ -82,7 +82,7 @@ mod tests {
     #[cfg(unix)]
     use std::os::unix::fs::symlink;
 
-    use semver::{Version, VersionReq};
+    use crate::version::{Version, VersionReq};
 
     use crate::test_file_utils::*;
 
@@ -4,8 +4,7 @@
 
 use std::vec::Vec;
 
-use anyhow::Result;
-use semver::{Version, VersionReq};
+use anyhow::{anyhow, Result};
 
 use crate::app::App;
 use crate::cmd::{install_packages, InstallRequest};
@@ -13,9 +12,10 @@ use crate::db::{Database, PackageInfo};
 use crate::package::Package;
 use crate::store::Store;
 use crate::ui::Ui;
+use crate::version::{Version, VersionReq};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
-struct Upgrade {
+pub struct Upgrade {
     pub package_info: PackageInfo,
     pub available_version: Version,
 }
@@ -33,8 +33,9 @@ fn get_newer_version(
     package: &Package,
     installed_version: &Version,
     version_req: &VersionReq,
+    allow_prereleases: bool,
 ) -> Option<Version> {
-    if let Some(version) = package.get_version_matching(version_req) {
+    if let Some(version) = package.get_version_matching(version_req, allow_prereleases) {
         if version > installed_version {
             return Some(version.clone());
         }
@@ -43,8 +44,14 @@ fn get_newer_version(
 }
 
 /// Check for upgrades, return a couple of Upgrade vectors. First element contains installable
-/// upgrades, second element contains blocked upgrades.
-fn get_upgrades(ui: &Ui, store: &dyn Store, db: &Database) -> Result<(Vec<Upgrade>, Vec<Upgrade>)> {
+/// upgrades, second element contains blocked upgrades. If `ignore_pins` is true, the requested
+/// versions are ignored, so there are no blocked upgrades.
+pub fn get_upgrades(
+    ui: &Ui,
+    store: &dyn Store,
+    db: &Database,
+    ignore_pins: bool,
+) -> Result<(Vec<Upgrade>, Vec<Upgrade>)> {
     let mut upgrades = Vec::<Upgrade>::new();
     let mut blocked_upgrades = Vec::<Upgrade>::new();
 
@@ -56,22 +63,64 @@ fn get_upgrades(ui: &Ui, store: &dyn Store, db: &Database) -> Result<(Vec<Upgrad
                 continue;
             }
         };
-        if let Some(available_version) =
-            get_newer_version(&package, &info.installed_version, &info.requested_version)
-        {
+        let version_req = if ignore_pins {
+            &VersionReq::STAR
+        } else {
+            &info.requested_version
+        };
+        if let Some(available_version) = get_newer_version(
+            &package,
+            &info.installed_version,
+            version_req,
+            info.allow_prereleases,
+        ) {
             upgrades.push(Upgrade::new(&info, &available_version));
-        } else if let Some(available_version) =
-            get_newer_version(&package, &info.installed_version, &VersionReq::STAR)
-        {
+        } else if let Some(available_version) = get_newer_version(
+            &package,
+            &info.installed_version,
+            &VersionReq::STAR,
+            info.allow_prereleases,
+        ) {
             blocked_upgrades.push(Upgrade::new(&info, &available_version));
         }
     }
     Ok((upgrades, blocked_upgrades))
 }
 
-pub fn upgrade_cmd(app: &App, ui: &Ui) -> Result<()> {
+/// Keep only the upgrades of `package_names` (or all upgrades if it's empty), except the ones of
+/// `excludes`
+fn filter_upgrades(
+    upgrades: Vec<Upgrade>,
+    package_names: &[String],
+    excludes: &[String],
+) -> Vec<Upgrade> {
+    upgrades
+        .into_iter()
+        .filter(|x| {
+            let name = &x.package_info.name;
+            (package_names.is_empty() || package_names.contains(name)) && !excludes.contains(name)
+        })
+        .collect()
+}
+
+pub fn upgrade_cmd(
+    app: &App,
+    ui: &Ui,
+    package_names: &[String],
+    excludes: &[String],
+    ignore_pins: bool,
+) -> Result<()> {
+    for name in package_names.iter().chain(excludes) {
+        if app.database.get_package_version(name)?.is_none() {
+            return Err(anyhow!("Package {name} is not installed"));
+        }
+    }
+
     ui.info("Checking upgrades");
-    let (upgrades, blocked_upgrades) = get_upgrades(&ui.nest(), &*app.store, &app.database)?;
+    let (upgrades, blocked_upgrades) =
+        get_upgrades(&ui.nest(), &*app.store, &app.database, ignore_pins)?;
+    let upgrades = filter_upgrades(upgrades, package_names, excludes);
+    let blocked_upgrades = filter_upgrades(blocked_upgrades, package_names, excludes);
 
     if !blocked_upgrades.is_empty() {
         ui.info("Blocked upgrades:");
@@ -92,8 +141,16 @@ pub fn upgrade_cmd(app: &App, ui: &Ui) -> Result<()> {
 
     ui.info("Available upgrades:");
     for upgrade in &upgrades {
+        let pin_str = if ignore_pins && upgrade.package_info.requested_version != VersionReq::STAR {
+            format!(
+                " (removing pin to {})",
+                upgrade.package_info.requested_version
+            )
+        } else {
+            "".to_string()
+        };
         ui.println(&format!(
-            "- {}: {} → {}",
+            "- {}: {} → {}{pin_str}",
             upgrade.package_info.name,
             upgrade.package_info.installed_version,
             upgrade.available_version
@@ -103,10 +160,13 @@ pub fn upgrade_cmd(app: &App, ui: &Ui) -> Result<()> {
     let install_requests: Vec<_> = upgrades
         .iter()
         .map(|u| {
-            InstallRequest::new(
-                &u.package_info.name,
-                u.package_info.requested_version.clone(),
-            )
+            let version_req = if ignore_pins {
+                VersionReq::STAR
+            } else {
+                u.package_info.requested_version.clone()
+            };
+            InstallRequest::new(&u.package_info.name, version_req)
+                .with_allow_prereleases(u.package_info.allow_prereleases)
         })
         .collect();
     install_packages(app, ui, false /* reinstall */, &install_requests)
@@ -119,8 +179,8 @@ mod tests {
     use std::collections::{HashMap, HashSet};
     use std::path::PathBuf;
 
+    use crate::version::{Version, VersionReq};
     use anyhow::{anyhow, Error};
-    use semver::{Version, VersionReq};
 
     use crate::package::Package;
     use crate::store::SearchHit;
@@ -158,6 +218,12 @@ mod tests {
         fn search(&self, _query: &str) -> Result<(Vec<SearchHit>, Vec<Error>)> {
             Ok((vec![], vec![]))
         }
+        fn list_packages(&self) -> Result<(Vec<Package>, Vec<Error>)> {
+            Ok((self.packages.values().cloned().collect(), vec![]))
+        }
+        fn get_revision(&self) -> Result<String> {
+            Ok(String::new())
+        }
     }
 
     #[test]
@@ -182,7 +248,7 @@ mod tests {
               1.2.0:
                 any:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
             installs: {}
             ",
         )
@@ -190,7 +256,8 @@ mod tests {
         store.packages.insert("foo".to_string(), package);
 
         // WHEN get_upgrades() is called
-        let (upgrades, blocked_upgrades) = get_upgrades(&Ui::default(), &store, &db).unwrap();
+        let (upgrades, blocked_upgrades) =
+            get_upgrades(&Ui::default(), &store, &db, false).unwrap();
 
         // THEN it returns empty vectors
         assert!(upgrades.is_empty());
@@ -220,11 +287,11 @@ mod tests {
               1.2.0:
                 any:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
               1.3.0:
                 any:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
             installs: {}
             ",
         )
@@ -232,7 +299,8 @@ mod tests {
         store.packages.insert("foo".to_string(), package);
 
         // WHEN get_upgrades() is called
-        let (upgrades, blocked_upgrades) = get_upgrades(&Ui::default(), &store, &db).unwrap();
+        let (upgrades, blocked_upgrades) =
+            get_upgrades(&Ui::default(), &store, &db, false).unwrap();
 
         // THEN it returns an empty upgrade vector
         assert_eq!(upgrades, vec![]);
@@ -265,11 +333,11 @@ mod tests {
               1.2.0:
                 any:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
               1.3.0:
                 any:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
             installs: {}
             ",
         )
@@ -277,7 +345,8 @@ mod tests {
         store.packages.insert("foo".to_string(), package);
 
         // WHEN get_upgrades() is called
-        let (upgrades, blocked_upgrades) = get_upgrades(&Ui::default(), &store, &db).unwrap();
+        let (upgrades, blocked_upgrades) =
+            get_upgrades(&Ui::default(), &store, &db, false).unwrap();
 
         // THEN it returns foo
         let package_info = PackageInfo::new("foo", &Version::new(1, 2, 0), &VersionReq::STAR);
@@ -287,4 +356,78 @@ mod tests {
         );
         assert!(blocked_upgrades.is_empty());
     }
+
+    #[test]
+    fn get_upgrades_should_ignore_pins_if_asked() {
+        // GIVEN a database
+        let db = Database::new_in_memory().unwrap();
+        db.create().unwrap();
+
+        // AND a package foo at version 1.2.0, pinned to 1.2.*
+        let files = HashSet::<PathBuf>::new();
+        let version_req = VersionReq::parse("1.2.*").unwrap();
+        db.add_package("foo", &Version::new(1, 2, 0), &version_req, &files)
+            .unwrap();
+
+        // AND a store with package foo at version 1.2.0 and 1.3.0
+        let mut store = FakeStore::new();
+        let package = Package::from_yaml_str(
+            "
+            name: foo
+            description: desc
+            homepage:
+            releases:
+              1.2.0:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+              1.3.0:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+            installs: {}
+            ",
+        )
+        .unwrap();
+        store.packages.insert("foo".to_string(), package);
+
+        // WHEN get_upgrades() is called with ignore_pins set to true
+        let (upgrades, blocked_upgrades) = get_upgrades(&Ui::default(), &store, &db, true).unwrap();
+
+        // THEN it returns foo in the upgrade vector
+        let package_info = PackageInfo::new("foo", &Version::new(1, 2, 0), &version_req);
+        assert_eq!(
+            upgrades,
+            vec![Upgrade::new(&package_info, &Version::new(1, 3, 0))]
+        );
+        assert!(blocked_upgrades.is_empty());
+    }
+
+    #[test]
+    fn filter_upgrades_should_keep_selected_packages_except_excluded_ones() {
+        // GIVEN upgrades for foo, bar and baz
+        let version = Version::new(1, 0, 0);
+        let upgrades: Vec<_> = ["foo", "bar", "baz"]
+            .iter()
+            .map(|name| {
+                let info = PackageInfo::new(name, &Version::new(0, 1, 0), &VersionReq::STAR);
+                Upgrade::new(&info, &version)
+            })
+            .collect();
+        let names = |upgrades: Vec<Upgrade>| -> Vec<String> {
+            upgrades.into_iter().map(|x| x.package_info.name).collect()
+        };
+
+        // WHEN no package is selected THEN all packages but the excluded ones are kept
+        let result = filter_upgrades(upgrades.clone(), &[], &["bar".to_string()]);
+        assert_eq!(names(result), vec!["foo", "baz"]);
+
+        // WHEN packages are selected THEN only they are kept
+        let result = filter_upgrades(
+            upgrades,
+            &["foo".to_string(), "bar".to_string()],
+            &["bar".to_string()],
+        );
+        assert_eq!(names(result), vec!["foo"]);
+    }
 }
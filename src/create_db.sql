@@ -1,10 +1,21 @@
 CREATE TABLE installed_package (
     name TEXT PRIMARY KEY,
     installed_version TEXT,
-    requested_version TEXT
+    requested_version TEXT,
+    allow_prereleases INTEGER NOT NULL DEFAULT 0,
+    version_scheme TEXT NOT NULL DEFAULT 'semver'
 ) STRICT;
 
 CREATE TABLE installed_file (
     path TEXT PRIMARY KEY,
     package_name TEXT REFERENCES installed_package(name) ON DELETE CASCADE
 ) STRICT;
+
+CREATE TABLE package_env (
+    package_name TEXT REFERENCES installed_package(name) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    action TEXT NOT NULL,
+    value TEXT NOT NULL
+) STRICT;
+
+PRAGMA user_version = 3;
@@ -7,8 +7,14 @@ use std::include_str;
 use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Result};
-use rusqlite::{params, Connection, Result as RusqliteResult};
-use semver::{Version, VersionReq};
+use rusqlite::{params, Connection, OpenFlags, Result as RusqliteResult};
+
+use crate::package::{EnvAction, EnvValue};
+use crate::version::{Version, VersionReq, VersionScheme};
+
+/// Version of the database schema, stored in the `user_version` pragma. Must match the one set in
+/// create_db.sql.
+pub const SCHEMA_VERSION: i32 = 3;
 
 pub struct Database {
     conn: Connection,
@@ -19,6 +25,8 @@ pub struct PackageInfo {
     pub name: String,
     pub installed_version: Version,
     pub requested_version: VersionReq,
+    /// True if upgrades can install pre-releases
+    pub allow_prereleases: bool,
 }
 
 impl PackageInfo {
@@ -27,14 +35,33 @@ impl PackageInfo {
             name: name.to_string(),
             installed_version: installed_version.clone(),
             requested_version: requested_version.clone(),
+            allow_prereleases: false,
         }
     }
+
+    pub fn with_allow_prereleases(mut self, allow_prereleases: bool) -> Self {
+        self.allow_prereleases = allow_prereleases;
+        self
+    }
 }
 
 impl Database {
     pub fn new_from_path(db_path: &Path) -> Result<Database> {
         let conn = Connection::open(db_path)?;
 
+        let db = Database { conn };
+        db.upgrade_schema()?;
+        Ok(db)
+    }
+
+    /// Opens an existing database in read-only mode. Unlike new_from_path(), does not upgrade the
+    /// schema: this can be used while another instance of Clyde is running.
+    pub fn new_read_only(db_path: &Path) -> Result<Database> {
+        let conn = Connection::open_with_flags(
+            db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+
         Ok(Database { conn })
     }
 
@@ -49,18 +76,89 @@ impl Database {
         Ok(())
     }
 
+    /// Upgrade the schema of a database created by an older version of Clyde. Does nothing if
+    /// the database has not been created yet.
+    fn upgrade_schema(&self) -> Result<()> {
+        if !self.is_created()? {
+            return Ok(());
+        }
+        // Run all the steps in a transaction: if one of them fails, the database must stay at
+        // its current version, otherwise the next upgrade would fail
+        let transaction = self.conn.unchecked_transaction()?;
+        let version: i32 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < 1 {
+            self.conn.execute_batch(
+                "ALTER TABLE installed_package
+                    ADD COLUMN allow_prereleases INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
+        if version < 2 {
+            self.conn.execute_batch(
+                "ALTER TABLE installed_package
+                    ADD COLUMN version_scheme TEXT NOT NULL DEFAULT 'semver';",
+            )?;
+        }
+        if version < 3 {
+            self.conn.execute_batch(
+                "CREATE TABLE package_env (
+                    package_name TEXT REFERENCES installed_package(name) ON DELETE CASCADE,
+                    name TEXT NOT NULL,
+                    action TEXT NOT NULL,
+                    value TEXT NOT NULL
+                ) STRICT;",
+            )?;
+        }
+        if version < SCHEMA_VERSION {
+            self.conn
+                .execute_batch(&format!("PRAGMA user_version = {SCHEMA_VERSION};"))?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Returns true if the tables of the database have been created
+    pub fn is_created(&self) -> Result<bool> {
+        let table_count: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'installed_package'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(table_count > 0)
+    }
+
+    /// Returns the version of the schema of the database, 0 if it has not been created
+    pub fn get_schema_version(&self) -> Result<i32> {
+        let version = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        Ok(version)
+    }
+
+    /// Runs SQLite integrity check. Returns the problems it found, if any.
+    pub fn check_integrity(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut problems = rows.collect::<RusqliteResult<Vec<_>>>()?;
+        if problems == ["ok"] {
+            problems.clear();
+        }
+        Ok(problems)
+    }
+
     /// Returns the installed version of `package` if it is installed
     /// Otherwise returns None
     pub fn get_package_version(&self, package: &str) -> Result<Option<Version>> {
-        let row: RusqliteResult<String> = self.conn.query_row(
-            "SELECT installed_version FROM installed_package
+        let row: RusqliteResult<(String, String)> = self.conn.query_row(
+            "SELECT installed_version, version_scheme FROM installed_package
             WHERE name = ?",
             [&package],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         );
 
         match row {
-            Ok(version) => Ok(Some(Version::parse(&version)?)),
+            Ok((version, scheme)) => Ok(Some(parse_version(&version, &scheme)?)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(x) => Err(anyhow!(
                 "Failed to get package version for {}: {}",
@@ -79,12 +177,18 @@ impl Database {
     ) -> Result<()> {
         let installed_version_str = installed_version.to_string();
         let requested_version_str = requested_version.to_string();
+        let version_scheme_str = installed_version.scheme().to_string();
 
         let tx = self.conn.unchecked_transaction()?;
         tx.execute(
-            "INSERT INTO installed_package(name, installed_version, requested_version)
-                    VALUES(?, ?, ?)",
-            params![&package, &installed_version_str, &requested_version_str],
+            "INSERT INTO installed_package(name, installed_version, requested_version, version_scheme)
+                    VALUES(?, ?, ?, ?)",
+            params![
+                &package,
+                &installed_version_str,
+                &requested_version_str,
+                &version_scheme_str
+            ],
         )?;
 
         {
@@ -98,6 +202,93 @@ impl Database {
         Ok(())
     }
 
+    /// Returns true if upgrades of `package` can install pre-releases. Returns false if `package`
+    /// is not installed.
+    pub fn get_allow_prereleases(&self, package: &str) -> Result<bool> {
+        let row: RusqliteResult<bool> = self.conn.query_row(
+            "SELECT allow_prereleases FROM installed_package WHERE name = ?",
+            [&package],
+            |row| row.get(0),
+        );
+
+        match row {
+            Ok(allow_prereleases) => Ok(allow_prereleases),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+            Err(x) => Err(anyhow!(
+                "Failed to get pre-release setting for {}: {}",
+                package,
+                x
+            )),
+        }
+    }
+
+    /// Define whether upgrades of an installed package can install pre-releases
+    pub fn set_allow_prereleases(&self, package: &str, allow_prereleases: bool) -> Result<()> {
+        let count = self.conn.execute(
+            "UPDATE installed_package SET allow_prereleases = ? WHERE name = ?",
+            params![&allow_prereleases, &package],
+        )?;
+        if count == 0 {
+            return Err(anyhow!("Package {} is not installed", package));
+        }
+        Ok(())
+    }
+
+    /// Change the requested version of an installed package
+    pub fn set_requested_version(
+        &self,
+        package: &str,
+        requested_version: &VersionReq,
+    ) -> Result<()> {
+        let count = self.conn.execute(
+            "UPDATE installed_package SET requested_version = ? WHERE name = ?",
+            params![&requested_version.to_string(), &package],
+        )?;
+        if count == 0 {
+            return Err(anyhow!("Package {} is not installed", package));
+        }
+        Ok(())
+    }
+
+    /// Store the environment variables defined by an installed package. Values must have been
+    /// expanded.
+    pub fn set_package_env(&self, package: &str, env: &[(String, EnvValue)]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM package_env WHERE package_name = ?", [&package])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO package_env (package_name, name, action, value) VALUES (?, ?, ?, ?)",
+            )?;
+            for (name, value) in env {
+                stmt.execute(params![
+                    &package,
+                    name,
+                    &value.action().to_string(),
+                    value.value()
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the environment variables defined by all installed packages, ordered by package
+    /// name
+    pub fn get_env(&self) -> Result<Vec<(String, EnvValue)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name, action, value FROM package_env ORDER BY package_name, rowid")?;
+        let mut rows = stmt.query([])?;
+        let mut env = vec![];
+        while let Some(row) = rows.next()? {
+            let name: String = row.get(0)?;
+            let action: String = row.get(1)?;
+            let value: String = row.get(2)?;
+            env.push((name, EnvValue::new(action.parse::<EnvAction>()?, &value)));
+        }
+        Ok(env)
+    }
+
     pub fn remove_package(&self, package: &str) -> Result<()> {
         self.conn
             .execute("DELETE from installed_package WHERE name = ?", [&package])?;
@@ -119,26 +310,52 @@ impl Database {
         Ok(files)
     }
 
+    /// Returns the name of the installed package owning `path`, a path relative to the install
+    /// dir
+    pub fn get_file_owner(&self, path: &Path) -> Result<Option<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, package_name FROM installed_file")?;
+        let mut rows = stmt.query([])?;
+        // Compare paths instead of strings, because paths may use different separators on Windows
+        while let Some(row) = rows.next()? {
+            let file_path: String = row.get(0)?;
+            if Path::new(&file_path) == path {
+                return Ok(Some(row.get(1)?));
+            }
+        }
+        Ok(None)
+    }
+
     pub fn get_installed_packages(&self) -> Result<Vec<PackageInfo>> {
         let mut packages: Vec<PackageInfo> = Vec::<PackageInfo>::new();
         let mut stmt = self
             .conn
-            .prepare("SELECT name, installed_version, requested_version FROM installed_package order by name")?;
+            .prepare("SELECT name, installed_version, requested_version, allow_prereleases, version_scheme FROM installed_package order by name")?;
         let mut rows = stmt.query([])?;
         while let Some(row) = rows.next()? {
             let name: String = row.get(0)?;
             let installed_version: String = row.get(1)?;
             let requested_version: String = row.get(2)?;
-            packages.push(PackageInfo::new(
-                &name,
-                &Version::parse(&installed_version)?,
-                &VersionReq::parse(&requested_version)?,
-            ));
+            let allow_prereleases: bool = row.get(3)?;
+            let version_scheme: String = row.get(4)?;
+            packages.push(
+                PackageInfo::new(
+                    &name,
+                    &parse_version(&installed_version, &version_scheme)?,
+                    &VersionReq::parse(&requested_version)?,
+                )
+                .with_allow_prereleases(allow_prereleases),
+            );
         }
         Ok(packages)
     }
 }
 
+fn parse_version(version: &str, scheme: &str) -> Result<Version> {
+    Version::parse_with_scheme(version, scheme.parse::<VersionScheme>()?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,6 +387,47 @@ mod tests {
         assert_eq!(db.get_package_files(&package).unwrap(), files);
     }
 
+    #[test]
+    fn get_file_owner_should_return_the_package_owning_the_file() {
+        // GIVEN a database with an installed package
+        let db = Database::new_in_memory().unwrap();
+        db.create().unwrap();
+        let files = HashSet::<PathBuf>::from([PathBuf::from("bin/p")]);
+        db.add_package("pkg", &Version::new(1, 0, 0), &VersionReq::STAR, &files)
+            .unwrap();
+
+        // WHEN get_file_owner() is called
+        // THEN it returns the package for its files, and None for other files
+        assert_eq!(
+            db.get_file_owner(Path::new("bin/p")).unwrap(),
+            Some("pkg".to_string())
+        );
+        assert_eq!(db.get_file_owner(Path::new("bin/q")).unwrap(), None);
+    }
+
+    #[test]
+    fn get_package_version_should_keep_non_semver_versions_intact() {
+        // GIVEN a database with a CalVer package
+        let db = Database::new_in_memory().unwrap();
+        db.create().unwrap();
+        let installed_version =
+            Version::parse_with_scheme("2024.01.05", VersionScheme::Calver).unwrap();
+        db.add_package(
+            "pkg",
+            &installed_version,
+            &VersionReq::STAR,
+            &HashSet::new(),
+        )
+        .unwrap();
+
+        // WHEN get_package_version() is called
+        let version = db.get_package_version("pkg").unwrap().unwrap();
+
+        // THEN it returns the version as it was written, with its scheme
+        assert_eq!(version.to_string(), "2024.01.05");
+        assert_eq!(version.scheme(), VersionScheme::Calver);
+    }
+
     #[test]
     fn get_package_version_returns_none_if_package_is_not_installed() {
         // GIVEN an empty database
@@ -205,4 +463,224 @@ mod tests {
         let names: Vec<String> = packages.iter().map(|x| x.name.clone()).collect();
         assert_eq!(names, &["alice", "bob", "carl", "deborah"]);
     }
+
+    #[test]
+    fn set_requested_version_should_update_requested_version() {
+        // GIVEN a database with a package installed without a requested version
+        let db = Database::new_in_memory().unwrap();
+        db.create().unwrap();
+        let installed_version = Version::parse("1.2.3").unwrap();
+        db.add_package(
+            "pkg",
+            &installed_version,
+            &VersionReq::STAR,
+            &HashSet::new(),
+        )
+        .unwrap();
+
+        // WHEN set_requested_version() is called
+        let requested_version = VersionReq::parse("=1.2.3").unwrap();
+        let result = db.set_requested_version("pkg", &requested_version);
+
+        // THEN it succeeds
+        assert!(result.is_ok(), "{:?}", result);
+
+        // AND the requested version has been updated
+        let packages = db.get_installed_packages().unwrap();
+        assert_eq!(
+            packages,
+            vec![PackageInfo::new(
+                "pkg",
+                &installed_version,
+                &requested_version
+            )]
+        );
+
+        // AND it fails for a package which is not installed
+        assert!(db
+            .set_requested_version("not_there", &requested_version)
+            .is_err());
+    }
+
+    #[test]
+    fn new_from_path_should_upgrade_old_databases() {
+        // GIVEN a database created with the initial schema
+        let dir = assert_fs::TempDir::new().unwrap();
+        let db_path = dir.join("clyde.sqlite");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE installed_package (
+                name TEXT PRIMARY KEY,
+                installed_version TEXT,
+                requested_version TEXT
+            ) STRICT;
+            CREATE TABLE installed_file (
+                path TEXT PRIMARY KEY,
+                package_name TEXT REFERENCES installed_package(name) ON DELETE CASCADE
+            ) STRICT;
+            INSERT INTO installed_package VALUES('foo', '1.2.3', '*');",
+        )
+        .unwrap();
+        drop(conn);
+
+        // WHEN it is opened
+        let db = Database::new_from_path(&db_path).unwrap();
+
+        // THEN existing packages can be read
+        let packages = db.get_installed_packages().unwrap();
+        assert_eq!(
+            packages,
+            vec![PackageInfo::new(
+                "foo",
+                &Version::new(1, 2, 3),
+                &VersionReq::STAR
+            )]
+        );
+
+        // AND the new columns can be used
+        db.set_allow_prereleases("foo", true).unwrap();
+        let packages = db.get_installed_packages().unwrap();
+        assert!(packages[0].allow_prereleases);
+        assert!(db.get_allow_prereleases("foo").unwrap());
+        assert!(!db.get_allow_prereleases("not_installed").unwrap());
+
+        // AND the new tables can be used
+        let env = vec![("FOO_HOME".to_string(), EnvValue::Set("/foo".into()))];
+        db.set_package_env("foo", &env).unwrap();
+        assert_eq!(db.get_env().unwrap(), env);
+    }
+
+    #[test]
+    fn upgrade_schema_should_not_leave_partially_upgraded_databases() {
+        // GIVEN a database created with the initial schema, which also contains a table added by
+        // a later version, making the upgrade fail at its last step
+        let dir = assert_fs::TempDir::new().unwrap();
+        let db_path = dir.join("clyde.sqlite");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE installed_package (
+                name TEXT PRIMARY KEY,
+                installed_version TEXT,
+                requested_version TEXT
+            ) STRICT;
+            CREATE TABLE package_env (name TEXT) STRICT;",
+        )
+        .unwrap();
+        drop(conn);
+
+        // WHEN it is opened
+        // THEN the upgrade fails
+        assert!(Database::new_from_path(&db_path).is_err());
+
+        // AND the database has not been modified
+        let db = Database::new_read_only(&db_path).unwrap();
+        assert_eq!(db.get_schema_version().unwrap(), 0);
+        drop(db);
+
+        // WHEN the conflicting table is removed, and the database is opened twice
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch("DROP TABLE package_env;").unwrap();
+        drop(conn);
+        Database::new_from_path(&db_path).unwrap();
+        let db = Database::new_from_path(&db_path).unwrap();
+
+        // THEN the database has been upgraded
+        assert_eq!(db.get_schema_version().unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn new_read_only_should_not_upgrade_old_databases() {
+        // GIVEN a database created with the initial schema
+        let dir = assert_fs::TempDir::new().unwrap();
+        let db_path = dir.join("clyde.sqlite");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE installed_package (
+                name TEXT PRIMARY KEY,
+                installed_version TEXT,
+                requested_version TEXT
+            ) STRICT;",
+        )
+        .unwrap();
+        drop(conn);
+
+        // WHEN it is opened in read-only mode
+        let db = Database::new_read_only(&db_path).unwrap();
+
+        // THEN its schema is not upgraded
+        assert_eq!(db.get_schema_version().unwrap(), 0);
+
+        // AND it cannot be modified
+        assert!(db.create().is_err());
+    }
+
+    #[test]
+    fn new_read_only_should_fail_if_the_database_does_not_exist() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let db_path = dir.join("clyde.sqlite");
+
+        assert!(Database::new_read_only(&db_path).is_err());
+        assert!(!db_path.exists());
+    }
+
+    #[test]
+    fn check_integrity_should_not_report_problems_for_valid_databases() {
+        let db = Database::new_in_memory().unwrap();
+        db.create().unwrap();
+        assert_eq!(db.check_integrity().unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn get_env_should_return_the_env_of_installed_packages() {
+        // GIVEN a database with two packages defining environment variables
+        let db = Database::new_in_memory().unwrap();
+        db.create().unwrap();
+        let version = Version::new(1, 0, 0);
+        for name in ["foo", "bar"] {
+            db.add_package(name, &version, &VersionReq::STAR, &HashSet::new())
+                .unwrap();
+        }
+        db.set_package_env(
+            "foo",
+            &[
+                ("FOO_HOME".to_string(), EnvValue::Set("/foo".into())),
+                (
+                    "PLUGIN_PATH".to_string(),
+                    EnvValue::new(EnvAction::Prepend, "/foo/plugins"),
+                ),
+            ],
+        )
+        .unwrap();
+        db.set_package_env(
+            "bar",
+            &[(
+                "PLUGIN_PATH".to_string(),
+                EnvValue::new(EnvAction::Prepend, "/bar/plugins"),
+            )],
+        )
+        .unwrap();
+
+        // WHEN get_env() is called
+        let env = db.get_env().unwrap();
+
+        // THEN it returns the variables of all packages, ordered by package name
+        assert_eq!(
+            env,
+            vec![
+                (
+                    "PLUGIN_PATH".to_string(),
+                    EnvValue::new(EnvAction::Prepend, "/bar/plugins")
+                ),
+                ("FOO_HOME".to_string(), EnvValue::Set("/foo".into())),
+                (
+                    "PLUGIN_PATH".to_string(),
+                    EnvValue::new(EnvAction::Prepend, "/foo/plugins")
+                ),
+            ]
+        );
+
+        // AND the variables of a package are removed when it is uninstalled
+        db.remove_package("foo").unwrap();
+        assert_eq!(env[..1], db.get_env().unwrap());
+    }
 }
@@ -12,7 +12,7 @@ use reqwest::blocking::Client;
 use reqwest::{header, Error as ReqwestError, StatusCode, Url};
 
 use crate::file_utils;
-use crate::ui::Ui;
+use crate::ui::{Event, Ui};
 
 const FILE_PREFIX: &str = "file://";
 
@@ -20,36 +20,77 @@ const PROGRESS_BAR_TEMPLATE: &str = "[{bar:40}] {bytes} / {total_bytes} - {bytes
 
 const DOWNLOAD_ATTEMPTS: u64 = 3;
 
+/// How download progress is reported
+enum Progress {
+    Bar(ProgressBar),
+    /// In JSON mode, emit an event each time the progress percentage changes
+    Events {
+        ui: Ui,
+        name: String,
+        last_percent: Option<u64>,
+    },
+}
+
 struct ProgressWriter<W: Write> {
     writer: W,
     start_size: u64,
+    total_size: u64,
     downloaded: u64,
-    bar: ProgressBar,
+    progress: Progress,
 }
 
 impl<W> ProgressWriter<W>
 where
     W: Write,
 {
-    fn new(ui: &Ui, writer: W, start_size: u64, total_size: u64) -> Self {
-        let bar = ProgressBar::new(start_size + total_size);
-        let template = ui.get_indent() + PROGRESS_BAR_TEMPLATE;
-        bar.set_style(
-            ProgressStyle::default_bar()
-                .template(&template)
-                .unwrap()
-                .progress_chars("●●."),
-        );
+    fn new(ui: &Ui, writer: W, name: &str, start_size: u64, total_size: u64) -> Self {
+        let progress = if ui.is_json() {
+            Progress::Events {
+                ui: ui.clone(),
+                name: name.to_string(),
+                last_percent: None,
+            }
+        } else {
+            let bar = ProgressBar::new(start_size + total_size);
+            let template = ui.get_indent() + PROGRESS_BAR_TEMPLATE;
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template(&template)
+                    .unwrap()
+                    .progress_chars("●●."),
+            );
+            Progress::Bar(bar)
+        };
         Self {
             writer,
             start_size,
+            total_size: start_size + total_size,
             downloaded: 0,
-            bar,
+            progress,
         }
     }
 
     fn print_progress(&mut self) {
-        self.bar.set_position(self.start_size + self.downloaded);
+        let position = self.start_size + self.downloaded;
+        match &mut self.progress {
+            Progress::Bar(bar) => bar.set_position(position),
+            Progress::Events {
+                ui,
+                name,
+                last_percent,
+            } => {
+                let percent = (position * 100).checked_div(self.total_size).unwrap_or(100);
+                if *last_percent == Some(percent) {
+                    return;
+                }
+                *last_percent = Some(percent);
+                ui.emit(Event::Downloading {
+                    name: name.clone(),
+                    downloaded: position,
+                    total: Some(self.total_size),
+                });
+            }
+        }
     }
 }
 
@@ -84,7 +125,13 @@ pub fn download(ui: &Ui, url_str: &str, dst_path: &Path) -> Result<()> {
     }
 }
 
-fn https_download_internal(ui: &Ui, client: &Client, url: &Url, partial_path: &Path) -> Result<()> {
+fn https_download_internal(
+    ui: &Ui,
+    client: &Client,
+    url: &Url,
+    name: &str,
+    partial_path: &Path,
+) -> Result<()> {
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
@@ -108,7 +155,7 @@ fn https_download_internal(ui: &Ui, client: &Client, url: &Url, partial_path: &P
         partial_size = 0;
     }
     if let Some(total_size) = response.content_length() {
-        let mut writer = ProgressWriter::new(&ui.nest(), file, partial_size, total_size);
+        let mut writer = ProgressWriter::new(&ui.nest(), file, name, partial_size, total_size);
         response.copy_to(&mut writer)?;
     } else {
         response.copy_to(&mut file)?;
@@ -130,7 +177,7 @@ fn https_download(ui: &Ui, url_str: &str, dst_path: &Path) -> Result<()> {
             ));
         }
         ui.info(&format!("Downloading {name}"));
-        match https_download_internal(ui, &client, &url, &partial_path) {
+        match https_download_internal(ui, &client, &url, name, &partial_path) {
             Ok(()) => break,
             Err(err) => match err.downcast_ref::<ReqwestError>() {
                 Some(req_err) => {
@@ -161,7 +208,8 @@ fn file_download(ui: &Ui, url_str: &str, dst_path: &Path) -> Result<()> {
     let total_size = fs::metadata(path_str)?.len();
 
     let mut dst_file = File::create(dst_path)?;
-    let mut writer = ProgressWriter::new(&ui.nest(), &mut dst_file, 0, total_size);
+    let name = file_utils::get_file_name(dst_path)?;
+    let mut writer = ProgressWriter::new(&ui.nest(), &mut dst_file, name, 0, total_size);
 
     io::copy(&mut file, &mut writer)?;
     Ok(())
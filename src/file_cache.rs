@@ -7,10 +7,41 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Result};
-use semver::Version;
+use sha2::{Digest, Sha256};
 
 use crate::download::download;
 use crate::ui::Ui;
+use crate::version::Version;
+
+/// Name used to store files whose URL path does not end with a file name
+const DEFAULT_FILE_NAME: &str = "download";
+
+/// Returns the name to store the file downloaded from `url` under, and the name of a
+/// sub-directory to store it in if `url` has a query string.
+///
+/// URLs which only differ by their query string (for example `download?os=linux` and
+/// `download?os=macos`) get the same name, so the sub-directory is used to keep them apart.
+fn get_cache_path(url: &str) -> Result<(String, Option<String>)> {
+    let (url_without_fragment, _) = url.split_once('#').unwrap_or((url, ""));
+    let (path, query) = match url_without_fragment.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (url_without_fragment, None),
+    };
+    let (_, name) = path
+        .rsplit_once('/')
+        .ok_or_else(|| anyhow!("Can't find archive name in URL {}", url))?;
+    let name = if name.is_empty() {
+        DEFAULT_FILE_NAME
+    } else {
+        name
+    };
+
+    let sub_dir = query.map(|query| {
+        let digest = Sha256::digest(query.as_bytes());
+        hex::encode(&digest[..6])
+    });
+    Ok((name.to_string(), sub_dir))
+}
 
 pub struct FileCache {
     dir: PathBuf,
@@ -40,11 +71,12 @@ impl FileCache {
         version: &Version,
         url: &str,
     ) -> Result<PathBuf> {
-        let (_, name) = url
-            .rsplit_once('/')
-            .ok_or_else(|| anyhow!("Can't find archive name in URL {}", url))?;
+        let (name, sub_dir) = get_cache_path(url)?;
 
-        let download_dir = self.get_download_dir(package_name, version);
+        let mut download_dir = self.get_download_dir(package_name, version);
+        if let Some(sub_dir) = sub_dir {
+            download_dir = download_dir.join(sub_dir);
+        }
         fs::create_dir_all(&download_dir)?;
 
         let archive_path = download_dir.join(OsString::from(name));
@@ -60,3 +92,41 @@ impl FileCache {
         Ok(archive_path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_cache_path_should_use_the_last_path_component() {
+        assert_eq!(
+            get_cache_path("https://example.com/foo/foo-1.2.tar.gz").unwrap(),
+            ("foo-1.2.tar.gz".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn get_cache_path_should_ignore_query_strings_and_fragments() {
+        // GIVEN 2 URLs which only differ by their query string
+        let (name1, sub_dir1) =
+            get_cache_path("https://example.com/download?os=linux#top").unwrap();
+        let (name2, sub_dir2) = get_cache_path("https://example.com/download?os=macos").unwrap();
+
+        // THEN the query string and the fragment are not part of the name
+        assert_eq!(name1, "download");
+        assert_eq!(name2, "download");
+
+        // AND the files are stored in different sub-directories
+        assert!(sub_dir1.is_some());
+        assert!(sub_dir2.is_some());
+        assert_ne!(sub_dir1, sub_dir2);
+    }
+
+    #[test]
+    fn get_cache_path_should_handle_urls_ending_with_a_slash() {
+        assert_eq!(
+            get_cache_path("https://example.com/foo/latest/").unwrap(),
+            ("download".to_string(), None)
+        );
+    }
+}
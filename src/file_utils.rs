@@ -17,6 +17,11 @@ pub fn get_file_name(path: &Path) -> Result<&str> {
     Ok(name)
 }
 
+/// Like Path::exists(), but returns true if the argument is a broken symbolic link
+pub fn path_exists(path: &Path) -> bool {
+    path.is_symlink() || path.exists()
+}
+
 #[cfg(unix)]
 pub fn set_file_executable(path: &Path) -> Result<()> {
     use std::fs;
@@ -14,6 +14,8 @@ pub mod file_cache;
 pub mod file_utils;
 pub mod package;
 pub mod pager;
+pub mod shell;
+pub mod signature;
 pub mod store;
 pub mod table;
 pub mod test_file_utils;
@@ -21,6 +23,7 @@ pub mod ui;
 pub mod unpacker;
 pub mod vars;
 pub mod vendored;
+pub mod version;
 
 #[macro_use]
 extern crate lazy_static;
@@ -2,79 +2,184 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::path::Path;
+use std::process::ExitCode;
+
 use anyhow::Result;
 use clap::Parser;
 
 use clyde::app::App;
 use clyde::cli::{Cli, Command};
 use clyde::cmd::{
-    doc_cmd, install_cmd, list_cmd, search_cmd, setup_cmd, show_cmd, uninstall_cmd, update_cmd,
-    upgrade_cmd,
+    doc_cmd, doctor_cmd, downgrade_cmd, env_cmd, fetch_cmd, install_cmd, list_cmd, outdated_cmd,
+    owns_cmd, pin_cmd, provides_cmd, search_cmd, setup_cmd, shell_init_cmd, show_cmd,
+    uninstall_cmd, unpin_cmd, update_cmd, upgrade_cmd, RcAction,
 };
 use clyde::ctrlcutils;
-use clyde::ui::Ui;
+use clyde::ui::{Event, Ui};
+
+/// Exit code used by `clyde outdated` when upgrades are available
+const UPGRADES_AVAILABLE_EXIT_CODE: u8 = 100;
+
+/// Create the app, for the arch-os defined by `arch_os` or $CLYDE_ARCH_OS if one of them is set
+fn create_app(home: &Path, arch_os: Option<&str>) -> Result<App> {
+    let app = App::new(home)?;
+    match App::read_arch_os(arch_os)? {
+        Some(arch_os) => app.with_arch_os(arch_os),
+        None => Ok(app),
+    }
+}
 
-pub fn exec(cli: Cli) -> Result<()> {
-    let ui = Ui::default();
+fn run_command(ui: &Ui, command: Command) -> Result<ExitCode> {
     let home = App::find_home()?;
 
-    let _instance = App::create_single_instance(&home)?;
+    // `env` and `shell-init` are run by the startup scripts of each new shell: they must not fail
+    // because another instance is running
+    let _instance = if matches!(command, Command::Env { .. } | Command::ShellInit { .. }) {
+        None
+    } else {
+        Some(App::create_single_instance(&home)?)
+    };
 
-    let result = match cli.command {
+    let result = match command {
         Command::Setup {
             update_scripts,
+            modify_rc,
+            unmodify_rc,
             store_url,
-        } => setup_cmd(&ui, &home, update_scripts, store_url.as_deref()),
+        } => {
+            let rc_action = if modify_rc {
+                Some(RcAction::Modify)
+            } else if unmodify_rc {
+                Some(RcAction::Unmodify)
+            } else {
+                None
+            };
+            setup_cmd(ui, &home, update_scripts, rc_action, store_url.as_deref())
+        }
         Command::Update {} => {
-            let app = App::new(&home)?;
-            update_cmd(&app, &ui)
+            let app = create_app(&home, None)?;
+            update_cmd(&app, ui)
         }
         Command::Install {
             reinstall,
+            pre,
+            no_pre,
+            arch_os,
+            package_names,
+        } => {
+            let allow_prereleases = if pre {
+                Some(true)
+            } else if no_pre {
+                Some(false)
+            } else {
+                None
+            };
+            let app = create_app(&home, arch_os.as_deref())?;
+            install_cmd(&app, ui, reinstall, allow_prereleases, &package_names)
+        }
+        Command::Fetch {
+            pre,
+            arch_os,
+            output_dir,
             package_names,
         } => {
-            let app = App::new(&home)?;
-            install_cmd(&app, &ui, reinstall, &package_names)
+            let app = create_app(&home, arch_os.as_deref())?;
+            fetch_cmd(&app, ui, pre, output_dir.as_deref(), &package_names)
         }
         Command::Uninstall { package_names } => {
-            let app = App::new(&home)?;
-            uninstall_cmd(&app, &ui, &package_names)
+            let app = create_app(&home, None)?;
+            uninstall_cmd(&app, ui, &package_names)
         }
         Command::Show {
             package_name,
             json,
             list,
+            arch_os,
         } => {
-            let app = App::new(&home)?;
-            show_cmd(&app, &package_name, json, list)
+            let app = create_app(&home, arch_os.as_deref())?;
+            show_cmd(&app, ui, &package_name, json, list)
         }
         Command::Search { query } => {
-            let app = App::new(&home)?;
-            search_cmd(&app, &ui, &query)
+            let app = create_app(&home, None)?;
+            search_cmd(&app, ui, &query)
         }
         Command::Doc { package_name } => {
-            let app = App::new(&home)?;
-            doc_cmd(&app, &package_name)
+            let app = create_app(&home, None)?;
+            doc_cmd(&app, ui, &package_name)
         }
         Command::List { json } => {
-            let app = App::new(&home)?;
-            list_cmd(&app, json)
+            let app = create_app(&home, None)?;
+            list_cmd(&app, ui, json)
         }
-        Command::Upgrade {} => {
-            let app = App::new(&home)?;
-            upgrade_cmd(&app, &ui)
+        Command::Upgrade {
+            excludes,
+            ignore_pins,
+            package_names,
+        } => {
+            let app = create_app(&home, None)?;
+            upgrade_cmd(&app, ui, &package_names, &excludes, ignore_pins)
+        }
+        Command::Outdated { json } => {
+            let app = create_app(&home, None)?;
+            let has_upgrades = outdated_cmd(&app, ui, json)?;
+            if has_upgrades {
+                return Ok(ExitCode::from(UPGRADES_AVAILABLE_EXIT_CODE));
+            }
+            Ok(())
+        }
+        Command::Pin { package_name } => {
+            let app = create_app(&home, None)?;
+            pin_cmd(&app, ui, &package_name)
+        }
+        Command::Unpin { package_names } => {
+            let app = create_app(&home, None)?;
+            unpin_cmd(&app, ui, &package_names)
+        }
+        Command::Downgrade { package_name } => {
+            let app = create_app(&home, None)?;
+            downgrade_cmd(&app, ui, &package_name)
+        }
+        Command::Env { shell } => env_cmd(&home, ui, shell),
+        Command::ShellInit { shell } => shell_init_cmd(&home, shell),
+        // `doctor` creates the app itself, to check the database before it is upgraded
+        Command::Doctor { fix } => doctor_cmd(&home, ui, fix),
+        Command::Owns { path_or_command } => {
+            let app = create_app(&home, None)?;
+            owns_cmd(&app, ui, &path_or_command)
+        }
+        Command::Provides { command } => {
+            let app = create_app(&home, None)?;
+            provides_cmd(&app, ui, &command)
         }
     };
+    result.map(|()| ExitCode::SUCCESS)
+}
+
+pub fn exec(cli: Cli) -> Result<ExitCode> {
+    let ui = Ui::new(cli.output);
+    let result = run_command(&ui, cli.command);
     if let Err(ref err) = result {
         if ctrlcutils::is_ctrlc(err) {
-            println!("Interrupted");
-            return Ok(());
+            if ui.is_json() {
+                ui.emit(Event::Result {
+                    success: false,
+                    error: Some("Interrupted".to_string()),
+                });
+            } else {
+                println!("Interrupted");
+            }
+            return Ok(ExitCode::SUCCESS);
         }
     }
+    ui.emit(Event::Result {
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|x| format!("{x:#}")),
+    });
     result
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
     exec(cli)
 }
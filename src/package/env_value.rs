@@ -0,0 +1,98 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Returns true if `name` can be used as an environment variable name by all shells
+pub fn is_valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|x| x.is_ascii_alphabetic() || x == '_')
+        && chars.all(|x| x.is_ascii_alphanumeric() || x == '_')
+}
+
+/// How an environment variable of a package is defined
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvAction {
+    /// Set the variable to the value
+    Set,
+    /// Prepend the value to the variable, which is a list of paths
+    Prepend,
+}
+
+impl fmt::Display for EnvAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnvAction::Set => write!(f, "set"),
+            EnvAction::Prepend => write!(f, "prepend"),
+        }
+    }
+}
+
+impl FromStr for EnvAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "set" => Ok(EnvAction::Set),
+            "prepend" => Ok(EnvAction::Prepend),
+            _ => Err(anyhow!("Invalid environment action '{s}'")),
+        }
+    }
+}
+
+/// An environment variable value, as defined in the `env` mapping of an install. A plain string
+/// sets the variable, `{prepend: value}` prepends to it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum EnvValue {
+    Set(String),
+    Prepend { prepend: String },
+}
+
+impl EnvValue {
+    pub fn new(action: EnvAction, value: &str) -> EnvValue {
+        match action {
+            EnvAction::Set => EnvValue::Set(value.to_string()),
+            EnvAction::Prepend => EnvValue::Prepend {
+                prepend: value.to_string(),
+            },
+        }
+    }
+
+    pub fn action(&self) -> EnvAction {
+        match self {
+            EnvValue::Set(_) => EnvAction::Set,
+            EnvValue::Prepend { .. } => EnvAction::Prepend,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        match self {
+            EnvValue::Set(value) => value,
+            EnvValue::Prepend { prepend } => prepend,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_valid_env_name_should_only_accept_portable_names() {
+        assert!(is_valid_env_name("JAVA_HOME"));
+        assert!(is_valid_env_name("_foo2"));
+        assert!(!is_valid_env_name(""));
+        assert!(!is_valid_env_name("2FOO"));
+        assert!(!is_valid_env_name("FOO-BAR"));
+        assert!(!is_valid_env_name("FOO;rm"));
+    }
+}
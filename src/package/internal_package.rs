@@ -4,14 +4,107 @@
 
 use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
+use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{anyhow, Error, Result};
 use chrono::{DateTime, Utc};
-use semver::Version;
 use serde::{Deserialize, Serialize};
 
 use crate::arch_os::ArchOs;
-use crate::package::{Asset, FetcherConfig, Install, Package, Release};
+use crate::checksum::{Checksum, HashAlgorithm};
+use crate::package::{Asset, FetcherConfig, Install, Package, Release, SigningConfig};
+use crate::version::{Version, VersionScheme};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum InternalChecksumEnum {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Serialized form of an Asset. `sha256` is kept for compatibility with older Clyde versions,
+/// other checksums are stored as `<algorithm>:<value>` strings in `checksum`.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct InternalAsset {
+    /// Empty if the URL comes from the `asset_url_template` of the package
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    url: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum: Option<InternalChecksumEnum>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+impl TryFrom<InternalAsset> for Asset {
+    type Error = Error;
+
+    fn try_from(internal_asset: InternalAsset) -> Result<Self> {
+        let asset_desc = if internal_asset.url.is_empty() {
+            "using asset_url_template".to_string()
+        } else {
+            internal_asset.url.clone()
+        };
+        let mut checksums = Vec::<Checksum>::new();
+        if let Some(sha256) = internal_asset.sha256 {
+            // Use the same validation as the `checksum` entry
+            checksums.push(Checksum::from_str(&format!("sha256:{sha256}"))?);
+        }
+        let checksum_strings = match internal_asset.checksum {
+            None => vec![],
+            Some(InternalChecksumEnum::One(x)) => vec![x],
+            Some(InternalChecksumEnum::Many(x)) => x,
+        };
+        for checksum_str in checksum_strings {
+            let checksum = Checksum::from_str(&checksum_str)?;
+            if let Some(other) = checksums.iter().find(|x| x.algorithm == checksum.algorithm) {
+                if other.value != checksum.value {
+                    return Err(anyhow!(
+                        "Asset {} has conflicting {} checksums",
+                        asset_desc,
+                        checksum.algorithm
+                    ));
+                }
+                continue;
+            }
+            checksums.push(checksum);
+        }
+        if checksums.is_empty() {
+            return Err(anyhow!("Asset {asset_desc} has no checksum"));
+        }
+        Ok(Asset::new(&internal_asset.url, checksums).with_signature(internal_asset.signature))
+    }
+}
+
+impl From<Asset> for InternalAsset {
+    fn from(asset: Asset) -> Self {
+        let sha256 = asset
+            .get_checksum(HashAlgorithm::Sha256)
+            .map(|x| x.value.clone());
+        let mut others: Vec<String> = asset
+            .checksums
+            .iter()
+            .filter(|x| x.algorithm != HashAlgorithm::Sha256)
+            .map(|x| x.to_string())
+            .collect();
+        let checksum = match others.len() {
+            0 => None,
+            1 => Some(InternalChecksumEnum::One(others.remove(0))),
+            _ => Some(InternalChecksumEnum::Many(others)),
+        };
+        InternalAsset {
+            url: asset.url,
+            sha256,
+            checksum,
+            signature: asset.signature,
+        }
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub(crate) struct InternalReleaseV2 {
@@ -32,6 +125,10 @@ fn is_auto_fetcher(x: &FetcherConfig) -> bool {
     *x == FetcherConfig::Auto
 }
 
+fn is_semver(x: &VersionScheme) -> bool {
+    *x == VersionScheme::Semver
+}
+
 /// Intermediate struct, used to serialize and deserialize. After deserializing it is turned into
 /// Package, which has stronger typing
 #[derive(Debug, Deserialize, Serialize)]
@@ -44,11 +141,20 @@ pub(crate) struct InternalPackage {
     #[serde(default)]
     #[serde(skip_serializing_if = "String::is_empty")]
     pub comment: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_semver")]
+    pub version_scheme: VersionScheme,
     pub releases: Option<BTreeMap<String, InternalReleaseEnum>>,
     pub installs: Option<BTreeMap<String, BTreeMap<String, Install>>>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_url_template: Option<String>,
+    #[serde(default)]
     #[serde(skip_serializing_if = "is_auto_fetcher")]
     pub fetcher: FetcherConfig,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
 }
 
 impl From<&Package> for InternalPackage {
@@ -59,7 +165,17 @@ impl From<&Package> for InternalPackage {
             let assets: BTreeMap<String, Asset> = release
                 .assets
                 .iter()
-                .map(|(arch_os, asset)| (arch_os.to_str(), asset.clone()))
+                .map(|(arch_os, asset)| {
+                    let mut asset = asset.clone();
+                    // Do not write the URL if the template produces it
+                    if package
+                        .get_templated_asset_url(version, arch_os)
+                        .is_some_and(|x| x.is_ok_and(|url| url == asset.url))
+                    {
+                        asset.url.clear();
+                    }
+                    (arch_os.to_str(), asset)
+                })
                 .collect();
             let internal_release_v2 = InternalReleaseV2 {
                 added_at: release.added_at,
@@ -84,9 +200,12 @@ impl From<&Package> for InternalPackage {
             homepage: package.homepage.clone(),
             repository: package.repository.clone(),
             comment: package.comment.clone(),
+            version_scheme: package.version_scheme,
             releases: Some(releases),
+            asset_url_template: package.asset_url_template.clone(),
             installs: Some(installs),
             fetcher: package.fetcher.clone(),
+            signing: package.signing.clone(),
         }
     }
 }
@@ -97,7 +216,7 @@ impl InternalPackage {
 
         if let Some(internal_releases) = &self.releases {
             for (version_str, internal_release_enum) in internal_releases.iter() {
-                let version = Version::parse(version_str)?;
+                let version = Version::parse_with_scheme(version_str, self.version_scheme)?;
                 let release = match internal_release_enum {
                     InternalReleaseEnum::V2(internal_release) => {
                         let assets = internal_release
@@ -128,7 +247,7 @@ impl InternalPackage {
         let mut installs = BTreeMap::<Version, HashMap<ArchOs, Install>>::new();
         if let Some(internal_installs) = &self.installs {
             for (version_str, installs_for_arch_os) in internal_installs.iter() {
-                let version = Version::parse(version_str)?;
+                let version = Version::parse_with_scheme(version_str, self.version_scheme)?;
                 let installs_for_arch_os = installs_for_arch_os
                     .iter()
                     .map(|(arch_os, install)| (ArchOs::parse(arch_os).unwrap(), install.clone()))
@@ -137,16 +256,21 @@ impl InternalPackage {
             }
         }
 
-        Ok(Package {
+        let mut package = Package {
             name: self.name.clone(),
             description: self.description.clone(),
             homepage: self.homepage.clone(),
             repository: self.repository.clone(),
             comment: self.comment.clone(),
+            version_scheme: self.version_scheme,
             releases,
+            asset_url_template: self.asset_url_template.clone(),
             installs,
             package_dir: package_dir.to_path_buf(),
             fetcher: self.fetcher.clone(),
-        })
+            signing: self.signing.clone(),
+        };
+        package.fill_templated_asset_urls()?;
+        Ok(package)
     }
 }
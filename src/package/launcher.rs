@@ -0,0 +1,280 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::package::is_valid_env_name;
+
+/// A wrapper script generated at install time, to start an executable with fixed arguments and
+/// environment variables
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Launcher {
+    /// The executable to start, relative to the install dir
+    pub target: String,
+    /// Arguments passed before the ones given to the launcher
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Environment variables to set before starting the executable
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Returns the length of the reference to an environment variable at the start of `text`:
+/// `NAME` or `{NAME}` after a `$` in POSIX scripts, `NAME%` after a `%` in batch files. Returns 0
+/// if `text` does not start with a reference.
+fn env_reference_len(text: &str, open: &str, close: &str) -> usize {
+    let Some(rest) = text.strip_prefix(open) else {
+        return 0;
+    };
+    let name_len = rest
+        .find(|x: char| !(x.is_ascii_alphanumeric() || x == '_'))
+        .unwrap_or(rest.len());
+    if !is_valid_env_name(&rest[..name_len]) || !rest[name_len..].starts_with(close) {
+        return 0;
+    }
+    open.len() + name_len + close.len()
+}
+
+/// Escapes `value` so that it can be put inside double quotes in a POSIX shell script. If
+/// `allow_vars` is true, references to environment variables (`$NAME` or `${NAME}`) are kept,
+/// but any other use of `$`, such as command substitution, is escaped.
+fn posix_escape(value: &str, allow_vars: bool) -> String {
+    let mut escaped = String::new();
+    let mut chars = value.char_indices();
+    while let Some((idx, ch)) = chars.next() {
+        if ch == '$' && allow_vars {
+            let rest = &value[idx + 1..];
+            let len = env_reference_len(rest, "{", "}").max(env_reference_len(rest, "", ""));
+            if len > 0 {
+                escaped.push('$');
+                escaped.push_str(&rest[..len]);
+                chars.nth(len - 1);
+                continue;
+            }
+        }
+        if matches!(ch, '"' | '\\' | '`' | '$') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+fn posix_quote(value: &str) -> String {
+    format!("\"{}\"", posix_escape(value, true))
+}
+
+/// Escapes `value` so that it can be put inside double quotes in a batch file, in which delayed
+/// expansion is disabled. If `allow_vars` is true, references to environment variables
+/// (`%NAME%`) are kept, but other `%` are escaped. Fails if `value` contains characters which
+/// cannot be escaped inside double quotes.
+fn cmd_escape(value: &str, allow_vars: bool) -> Result<String> {
+    if value.contains(['"', '\r', '\n']) {
+        return Err(anyhow!(
+            "{value:?} cannot be used in a batch file: it contains a double quote or a line break"
+        ));
+    }
+    let mut escaped = String::new();
+    let mut chars = value.char_indices();
+    while let Some((idx, ch)) = chars.next() {
+        if ch == '%' {
+            let rest = &value[idx + 1..];
+            let len = if allow_vars {
+                env_reference_len(rest, "", "%")
+            } else {
+                0
+            };
+            if len > 0 {
+                escaped.push('%');
+                escaped.push_str(&rest[..len]);
+                chars.nth(len - 1);
+            } else {
+                escaped.push_str("%%");
+            }
+            continue;
+        }
+        escaped.push(ch);
+    }
+    Ok(escaped)
+}
+
+/// Quotes `arg` for a batch file, so that the started executable receives it unchanged
+fn cmd_quote_arg(arg: &str) -> Result<String> {
+    let escaped = cmd_escape(arg, true)?;
+    // Backslashes before the closing quote must be doubled, otherwise the executable sees an
+    // escaped quote
+    let trailing_backslashes = escaped.len() - escaped.trim_end_matches('\\').len();
+    Ok(format!(
+        "\"{escaped}{}\"",
+        "\\".repeat(trailing_backslashes)
+    ))
+}
+
+fn check_env_name(name: &str) -> Result<()> {
+    if !is_valid_env_name(name) {
+        return Err(anyhow!("Invalid environment variable name '{name}'"));
+    }
+    Ok(())
+}
+
+impl Launcher {
+    /// Returns a POSIX shell script starting `target` with the args and env of the launcher.
+    /// `target` is relative to the directory of the script, so that the script keeps working if
+    /// Clyde home is moved. `args` and `env` must have been expanded.
+    pub fn to_posix_script(&self, target: &Path) -> Result<String> {
+        let mut script = String::from("#!/bin/sh\n# Generated by Clyde, do not edit\n");
+        for (name, value) in &self.env {
+            check_env_name(name)?;
+            script.push_str(&format!("export {name}={}\n", posix_quote(value)));
+        }
+        script.push_str(&format!(
+            "exec \"$(dirname -- \"$0\")/{}\"",
+            posix_escape(&target.to_string_lossy(), false)
+        ));
+        for arg in &self.args {
+            script.push(' ');
+            script.push_str(&posix_quote(arg));
+        }
+        script.push_str(" \"$@\"\n");
+        Ok(script)
+    }
+
+    /// Returns a Windows batch script starting `target` with the args and env of the launcher.
+    /// `target` is relative to the directory of the script, so that the script keeps working if
+    /// Clyde home is moved. `args` and `env` must have been expanded.
+    pub fn to_cmd_script(&self, target: &Path) -> Result<String> {
+        let mut script = String::from(
+            "@echo off\r\nrem Generated by Clyde, do not edit\r\nsetlocal DisableDelayedExpansion\r\n",
+        );
+        for (name, value) in &self.env {
+            check_env_name(name)?;
+            script.push_str(&format!("set \"{name}={}\"\r\n", cmd_escape(value, true)?));
+        }
+        let target = target
+            .components()
+            .map(|x| x.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("\\");
+        script.push_str(&format!("\"%~dp0{}\"", cmd_escape(&target, false)?));
+        for arg in &self.args {
+            script.push(' ');
+            script.push_str(&cmd_quote_arg(arg)?);
+        }
+        script.push_str(" %*\r\n");
+        Ok(script)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_launcher() -> Launcher {
+        Launcher {
+            target: "opt/foo/foo".into(),
+            args: vec!["--config".into(), "a \"b\" $HOME".into()],
+            env: BTreeMap::from([("FOO_HOME".into(), "$CLYDE_HOME/inst/opt/foo".into())]),
+        }
+    }
+
+    #[test]
+    fn to_posix_script_should_quote_args_and_env() {
+        let launcher = create_launcher();
+
+        let script = launcher
+            .to_posix_script(Path::new("../opt/foo/foo"))
+            .unwrap();
+
+        assert_eq!(
+            script,
+            r#"#!/bin/sh
+# Generated by Clyde, do not edit
+export FOO_HOME="$CLYDE_HOME/inst/opt/foo"
+exec "$(dirname -- "$0")/../opt/foo/foo" "--config" "a \"b\" $HOME" "$@"
+"#
+        );
+    }
+
+    #[test]
+    fn to_posix_script_should_escape_command_substitutions() {
+        let launcher = Launcher {
+            target: "opt/foo/foo".into(),
+            args: vec!["$(rm -rf ~)".into(), "${HOME}/`id`".into(), "100$".into()],
+            env: BTreeMap::new(),
+        };
+
+        let script = launcher
+            .to_posix_script(Path::new("../opt/foo/foo"))
+            .unwrap();
+
+        assert!(
+            script.contains(r#""\$(rm -rf ~)" "${HOME}/\`id\`" "100\$" "$@""#),
+            "{script}"
+        );
+    }
+
+    #[test]
+    fn to_cmd_script_should_set_env_and_pass_args() {
+        let launcher = Launcher {
+            target: "opt/foo/foo.exe".into(),
+            args: vec!["--config".into()],
+            env: BTreeMap::from([("FOO_HOME".into(), "%CLYDE_HOME%\\inst\\opt\\foo".into())]),
+        };
+
+        let script = launcher
+            .to_cmd_script(Path::new("../opt/foo/foo.exe"))
+            .unwrap();
+
+        assert_eq!(
+            script,
+            "@echo off\r\nrem Generated by Clyde, do not edit\r\nsetlocal DisableDelayedExpansion\r\nset \"FOO_HOME=%CLYDE_HOME%\\inst\\opt\\foo\"\r\n\"%~dp0..\\opt\\foo\\foo.exe\" \"--config\" %*\r\n"
+        );
+    }
+
+    #[test]
+    fn to_cmd_script_should_escape_special_characters() {
+        let launcher = Launcher {
+            target: "opt/foo/foo.exe".into(),
+            args: vec!["a & b | c ^ !d!".into(), "100%".into(), "C:\\dir\\".into()],
+            env: BTreeMap::new(),
+        };
+
+        let script = launcher
+            .to_cmd_script(Path::new("../opt/foo/foo.exe"))
+            .unwrap();
+
+        assert!(
+            script.contains("\"a & b | c ^ !d!\" \"100%%\" \"C:\\dir\\\\\" %*"),
+            "{script}"
+        );
+    }
+
+    #[test]
+    fn to_cmd_script_should_reject_double_quotes() {
+        let mut launcher = create_launcher();
+        launcher.env.clear();
+
+        assert!(launcher
+            .to_cmd_script(Path::new("../opt/foo/foo.exe"))
+            .is_err());
+    }
+
+    #[test]
+    fn scripts_should_reject_invalid_env_names() {
+        let launcher = Launcher {
+            target: "opt/foo/foo".into(),
+            args: vec![],
+            env: BTreeMap::from([("FOO=1 evil".into(), "value".into())]),
+        };
+
+        assert!(launcher.to_posix_script(Path::new("foo")).is_err());
+        assert!(launcher.to_cmd_script(Path::new("foo.exe")).is_err());
+    }
+}
@@ -2,8 +2,11 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+mod env_value;
 mod fetcher_config;
 mod internal_package;
+mod launcher;
+mod signing_config;
 
 use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
@@ -11,21 +14,49 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, TimeDelta, Utc};
-use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 
+pub use env_value::{is_valid_env_name, EnvAction, EnvValue};
 pub use fetcher_config::FetcherConfig;
+pub use launcher::Launcher;
+pub use signing_config::SigningConfig;
 
-use crate::arch_os::{Arch, ArchOs, Os};
+use crate::arch_os::ArchOs;
+use crate::checksum::{Checksum, HashAlgorithm};
+use crate::unpacker::ArchiveFormat;
+use crate::vars::{expand_vars, VarsMap};
+use crate::version::{Version, VersionReq, VersionScheme};
 
-use internal_package::InternalPackage;
+use internal_package::{InternalAsset, InternalPackage};
 
 pub const EXTRA_FILES_DIR_NAME: &str = "extra_files";
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(try_from = "InternalAsset", into = "InternalAsset")]
 pub struct Asset {
     pub url: String,
-    pub sha256: String,
+    pub checksums: Vec<Checksum>,
+    /// URL of the upstream signature of the asset
+    pub signature: Option<String>,
+}
+
+impl Asset {
+    pub fn new(url: &str, checksums: Vec<Checksum>) -> Self {
+        Asset {
+            url: url.to_string(),
+            checksums,
+            signature: None,
+        }
+    }
+
+    pub fn with_signature(mut self, signature: Option<String>) -> Self {
+        self.signature = signature;
+        self
+    }
+
+    pub fn get_checksum(&self, algorithm: HashAlgorithm) -> Option<&Checksum> {
+        self.checksums.iter().find(|x| x.algorithm == algorithm)
+    }
 }
 
 pub type ReleaseAssets = HashMap<ArchOs, Asset>;
@@ -57,15 +88,53 @@ pub struct Install {
     #[serde(default)]
     #[serde(skip_serializing_if = "is_zero")]
     pub strip: u32,
+    /// Format of the asset, when it cannot be detected
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ArchiveFormat>,
     pub files: BTreeMap<String, String>,
     #[serde(default)]
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub extra_files: BTreeMap<String, String>,
+    /// Symlinks to create, mapping the path of the link to its target. Both are relative to the
+    /// install dir.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub symlinks: BTreeMap<String, String>,
+    /// Launcher scripts to generate, indexed by their path relative to the install dir
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub launchers: BTreeMap<String, Launcher>,
+    /// Files to generate by running an installed executable, mapping the path of the file,
+    /// relative to the install dir, to the command whose output it receives
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub generate: BTreeMap<String, String>,
+    /// Environment variables to define when Clyde is activated
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, EnvValue>,
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tests: Vec<String>,
 }
 
+/// Expands the variables of `template`, the `asset_url_template` of package `name`
+fn expand_asset_url_template(
+    template: &str,
+    name: &str,
+    version: &Version,
+    arch_os: &ArchOs,
+) -> Result<String> {
+    let vars = VarsMap::from([
+        ("name".to_string(), name.to_string()),
+        ("version".to_string(), version.to_string()),
+        ("arch".to_string(), arch_os.arch.to_string()),
+        ("os".to_string(), arch_os.os.to_string()),
+    ]);
+    expand_vars(template, &vars)
+}
+
 #[derive(Debug, Clone)]
 pub struct Package {
     pub name: String,
@@ -73,12 +142,17 @@ pub struct Package {
     pub homepage: String,
     pub repository: String,
     pub comment: String,
+    pub version_scheme: VersionScheme,
     pub releases: BTreeMap<Version, Release>,
+    /// If set, the URL of assets which do not define one. Can use the `${name}`, `${version}`,
+    /// `${arch}` and `${os}` variables.
+    pub asset_url_template: Option<String>,
 
     pub installs: BTreeMap<Version, HashMap<ArchOs, Install>>,
     pub package_dir: PathBuf,
 
     pub fetcher: FetcherConfig,
+    pub signing: Option<SigningConfig>,
 }
 
 impl Package {
@@ -103,6 +177,35 @@ impl Package {
         Ok(())
     }
 
+    /// Returns the URL of the asset for `version` and `arch_os`, built from
+    /// `asset_url_template`. Returns None if the package has no template.
+    pub fn get_templated_asset_url(
+        &self,
+        version: &Version,
+        arch_os: &ArchOs,
+    ) -> Option<Result<String>> {
+        let template = self.asset_url_template.as_ref()?;
+        Some(expand_asset_url_template(
+            template, &self.name, version, arch_os,
+        ))
+    }
+
+    /// Sets the URL of the assets which do not define one using `asset_url_template`
+    fn fill_templated_asset_urls(&mut self) -> Result<()> {
+        for (version, release) in self.releases.iter_mut() {
+            for (arch_os, asset) in release.assets.iter_mut() {
+                if !asset.url.is_empty() {
+                    continue;
+                }
+                let template = self.asset_url_template.as_ref().ok_or_else(|| {
+                    anyhow!("The {arch_os} asset of {version} has no URL, and the package does not define `asset_url_template`")
+                })?;
+                asset.url = expand_asset_url_template(template, &self.name, version, arch_os)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Returns a clone of the package with the builds for version `version` replaced by
     /// those from `release`
     pub fn replace_release(&self, version: &Version, release: Release) -> Package {
@@ -114,18 +217,35 @@ impl Package {
             homepage: self.homepage.clone(),
             repository: self.repository.clone(),
             comment: self.comment.clone(),
+            version_scheme: self.version_scheme,
             releases,
+            asset_url_template: self.asset_url_template.clone(),
             installs: self.installs.clone(),
             package_dir: self.package_dir.clone(),
             fetcher: self.fetcher.clone(),
+            signing: self.signing.clone(),
         }
     }
 
-    pub fn get_version_matching(&self, requested_version: &VersionReq) -> Option<&Version> {
-        self.releases
-            .keys()
-            .rev()
-            .find(|&version| requested_version.matches(version))
+    /// Returns the most recent version matching `requested_version`. Pre-releases are only
+    /// considered if `allow_prereleases` is true.
+    pub fn get_version_matching(
+        &self,
+        requested_version: &VersionReq,
+        allow_prereleases: bool,
+    ) -> Option<&Version> {
+        self.releases.keys().rev().find(|&version| {
+            if allow_prereleases {
+                requested_version.matches_prerelease(version)
+            } else {
+                requested_version.matches(version)
+            }
+        })
+    }
+
+    /// Parse `text` using the version scheme of the package
+    pub fn parse_version(&self, text: &str) -> Result<Version> {
+        Version::parse_with_scheme(text, self.version_scheme)
     }
 
     pub fn get_latest_version(&self) -> Option<&Version> {
@@ -135,46 +255,19 @@ impl Package {
 
     pub fn get_asset(&self, version: &Version, arch_os: &ArchOs) -> Option<&Asset> {
         let release = self.releases.get(version)?;
-        let asset = release.assets.get(arch_os);
-        if asset.is_some() {
-            return asset;
-        }
-        if arch_os.arch != Arch::Any {
-            let asset = release.assets.get(&arch_os.with_any_arch());
-            if asset.is_some() {
-                return asset;
-            }
-        }
-        if arch_os.os != Os::Any {
-            let asset = release.assets.get(&arch_os.with_any_os());
-            if asset.is_some() {
-                return asset;
-            }
-        }
-        release.assets.get(&ArchOs::any())
+        arch_os
+            .fallbacks()
+            .iter()
+            .find_map(|x| release.assets.get(x))
     }
 
     /// Return files definition for wanted_version
     /// Uses the highest version which is less or equal to wanted_version
     pub fn get_install(&self, wanted_version: &Version, arch_os: &ArchOs) -> Option<&Install> {
-        let install = self.get_install_internal(wanted_version, arch_os);
-        if install.is_some() {
-            return install;
-        }
-        if arch_os.arch != Arch::Any {
-            let install = self.get_install_internal(wanted_version, &arch_os.with_any_arch());
-            if install.is_some() {
-                return install;
-            }
-        }
-        if arch_os.os != Os::Any {
-            // Probably less useful than the previous check, but you never know
-            let install = self.get_install_internal(wanted_version, &arch_os.with_any_os());
-            if install.is_some() {
-                return install;
-            }
-        }
-        self.get_install_internal(wanted_version, &ArchOs::any())
+        arch_os
+            .fallbacks()
+            .iter()
+            .find_map(|x| self.get_install_internal(wanted_version, x))
     }
 
     fn get_install_internal(&self, wanted_version: &Version, arch_os: &ArchOs) -> Option<&Install> {
@@ -207,6 +300,7 @@ mod tests {
     use super::*;
     use std::{fs, str::FromStr};
 
+    use crate::arch_os::{Arch, Libc, Os};
     use crate::store::INDEX_NAME;
 
     const TEST_PACKAGE_YAML_CONTENT: &str = "
@@ -217,13 +311,13 @@ mod tests {
       1.2.0:
         any:
           url: https://example.com/foo-1.2.0
-          sha256: '1234'
+          sha256: '1234123412341234123412341234123412341234123412341234123412341234'
       1.3.0:
         added_at: '2024-01-02T12:34:56Z'
         assets:
           any:
             url: https://example.com/foo-1.3.0
-            sha256: '5678'
+            sha256: '5678567856785678567856785678567856785678567856785678567856785678'
     installs:
       1.2.0:
         any:
@@ -303,7 +397,10 @@ mod tests {
             .unwrap();
         assert!(release_120.added_at.is_none());
         let asset_120 = release_120.assets.get(&ArchOs::any()).unwrap();
-        assert_eq!(asset_120.sha256, "1234");
+        assert_eq!(
+            asset_120.checksums,
+            vec![Checksum::new(HashAlgorithm::Sha256, &"1234".repeat(16))]
+        );
 
         // AND the 1.3.0 release, which uses the V2 variant, is correctly loaded
         let release_130 = package
@@ -320,7 +417,10 @@ mod tests {
         );
         assert!(release_130.assets.contains_key(&ArchOs::any()));
         let asset_130 = release_130.assets.get(&ArchOs::any()).unwrap();
-        assert_eq!(asset_130.sha256, "5678");
+        assert_eq!(
+            asset_130.checksums,
+            vec![Checksum::new(HashAlgorithm::Sha256, &"5678".repeat(16))]
+        );
 
         // AND the install section for the 1.2.0 release is correctly loaded
         let install = package
@@ -345,7 +445,7 @@ mod tests {
               2.0.0:
                 x86_64-linux:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
             installs: {}
             ",
         )
@@ -392,7 +492,7 @@ mod tests {
               2.0.0:
                 x86_64-linux:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
             installs: {}
             ",
         )
@@ -409,6 +509,138 @@ mod tests {
         assert_eq!(comment.as_str().unwrap(), "Careful with test");
     }
 
+    #[test]
+    fn load_package_with_typed_checksums() {
+        // GIVEN a package using sha256, a typed checksum and multiple typed checksums
+        let sha512 = "5678".repeat(32);
+        let blake3 = "abcd".repeat(16);
+        let package = Package::from_yaml_str(&format!(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                x86_64-linux:
+                  url: https://example.com/linux
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+                  checksum: blake3:{blake3}
+                x86_64-windows:
+                  url: https://example.com/windows
+                  checksum: sha512:{sha512}
+                x86_64-macos:
+                  url: https://example.com/macos
+                  checksum:
+                    - sha512:{sha512}
+                    - blake3:{blake3}
+            installs: {{}}
+            "
+        ))
+        .unwrap();
+
+        // THEN all checksums are loaded
+        let release = package.releases.get(&Version::new(1, 0, 0)).unwrap();
+        let get_checksums = |os| {
+            release
+                .assets
+                .get(&ArchOs::new(Arch::X86_64, os))
+                .unwrap()
+                .checksums
+                .clone()
+        };
+        assert_eq!(
+            get_checksums(Os::Linux),
+            vec![
+                Checksum::new(HashAlgorithm::Sha256, &"1234".repeat(16)),
+                Checksum::new(HashAlgorithm::Blake3, &blake3)
+            ]
+        );
+        assert_eq!(
+            get_checksums(Os::Windows),
+            vec![Checksum::new(HashAlgorithm::Sha512, &sha512)]
+        );
+        assert_eq!(
+            get_checksums(Os::MacOs),
+            vec![
+                Checksum::new(HashAlgorithm::Sha512, &sha512),
+                Checksum::new(HashAlgorithm::Blake3, &blake3)
+            ]
+        );
+    }
+
+    #[test]
+    fn load_package_fails_if_an_asset_has_no_checksum() {
+        let result = Package::from_yaml_str(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                any:
+                  url: https://example.com
+            installs: {}
+            ",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn load_package_fails_if_the_sha256_entry_is_invalid() {
+        let result = Package::from_yaml_str(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                any:
+                  url: https://example.com
+                  sha256: '1234'
+            installs: {}
+            ",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn saving_package_keeps_sha256_entry() {
+        // GIVEN a package with a sha256 and a sha512 checksum
+        let sha256 = "1234".repeat(16);
+        let sha512 = "5678".repeat(32);
+        let package = Package::from_yaml_str(&format!(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases:
+              2.0.0:
+                any:
+                  url: https://example.com
+                  checksum: [sha256:{sha256}, sha512:{sha512}]
+            installs: {{}}
+            "
+        ))
+        .unwrap();
+
+        // WHEN it's saved to disk
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.join("test.yaml");
+        package.to_file(&path).unwrap();
+
+        // THEN the sha256 checksum is stored in the `sha256` entry, so that older versions of
+        // Clyde can still install the package
+        let root = read_yaml_from_path(&path);
+        let asset = &root["releases"]["2.0.0"]["assets"]["any-any"];
+        assert_eq!(asset["sha256"].as_str(), Some(sha256.as_str()));
+
+        // AND the other checksum is stored in the `checksum` entry
+        assert_eq!(
+            asset["checksum"].as_str(),
+            Some(format!("sha512:{sha512}").as_str())
+        );
+    }
+
     #[test]
     fn test_get_version_matching() {
         let package = Package::from_yaml_str(
@@ -420,15 +652,15 @@ mod tests {
               2.0.0:
                 any:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
               1.2.1:
                 any:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
               1.2.0:
                 any:
                   url: https://example.com
-                  sha256: '1234'
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
             installs: {}
             ",
         )
@@ -442,10 +674,119 @@ mod tests {
         let v121 = Version::new(1, 2, 1);
         let v200 = Version::new(2, 0, 0);
 
-        assert_eq!(package.get_version_matching(&req300), None);
-        assert_eq!(package.get_version_matching(&req121), Some(&v121));
-        assert_eq!(package.get_version_matching(&req12), Some(&v121));
-        assert_eq!(package.get_version_matching(&req2), Some(&v200));
+        assert_eq!(package.get_version_matching(&req300, false), None);
+        assert_eq!(package.get_version_matching(&req121, false), Some(&v121));
+        assert_eq!(package.get_version_matching(&req12, false), Some(&v121));
+        assert_eq!(package.get_version_matching(&req2, false), Some(&v200));
+    }
+
+    #[test]
+    fn get_version_matching_should_only_return_prereleases_if_allowed() {
+        // GIVEN a package with a 1.0.0 release and a 1.1.0-rc.1 pre-release
+        let package = Package::from_yaml_str(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+              1.1.0-rc.1:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+            installs: {}
+            ",
+        )
+        .unwrap();
+        let v100 = Version::new(1, 0, 0);
+        let v110rc1 = Version::parse("1.1.0-rc.1").unwrap();
+        let req11 = VersionReq::parse("1.1.*").unwrap();
+
+        // WHEN pre-releases are not allowed THEN the pre-release is ignored
+        assert_eq!(
+            package.get_version_matching(&VersionReq::STAR, false),
+            Some(&v100)
+        );
+        assert_eq!(package.get_version_matching(&req11, false), None);
+
+        // WHEN pre-releases are allowed THEN the pre-release is returned
+        assert_eq!(
+            package.get_version_matching(&VersionReq::STAR, true),
+            Some(&v110rc1)
+        );
+        assert_eq!(package.get_version_matching(&req11, true), Some(&v110rc1));
+
+        // WHEN the requirement is exact THEN pre-releases with a different tag are ignored
+        let req110 = VersionReq::parse("=1.1.0").unwrap();
+        assert_eq!(package.get_version_matching(&req110, true), None);
+        let req110rc1 = VersionReq::parse("=1.1.0-rc.1").unwrap();
+        assert_eq!(
+            package.get_version_matching(&req110rc1, true),
+            Some(&v110rc1)
+        );
+
+        // WHEN the final release is available THEN it is preferred over the pre-release
+        let package = package.replace_release(&Version::new(1, 1, 0), Release::default());
+        assert_eq!(
+            package.get_version_matching(&VersionReq::STAR, true),
+            Some(&Version::new(1, 1, 0))
+        );
+    }
+
+    #[test]
+    fn packages_should_support_non_semver_versions() {
+        // GIVEN a package using CalVer
+        let yaml = "
+            name: test
+            description: desc
+            homepage:
+            version_scheme: calver
+            releases:
+              2023.12.31:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+              2024.01.05:
+                any:
+                  url: https://example.com
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+            installs:
+              2023.12.31:
+                any:
+                  files:
+                    foo:
+            ";
+
+        // WHEN it is loaded
+        let package = Package::from_yaml_str(yaml).unwrap();
+
+        // THEN versions are ordered using the package scheme
+        let latest = package.get_latest_version().unwrap();
+        assert_eq!(latest.to_string(), "2024.01.05");
+
+        // AND requirements match them
+        let req = VersionReq::parse("2023").unwrap();
+        assert_eq!(
+            package
+                .get_version_matching(&req, false)
+                .unwrap()
+                .to_string(),
+            "2023.12.31"
+        );
+
+        // AND installs apply to the following versions
+        assert!(package.get_install(latest, &ArchOs::any()).is_some());
+
+        // AND versions are serialized as they were written
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.join("test.yaml");
+        package.to_file(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("version_scheme: calver"));
+        assert!(content.contains("2024.01.05:"));
     }
 
     #[test]
@@ -483,6 +824,54 @@ mod tests {
         assert_eq!(install.strip, 3);
     }
 
+    #[test]
+    fn get_asset_should_only_use_gnu_assets_on_glibc_hosts() {
+        // GIVEN a package with a gnu asset for x86_64 and gnu and musl assets for aarch64
+        let package = Package::from_yaml_str(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                x86_64-linux-gnu:
+                  url: https://example.com/x86_64-gnu
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+                aarch64-linux-gnu:
+                  url: https://example.com/aarch64-gnu
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+                aarch64-linux-musl:
+                  url: https://example.com/aarch64-musl
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+            installs: {}
+            ",
+        )
+        .unwrap();
+        let version = Version::new(1, 0, 0);
+        let get_url = |arch: Arch, libc: Libc| {
+            package
+                .get_asset(&version, &ArchOs::new(arch, Os::Linux).with_libc(libc))
+                .map(|x| x.url.as_str())
+        };
+
+        // WHEN installing on a musl host THEN gnu assets are not used
+        assert_eq!(get_url(Arch::X86_64, Libc::Musl), None);
+        assert_eq!(
+            get_url(Arch::Aarch64, Libc::Musl),
+            Some("https://example.com/aarch64-musl")
+        );
+
+        // WHEN installing on a glibc host THEN gnu assets are preferred
+        assert_eq!(
+            get_url(Arch::X86_64, Libc::Gnu),
+            Some("https://example.com/x86_64-gnu")
+        );
+        assert_eq!(
+            get_url(Arch::Aarch64, Libc::Gnu),
+            Some("https://example.com/aarch64-gnu")
+        );
+    }
+
     #[test]
     fn strip_should_default_to_0_if_not_set() {
         // GIVEN a package with no value for `strip`
@@ -511,6 +900,201 @@ mod tests {
         assert_eq!(install.strip, 0);
     }
 
+    #[test]
+    fn install_should_accept_a_format_override() {
+        // GIVEN a package whose install sets the format of the asset
+        // WHEN parsing it
+        let package = Package::from_yaml_str(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases: {}
+            installs:
+              1.0.0:
+                any:
+                  format: tar.zst
+                  files:
+                    foo:
+            ",
+        )
+        .unwrap();
+
+        // THEN the format is set
+        let install = package
+            .get_install(&Version::new(1, 0, 0), &ArchOs::any())
+            .unwrap();
+        assert_eq!(install.format, Some(ArchiveFormat::TarZst));
+    }
+
+    #[test]
+    fn install_should_accept_symlinks_and_launchers() {
+        // GIVEN a package whose install defines symlinks and launchers
+        // WHEN parsing it
+        let package = Package::from_yaml_str(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases: {}
+            installs:
+              1.0.0:
+                any:
+                  files:
+                    foo: opt/foo/
+                  symlinks:
+                    bin/foo: opt/foo/foo
+                  launchers:
+                    bin/foo-debug:
+                      target: opt/foo/foo
+                      args: [--debug]
+                      env:
+                        FOO_LOG: debug
+            ",
+        )
+        .unwrap();
+
+        // THEN they are set
+        let install = package
+            .get_install(&Version::new(1, 0, 0), &ArchOs::any())
+            .unwrap();
+        assert_eq!(
+            install.symlinks,
+            BTreeMap::from([("bin/foo".to_string(), "opt/foo/foo".to_string())])
+        );
+        assert_eq!(
+            install.launchers,
+            BTreeMap::from([(
+                "bin/foo-debug".to_string(),
+                Launcher {
+                    target: "opt/foo/foo".into(),
+                    args: vec!["--debug".into()],
+                    env: BTreeMap::from([("FOO_LOG".into(), "debug".into())]),
+                }
+            )])
+        );
+    }
+
+    const TEMPLATED_PACKAGE: &str = "
+        name: foo
+        description: desc
+        homepage:
+        asset_url_template: https://example.com/v${version}/${name}-${arch}-${os}.tar.gz
+        releases:
+          1.2.0:
+            x86_64-linux:
+              sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+            aarch64-macos:
+              url: https://example.com/v1.2.0/foo-universal-macos.tar.gz
+              sha256: '5678567856785678567856785678567856785678567856785678567856785678'
+        installs: {}
+        ";
+
+    #[test]
+    fn assets_without_url_should_use_the_asset_url_template() {
+        // GIVEN a package with an asset url template and an asset without url
+        // WHEN parsing it
+        let package = Package::from_yaml_str(TEMPLATED_PACKAGE).unwrap();
+
+        // THEN the url of the asset comes from the template
+        let version = Version::new(1, 2, 0);
+        let asset = package
+            .get_asset(&version, &ArchOs::parse("x86_64-linux").unwrap())
+            .unwrap();
+        assert_eq!(
+            asset.url,
+            "https://example.com/v1.2.0/foo-x86_64-linux.tar.gz"
+        );
+
+        // AND assets with an url keep it
+        let asset = package
+            .get_asset(&version, &ArchOs::parse("aarch64-macos").unwrap())
+            .unwrap();
+        assert_eq!(
+            asset.url,
+            "https://example.com/v1.2.0/foo-universal-macos.tar.gz"
+        );
+    }
+
+    #[test]
+    fn saving_package_should_omit_urls_produced_by_the_asset_url_template() {
+        // GIVEN a package with an asset url template
+        let package = Package::from_yaml_str(TEMPLATED_PACKAGE).unwrap();
+
+        // WHEN it's saved to disk
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.join("test.yaml");
+        package.to_file(&path).unwrap();
+
+        // THEN only the url which does not match the template is written
+        let root = read_yaml_from_path(&path);
+        let assets = &root["releases"]["1.2.0"]["assets"];
+        assert!(assets["x86_64-linux"].get("url").is_none());
+        assert_eq!(
+            assets["aarch64-macos"]["url"].as_str(),
+            Some("https://example.com/v1.2.0/foo-universal-macos.tar.gz")
+        );
+    }
+
+    #[test]
+    fn load_package_fails_if_an_asset_has_no_url_and_there_is_no_template() {
+        let result = Package::from_yaml_str(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases:
+              1.0.0:
+                x86_64-linux:
+                  sha256: '1234123412341234123412341234123412341234123412341234123412341234'
+            installs: {}
+            ",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn install_should_accept_env_entries() {
+        // GIVEN a package whose install defines environment variables
+        // WHEN parsing it
+        let package = Package::from_yaml_str(
+            "
+            name: test
+            description: desc
+            homepage:
+            releases: {}
+            installs:
+              1.0.0:
+                any:
+                  files:
+                    jdk: opt/jdk/
+                  env:
+                    JAVA_HOME: ${install_dir}/opt/jdk
+                    CLASSPATH:
+                      prepend: ${install_dir}/opt/jdk/lib
+            ",
+        )
+        .unwrap();
+
+        // THEN they are set
+        let install = package
+            .get_install(&Version::new(1, 0, 0), &ArchOs::any())
+            .unwrap();
+        assert_eq!(
+            install.env,
+            BTreeMap::from([
+                (
+                    "JAVA_HOME".to_string(),
+                    EnvValue::Set("${install_dir}/opt/jdk".into())
+                ),
+                (
+                    "CLASSPATH".to_string(),
+                    EnvValue::new(EnvAction::Prepend, "${install_dir}/opt/jdk/lib")
+                ),
+            ])
+        );
+    }
+
     #[test]
     fn enforce_cooldown_days_remove_too_recent_release() {
         // GIVEN a package with release 2.0 from 2 day ago
@@ -529,13 +1113,13 @@ mod tests {
                 assets:
                   any:
                     url: https://example.com/foo
-                    sha256: '1234'
+                    sha256: '1234123412341234123412341234123412341234123412341234123412341234'
               2.0.0:
                 added_at: {}
                 assets:
                   any:
                     url: https://example.com/foo
-                    sha256: '1234'
+                    sha256: '1234123412341234123412341234123412341234123412341234123412341234'
             installs: {{}}
             ",
             v1_added_at, v2_added_at
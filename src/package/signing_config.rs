@@ -0,0 +1,43 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use serde::{Deserialize, Serialize};
+
+/// Defines how to verify the upstream signatures of the package assets
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Hash, Serialize)]
+pub enum SigningConfig {
+    Minisign {
+        /// The minisign public key, in base64
+        public_key: String,
+    },
+    Gpg {
+        /// The ASCII-armored GPG public key
+        public_key: String,
+    },
+    Cosign {
+        /// The PEM-encoded public key, for key-based signatures
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        public_key: Option<String>,
+        /// The certificate identity, for keyless signatures
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        identity: Option<String>,
+        /// The certificate OIDC issuer, for keyless signatures
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        oidc_issuer: Option<String>,
+    },
+}
+
+impl SigningConfig {
+    /// Extensions used by the signature files of this signing method, ordered by preference
+    pub fn signature_extensions(&self) -> &'static [&'static str] {
+        match self {
+            SigningConfig::Minisign { .. } => &["minisig"],
+            SigningConfig::Gpg { .. } => &["asc", "sig"],
+            SigningConfig::Cosign { .. } => &["bundle", "sig"],
+        }
+    }
+}
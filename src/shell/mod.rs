@@ -0,0 +1,221 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{anyhow, Result};
+
+pub use crate::cli::Shell;
+
+pub mod rc_file;
+
+const SH_INIT: &str = include_str!("activate.sh.tmpl");
+const FISH_INIT: &str = include_str!("activate.fish.tmpl");
+const NU_INIT: &str = include_str!("activate.nu.tmpl");
+const POWERSHELL_INIT: &str = include_str!("activate.ps1.tmpl");
+const ELVISH_INIT: &str = include_str!("activate.elv.tmpl");
+
+/// One shell for each activation script. sh, bash and zsh share the same script.
+pub const ACTIVATION_SHELLS: [Shell; 5] = [
+    Shell::Sh,
+    Shell::Fish,
+    Shell::Nu,
+    Shell::PowerShell,
+    Shell::Elvish,
+];
+
+impl Shell {
+    pub fn is_posix(&self) -> bool {
+        matches!(self, Shell::Sh | Shell::Bash | Shell::Zsh)
+    }
+
+    /// Name of the activation script of the shell, in the `scripts` dir
+    pub fn activation_script_name(&self) -> &'static str {
+        match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh => "activate.sh",
+            Shell::Fish => "activate.fish",
+            Shell::Nu => "activate.nu",
+            Shell::PowerShell => "activate.ps1",
+            Shell::Elvish => "activate.elv",
+        }
+    }
+
+    /// Returns the command to add to the startup script of the shell to load the activation
+    /// script stored in `script_path`
+    pub fn source_command(&self, script_path: &Path) -> Result<String> {
+        let path = self.quote_path(script_path)?;
+        let command = match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh => format!(". {path}"),
+            Shell::Fish | Shell::Nu => format!("source {path}"),
+            Shell::PowerShell => format!(". {path}"),
+            Shell::Elvish => format!("eval (slurp < {path})"),
+        };
+        Ok(command)
+    }
+
+    /// Returns `text` quoted so that the shell reads it as a single string
+    pub fn quote(&self, text: &str) -> String {
+        match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh => shell_words::quote(text).to_string(),
+            Shell::Fish => format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'")),
+            // JSON strings are valid Nushell double-quoted strings
+            Shell::Nu => serde_json::to_string(text).unwrap(),
+            Shell::PowerShell | Shell::Elvish => format!("'{}'", text.replace('\'', "''")),
+        }
+    }
+
+    /// Returns `path` quoted for the shell. On Windows, POSIX shells get a Cygwin path.
+    pub fn quote_path(&self, path: &Path) -> Result<String> {
+        if self.is_posix() {
+            posix_shell_path_from_path(path)
+        } else {
+            Ok(self.quote(path.to_str().unwrap()))
+        }
+    }
+
+    /// Returns the content of the activation script of the shell, for the Clyde home `home`
+    pub fn create_activation_script(&self, home: &Path) -> Result<String> {
+        let template = match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh => SH_INIT,
+            Shell::Fish => FISH_INIT,
+            Shell::Nu => NU_INIT,
+            Shell::PowerShell => POWERSHELL_INIT,
+            Shell::Elvish => ELVISH_INIT,
+        };
+        Ok(template.replace("@CLYDE_HOME@", &self.quote_path(home)?))
+    }
+}
+
+fn posix_shell_path_from_unix_path(path: &Path) -> String {
+    shell_words::quote(path.to_str().unwrap()).to_string()
+}
+
+fn posix_shell_path_from_path(path: &Path) -> Result<String> {
+    if cfg!(not(target_os = "windows")) {
+        return Ok(posix_shell_path_from_unix_path(path));
+    }
+
+    let output = match Command::new("cygpath").arg(path).output() {
+        Ok(x) => x,
+        Err(e) => {
+            if e.kind() != io::ErrorKind::NotFound {
+                // TODO Try to use anyhow context() here?
+                return Err(anyhow!("Failed to run cygpath"));
+            }
+            // No cygpath executable, ignore the error, assume we are in an environment which does
+            // not need it
+            return Ok(posix_shell_path_from_unix_path(path));
+        }
+    };
+    if !output.status.success() {
+        let code = output.status.code().unwrap();
+        return Err(anyhow!(
+            "cygpath exited with code {code}:\n{}",
+            output.status.code().unwrap()
+        ));
+    }
+    let cygpath_output = String::from_utf8(output.stdout).unwrap();
+    Ok(shell_words::quote(cygpath_output.trim()).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use assert_fs::TempDir;
+    use which::which;
+    use yare::parameterized;
+
+    #[test]
+    fn shell_path_from_path_should_quote_correctly() {
+        if which("sh").is_err() {
+            // Skip test, can't be run in this environment
+            println!("Skipped");
+            return;
+        }
+        for input_path in ["/foo bar", "/it's quoted"] {
+            // GIVEN a path with characters requiring quoting
+            let shell_path = posix_shell_path_from_path(Path::new(input_path)).unwrap();
+
+            // WHEN the result path is passed to a shell to run "echo -n $result_path"
+            let echo_output = Command::new("sh")
+                .arg("-c")
+                .arg(format!("echo {shell_path}"))
+                .output()
+                .unwrap();
+
+            // THEN the output of the shell is input_path
+            let echo_path = String::from_utf8(echo_output.stdout).unwrap();
+            let echo_path = echo_path.trim();
+            assert_eq!(input_path, echo_path);
+        }
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn shell_path_from_path_should_use_cygpath() {
+        if which("cygpath").is_err() {
+            // Skip test, can't be run in this environment
+            println!("Skipped");
+            return;
+        }
+        for (input, expected) in [
+            (r"C:\foo", "/c/foo"),
+            (r"C:\foo with spaces", "'/c/foo with spaces'"),
+        ] {
+            let output = posix_shell_path_from_path(Path::new(input)).unwrap();
+            assert_eq!(output, expected.to_string());
+        }
+    }
+
+    #[parameterized(
+        fish = { Shell::Fish, r"'it\'s a \\ path'" },
+        nu = { Shell::Nu, r#""it's a \\ path""# },
+        powershell = { Shell::PowerShell, r"'it''s a \ path'" },
+        elvish = { Shell::Elvish, r"'it''s a \ path'" },
+    )]
+    fn quote_should_escape_quotes_and_backslashes(shell: Shell, expected: &str) {
+        assert_eq!(shell.quote(r"it's a \ path"), expected);
+    }
+
+    #[test]
+    fn posix_activation_script_should_set_path() {
+        for sh in ["sh", "dash", "bash"] {
+            if which(sh).is_err() {
+                continue;
+            }
+            // GIVEN a Clyde home with a space in its path
+            let temp_dir = TempDir::new().unwrap();
+            let home = temp_dir.join("clyde home");
+            let script_path = temp_dir.join("activate.sh");
+            std::fs::write(
+                &script_path,
+                Shell::Sh.create_activation_script(&home).unwrap(),
+            )
+            .unwrap();
+
+            // WHEN the activation script is sourced
+            let output = Command::new(sh)
+                .arg("-c")
+                .arg(format!(
+                    "{} && echo \"$CLYDE_HOME\" && echo \"$PATH\"",
+                    Shell::Sh.source_command(&script_path).unwrap()
+                ))
+                .env("PATH", "/usr/bin:/bin")
+                .output()
+                .unwrap();
+
+            // THEN CLYDE_HOME is set and the bin dir is in PATH
+            assert!(output.status.success(), "{sh} failed: {output:?}");
+            let stdout = String::from_utf8(output.stdout).unwrap();
+            let home_str = home.to_str().unwrap();
+            assert_eq!(
+                stdout,
+                format!("{home_str}\n{home_str}/inst/bin:/usr/bin:/bin\n")
+            );
+        }
+    }
+}
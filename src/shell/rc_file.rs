@@ -0,0 +1,258 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use directories::{BaseDirs, UserDirs};
+
+use crate::shell::Shell;
+
+const BLOCK_START: &str = "# >>> clyde >>>";
+const BLOCK_END: &str = "# <<< clyde <<<";
+const BLOCK_COMMENT: &str =
+    "# Added by `clyde setup --modify-rc`, remove with `clyde setup --unmodify-rc`";
+
+/// Shells which have a startup script Clyde can modify
+pub const RC_SHELLS: [Shell; 6] = [
+    Shell::Bash,
+    Shell::Zsh,
+    Shell::Fish,
+    Shell::Nu,
+    Shell::PowerShell,
+    Shell::Elvish,
+];
+
+fn xdg_config_dir(home_dir: &Path) -> PathBuf {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => home_dir.join(".config"),
+    }
+}
+
+/// Returns the path of the startup script of `shell`, if it has one
+pub fn get_rc_file_path(shell: Shell) -> Option<PathBuf> {
+    let base_dirs = BaseDirs::new()?;
+    let home_dir = base_dirs.home_dir();
+    let path = match shell {
+        Shell::Sh => return None,
+        Shell::Bash => home_dir.join(".bashrc"),
+        Shell::Zsh => {
+            let dir = env::var_os("ZDOTDIR").map_or(home_dir.to_path_buf(), PathBuf::from);
+            dir.join(".zshrc")
+        }
+        // fish uses ~/.config on all OSes
+        Shell::Fish => xdg_config_dir(home_dir).join("fish").join("config.fish"),
+        Shell::Nu => base_dirs.config_dir().join("nushell").join("config.nu"),
+        Shell::PowerShell => {
+            let dir = if cfg!(windows) {
+                UserDirs::new()?.document_dir()?.join("PowerShell")
+            } else {
+                xdg_config_dir(home_dir).join("powershell")
+            };
+            dir.join("Microsoft.PowerShell_profile.ps1")
+        }
+        Shell::Elvish => {
+            let dir = if cfg!(windows) {
+                base_dirs.config_dir().to_path_buf()
+            } else {
+                xdg_config_dir(home_dir)
+            };
+            dir.join("elvish").join("rc.elv")
+        }
+    };
+    Some(path)
+}
+
+/// Returns the login shell of the user, as defined by $SHELL
+fn get_login_shell() -> Option<Shell> {
+    let shell_path = PathBuf::from(env::var_os("SHELL")?);
+    let name = shell_path.file_stem()?.to_str()?;
+    Shell::from_str(name, true).ok()
+}
+
+/// Returns the shells used by the user, with the path of their startup script. A shell is
+/// considered used if its startup script exists, or if it's the login shell.
+pub fn find_user_shells() -> Vec<(Shell, PathBuf)> {
+    let login_shell = get_login_shell();
+    RC_SHELLS
+        .into_iter()
+        .filter_map(|shell| {
+            let path = get_rc_file_path(shell)?;
+            (path.exists() || login_shell == Some(shell)).then_some((shell, path))
+        })
+        .collect()
+}
+
+/// Returns the block to add to a startup script, which runs `command`
+pub fn create_block(command: &str) -> String {
+    format!("{BLOCK_START}\n{BLOCK_COMMENT}\n{command}\n{BLOCK_END}\n")
+}
+
+/// Returns the start and end line indexes of the Clyde block of `lines`, if any. `end` is the
+/// index of the end marker.
+fn find_block(lines: &[&str]) -> Result<Option<(usize, usize)>> {
+    let Some(start) = lines.iter().position(|x| x.trim_end() == BLOCK_START) else {
+        return Ok(None);
+    };
+    let end = lines[start..]
+        .iter()
+        .position(|x| x.trim_end() == BLOCK_END)
+        .ok_or_else(|| anyhow!("Found the start of a Clyde block, but not its end"))?;
+    Ok(Some((start, start + end)))
+}
+
+/// Returns `content` with `block` added, or with its existing Clyde block replaced with `block`.
+/// For zsh the block is inserted before the call to `compinit`, so that completions are found.
+pub fn add_block(content: &str, block: &str, shell: Shell) -> Result<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    if let Some((start, end)) = find_block(&lines)? {
+        let mut new_content = lines[..start].join("\n");
+        if start > 0 {
+            new_content.push('\n');
+        }
+        new_content.push_str(block);
+        for line in &lines[end + 1..] {
+            new_content.push_str(line);
+            new_content.push('\n');
+        }
+        return Ok(new_content);
+    }
+
+    let compinit_index = if shell == Shell::Zsh {
+        lines
+            .iter()
+            .position(|x| !x.trim_start().starts_with('#') && x.contains("compinit"))
+    } else {
+        None
+    };
+    if let Some(index) = compinit_index {
+        let mut new_content = String::new();
+        for line in &lines[..index] {
+            new_content.push_str(line);
+            new_content.push('\n');
+        }
+        new_content.push_str(block);
+        new_content.push('\n');
+        for line in &lines[index..] {
+            new_content.push_str(line);
+            new_content.push('\n');
+        }
+        return Ok(new_content);
+    }
+
+    let mut new_content = content.to_string();
+    if !new_content.is_empty() {
+        if !new_content.ends_with('\n') {
+            new_content.push('\n');
+        }
+        new_content.push('\n');
+    }
+    new_content.push_str(block);
+    Ok(new_content)
+}
+
+/// Returns `content` without its Clyde block, or None if it does not contain one
+pub fn remove_block(content: &str) -> Result<Option<String>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let Some((mut start, mut end)) = find_block(&lines)? else {
+        return Ok(None);
+    };
+    // Remove the empty line separating the block from the rest of the content
+    if start > 0 && lines[start - 1].trim().is_empty() {
+        start -= 1;
+    } else if end + 1 < lines.len() && lines[end + 1].trim().is_empty() {
+        end += 1;
+    }
+    let mut new_content = String::new();
+    for line in lines[..start].iter().chain(&lines[end + 1..]) {
+        new_content.push_str(line);
+        new_content.push('\n');
+    }
+    Ok(Some(new_content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_block_should_append_block() {
+        // GIVEN a startup script
+        let content = "export EDITOR=vim\n";
+
+        // WHEN a block is added to it
+        let block = create_block(". /clyde/activate.sh");
+        let new_content = add_block(content, &block, Shell::Bash).unwrap();
+
+        // THEN the block is appended, separated by an empty line
+        assert_eq!(
+            new_content,
+            format!("export EDITOR=vim\n\n{BLOCK_START}\n{BLOCK_COMMENT}\n. /clyde/activate.sh\n{BLOCK_END}\n")
+        );
+    }
+
+    #[test]
+    fn add_block_should_replace_existing_block() {
+        // GIVEN a startup script with a Clyde block
+        let old_block = create_block(". /old/activate.sh");
+        let content = format!("a\n\n{old_block}b\n");
+
+        // WHEN a block is added to it
+        let new_block = create_block(". /new/activate.sh");
+        let new_content = add_block(&content, &new_block, Shell::Bash).unwrap();
+
+        // THEN the existing block is replaced
+        assert_eq!(new_content, format!("a\n\n{new_block}b\n"));
+
+        // AND adding the block again does not change the content
+        assert_eq!(
+            add_block(&new_content, &new_block, Shell::Bash).unwrap(),
+            new_content
+        );
+    }
+
+    #[test]
+    fn add_block_should_insert_before_compinit_for_zsh() {
+        // GIVEN a zsh startup script calling compinit
+        let content = "# compinit is called below\nautoload -U compinit\ncompinit\n";
+
+        // WHEN a block is added to it
+        let block = create_block(". /clyde/activate.sh");
+        let new_content = add_block(content, &block, Shell::Zsh).unwrap();
+
+        // THEN the block is inserted before the first line loading compinit
+        assert_eq!(
+            new_content,
+            format!("# compinit is called below\n{block}\nautoload -U compinit\ncompinit\n")
+        );
+    }
+
+    #[test]
+    fn remove_block_should_restore_content() {
+        // GIVEN a startup script
+        let content = "export EDITOR=vim\n";
+        let block = create_block(". /clyde/activate.sh");
+
+        // AND the same script with a Clyde block added to it
+        let new_content = add_block(content, &block, Shell::Bash).unwrap();
+
+        // WHEN the block is removed
+        let result = remove_block(&new_content).unwrap();
+
+        // THEN the original content is restored
+        assert_eq!(result, Some(content.to_string()));
+
+        // AND removing the block again does nothing
+        assert_eq!(remove_block(content).unwrap(), None);
+    }
+
+    #[test]
+    fn find_block_should_fail_if_block_is_not_terminated() {
+        let content = format!("a\n{BLOCK_START}\n. /clyde/activate.sh\n");
+        assert!(remove_block(&content).is_err());
+    }
+}
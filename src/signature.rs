@@ -0,0 +1,302 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use minisign_verify::{Error as MinisignError, PublicKey, Signature};
+use tempfile::TempDir;
+
+use crate::package::SigningConfig;
+
+/// Verify `asset_path` has been signed by the key defined in `config`. `signature_path` is the
+/// path to the signature file.
+pub fn verify_signature(
+    config: &SigningConfig,
+    asset_path: &Path,
+    signature_path: &Path,
+) -> Result<()> {
+    match config {
+        SigningConfig::Minisign { public_key } => {
+            verify_minisign_signature(public_key, asset_path, signature_path)
+        }
+        SigningConfig::Gpg { public_key } => {
+            verify_gpg_signature(public_key, asset_path, signature_path)
+        }
+        SigningConfig::Cosign {
+            public_key,
+            identity,
+            oidc_issuer,
+        } => {
+            let temp_dir = TempDir::new()?;
+            let mut command = create_cosign_command(
+                temp_dir.path(),
+                public_key,
+                identity,
+                oidc_issuer,
+                asset_path,
+                signature_path,
+            )?;
+            run_verifier(&mut command, "cosign")
+        }
+    }
+}
+
+fn verify_minisign_signature(
+    public_key: &str,
+    asset_path: &Path,
+    signature_path: &Path,
+) -> Result<()> {
+    let public_key = PublicKey::from_base64(public_key)
+        .map_err(|x| anyhow!("Invalid minisign public key: {x}"))?;
+    let signature = Signature::from_file(signature_path)
+        .map_err(|x| anyhow!("Invalid minisign signature {signature_path:?}: {x}"))?;
+
+    let result = match public_key.verify_stream(&signature) {
+        Ok(mut verifier) => {
+            let mut file = File::open(asset_path)?;
+            let mut buffer = [0; 64 * 1024];
+            loop {
+                let count = match file.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(x) => x,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err.into()),
+                };
+                verifier.update(&buffer[..count]);
+            }
+            verifier.finalize()
+        }
+        Err(MinisignError::UnsupportedLegacyMode) => {
+            // Legacy signatures are not prehashed, so they cannot be verified in a streaming way
+            let content = fs::read(asset_path)?;
+            public_key.verify(&content, &signature, true /* allow_legacy */)
+        }
+        Err(x) => Err(x),
+    };
+    result.map_err(|x| anyhow!("Minisign signature verification failed: {x}"))
+}
+
+fn verify_gpg_signature(public_key: &str, asset_path: &Path, signature_path: &Path) -> Result<()> {
+    // Use a temporary GPG home, so that only the package key is trusted and the user keyring is
+    // left untouched
+    let gpg_home = TempDir::new()?;
+    let key_path = gpg_home.path().join("public_key.asc");
+    fs::write(&key_path, public_key)?;
+
+    let mut import_command = Command::new("gpg");
+    import_command
+        .arg("--batch")
+        .arg("--homedir")
+        .arg(gpg_home.path())
+        .arg("--import")
+        .arg(&key_path);
+    run_verifier(&mut import_command, "gpg").context("Failed to import package GPG public key")?;
+
+    let mut verify_command = Command::new("gpg");
+    verify_command
+        .arg("--batch")
+        .arg("--homedir")
+        .arg(gpg_home.path())
+        .arg("--verify")
+        .arg(signature_path)
+        .arg(asset_path);
+    run_verifier(&mut verify_command, "gpg")
+}
+
+fn create_cosign_command(
+    temp_dir: &Path,
+    public_key: &Option<String>,
+    identity: &Option<String>,
+    oidc_issuer: &Option<String>,
+    asset_path: &Path,
+    signature_path: &Path,
+) -> Result<Command> {
+    let mut command = Command::new("cosign");
+    command.arg("verify-blob");
+
+    match (public_key, identity, oidc_issuer) {
+        (Some(public_key), None, None) => {
+            let key_path = temp_dir.join("public_key.pem");
+            fs::write(&key_path, public_key)?;
+            command.arg("--key").arg(key_path);
+        }
+        (None, Some(identity), Some(oidc_issuer)) => {
+            command
+                .arg("--certificate-identity")
+                .arg(identity)
+                .arg("--certificate-oidc-issuer")
+                .arg(oidc_issuer);
+        }
+        _ => {
+            return Err(anyhow!(
+                "Cosign signing must define either `public_key`, or `identity` and `oidc_issuer`"
+            ));
+        }
+    }
+
+    if signature_path.extension() == Some("bundle".as_ref()) {
+        command.arg("--bundle");
+    } else {
+        command.arg("--signature");
+    }
+    command.arg(signature_path);
+    command.arg(asset_path);
+    Ok(command)
+}
+
+/// Run a verifier command, return an error containing its output if it fails
+fn run_verifier(command: &mut Command, name: &str) -> Result<()> {
+    let output = match command.output() {
+        Ok(x) => x,
+        Err(err) => {
+            if err.kind() == io::ErrorKind::NotFound {
+                return Err(anyhow!("Can't verify signature: {name} is not installed"));
+            } else {
+                return Err(err.into());
+            }
+        }
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!(
+            "{name} signature verification failed:\n{}",
+            stderr.trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use which::which;
+
+    use crate::test_file_utils::get_fixture_path;
+
+    const MINISIGN_PUBLIC_KEY: &str = "RWQSNFZ4mrze8AOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4";
+
+    fn minisign_config() -> SigningConfig {
+        SigningConfig::Minisign {
+            public_key: MINISIGN_PUBLIC_KEY.to_string(),
+        }
+    }
+
+    #[test]
+    fn verify_signature_should_accept_valid_minisign_signature() {
+        let result = verify_signature(
+            &minisign_config(),
+            &get_fixture_path("signed_file.txt"),
+            &get_fixture_path("signed_file.txt.minisig"),
+        );
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn verify_signature_should_reject_modified_file() {
+        // GIVEN a modified copy of a signed file
+        let dir = assert_fs::TempDir::new().unwrap();
+        let asset_path = dir.join("signed_file.txt");
+        fs::write(&asset_path, "Not the signed content\n").unwrap();
+
+        // WHEN verifying it with the signature of the original file
+        let result = verify_signature(
+            &minisign_config(),
+            &asset_path,
+            &get_fixture_path("signed_file.txt.minisig"),
+        );
+
+        // THEN it fails
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn verify_signature_should_reject_signature_from_another_key() {
+        // Same key ID as MINISIGN_PUBLIC_KEY, but a different key
+        let config = SigningConfig::Minisign {
+            public_key: "RWQSNFZ4mrze8Hm1Vi6P5lT5QHixEuipi6eQH4U65pW+1+DjkQutBJZk".to_string(),
+        };
+        let result = verify_signature(
+            &config,
+            &get_fixture_path("signed_file.txt"),
+            &get_fixture_path("signed_file.txt.minisig"),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn verify_signature_should_accept_valid_gpg_signature() {
+        if which("gpg").is_err() {
+            // Skip test, can't be run in this environment
+            println!("Skipped");
+            return;
+        }
+        let public_key = fs::read_to_string(get_fixture_path("gpg_public_key.asc")).unwrap();
+        let config = SigningConfig::Gpg { public_key };
+
+        let result = verify_signature(
+            &config,
+            &get_fixture_path("signed_file.txt"),
+            &get_fixture_path("signed_file.txt.asc"),
+        );
+        assert!(result.is_ok(), "{:?}", result);
+
+        // AND it rejects a file which does not match the signature
+        let result = verify_signature(
+            &config,
+            &get_fixture_path("gpg_public_key.asc"),
+            &get_fixture_path("signed_file.txt.asc"),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn create_cosign_command_should_use_bundle_for_keyless_signatures() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let command = create_cosign_command(
+            &dir,
+            &None,
+            &Some(
+                "https://github.com/foo/foo/.github/workflows/release.yml@refs/tags/v1.0.0".into(),
+            ),
+            &Some("https://token.actions.githubusercontent.com".into()),
+            Path::new("foo.tar.gz"),
+            Path::new("foo.tar.gz.bundle"),
+        )
+        .unwrap();
+
+        let args: Vec<_> = command.get_args().map(|x| x.to_str().unwrap()).collect();
+        assert_eq!(
+            args,
+            &[
+                "verify-blob",
+                "--certificate-identity",
+                "https://github.com/foo/foo/.github/workflows/release.yml@refs/tags/v1.0.0",
+                "--certificate-oidc-issuer",
+                "https://token.actions.githubusercontent.com",
+                "--bundle",
+                "foo.tar.gz.bundle",
+                "foo.tar.gz",
+            ]
+        );
+    }
+
+    #[test]
+    fn create_cosign_command_should_fail_without_key_or_identity() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let result = create_cosign_command(
+            &dir,
+            &None,
+            &None,
+            &None,
+            Path::new("foo.tar.gz"),
+            Path::new("foo.tar.gz.sig"),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -7,8 +7,10 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::UNIX_EPOCH;
 
-use anyhow::{anyhow, Error, Result};
+use anyhow::{anyhow, Context, Error, Result};
+use sha2::{Digest, Sha256};
 
 use crate::file_utils;
 use crate::package::Package;
@@ -28,6 +30,11 @@ pub trait Store {
     fn update(&self) -> Result<()>;
     fn get_package(&self, name: &str) -> Result<Package>;
     fn search(&self, query: &str) -> Result<(Vec<SearchHit>, Vec<Error>)>;
+    /// Returns all the packages of the store, and the errors of the packages which could not be
+    /// parsed
+    fn list_packages(&self) -> Result<(Vec<Package>, Vec<Error>)>;
+    /// Returns an identifier of the current state of the store, which changes when it's updated
+    fn get_revision(&self) -> Result<String>;
 }
 
 pub struct GitStore {
@@ -121,6 +128,8 @@ impl Store for GitStore {
         let mut cmd = Command::new("git");
         cmd.args(["clone", "--depth", "1", url]);
         cmd.arg(self.dir.as_os_str());
+        // Keep stdout for machine-readable output
+        cmd.stdout(io::stderr());
 
         let status = match cmd.status() {
             Ok(x) => x,
@@ -143,6 +152,7 @@ impl Store for GitStore {
         cmd.arg("-C");
         cmd.arg(self.dir.as_os_str());
         cmd.arg("pull");
+        cmd.stdout(io::stderr());
         let status = cmd.status()?;
         if !status.success() {
             return Err(anyhow!("Failed to update"));
@@ -163,40 +173,105 @@ impl Store for GitStore {
         let mut name_hits = Vec::<SearchHit>::new();
         let mut description_hits = Vec::<SearchHit>::new();
 
-        let mut errors = Vec::<Error>::new();
-
         // This implementation is very inefficient, but it's good enough for now given the number
         // of available packages. It should be revisited when the number of packages grow.
         // A possible solution is to create a database table to store the name and description of
         // available packages.
-        let packages_dir = self.packages_dir();
-        if packages_dir.exists() {
-            search_in_dir(
-                &packages_dir,
-                &query,
-                &mut name_hits,
-                &mut description_hits,
-                &mut errors,
-            )?;
+        let (packages, errors) = self.list_packages()?;
+        for package in packages {
+            if package.name.to_lowercase().contains(&query) {
+                name_hits.push(SearchHit::from_package(&package));
+            } else if package.description.to_lowercase().contains(&query) {
+                description_hits.push(SearchHit::from_package(&package));
+            }
         }
-        search_in_dir(
-            &self.dir,
-            &query,
-            &mut name_hits,
-            &mut description_hits,
-            &mut errors,
-        )?;
 
         name_hits.extend_from_slice(&description_hits);
         Ok((name_hits, errors))
     }
+
+    fn list_packages(&self) -> Result<(Vec<Package>, Vec<Error>)> {
+        let mut packages = Vec::<Package>::new();
+        let mut errors = Vec::<Error>::new();
+        let packages_dir = self.packages_dir();
+        if packages_dir.exists() {
+            list_packages_in_dir(&packages_dir, &mut packages, &mut errors)?;
+        }
+        list_packages_in_dir(&self.dir, &mut packages, &mut errors)?;
+        let packages = packages
+            .into_iter()
+            .map(|x| x.enforce_cooldown_days(self.cooldown_days))
+            .collect();
+        Ok((packages, errors))
+    }
+
+    /// Uses the Git revision of the store. Uncommitted changes, and stores which are not Git
+    /// clones, are identified by the modification times and sizes of their files.
+    fn get_revision(&self) -> Result<String> {
+        let head = run_git(&self.dir, &["rev-parse", "HEAD"]);
+        let is_clean = head.is_some()
+            && run_git(&self.dir, &["status", "--porcelain"]).is_some_and(|x| x.is_empty());
+        match head {
+            Some(head) if is_clean => Ok(head),
+            Some(head) => Ok(format!("{head}+{}", compute_files_fingerprint(&self.dir)?)),
+            None => compute_files_fingerprint(&self.dir),
+        }
+    }
+}
+
+/// Runs git in `dir`, returns its trimmed output if it succeeded
+fn run_git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir.as_os_str())
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
+
+/// Returns a digest of the paths, modification times and sizes of the files in `dir`, ignoring
+/// the `.git` dir
+fn compute_files_fingerprint(dir: &Path) -> Result<String> {
+    let mut files = Vec::new();
+    list_files_for_fingerprint(dir, &mut files)
+        .with_context(|| format!("Failed to get the revision of the store {dir:?}"))?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for (path, mtime, size) in files {
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update(mtime.to_le_bytes());
+        hasher.update(size.to_le_bytes());
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn list_files_for_fingerprint(dir: &Path, files: &mut Vec<(PathBuf, u128, u64)>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            if entry.file_name() != ".git" {
+                list_files_for_fingerprint(&path, files)?;
+            }
+            continue;
+        }
+        let mtime = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_nanos());
+        files.push((path, mtime, metadata.len()));
+    }
+    Ok(())
 }
 
-fn search_in_dir(
+fn list_packages_in_dir(
     dir: &Path,
-    query: &str,
-    name_hits: &mut Vec<SearchHit>,
-    description_hits: &mut Vec<SearchHit>,
+    packages: &mut Vec<Package>,
     errors: &mut Vec<Error>,
 ) -> Result<()> {
     for entry in fs::read_dir(dir)? {
@@ -206,20 +281,13 @@ fn search_in_dir(
             None => continue,
         };
 
-        let package = match Package::from_file(&path) {
-            Ok(x) => x,
+        match Package::from_file(&path) {
+            Ok(x) => packages.push(x),
             Err(x) => {
                 let x = x.context(format!("Failed to parse {}", &path.display()));
                 errors.push(x);
-                continue;
             }
         };
-
-        if package.name.to_lowercase().contains(query) {
-            name_hits.push(SearchHit::from_package(&package));
-        } else if package.description.to_lowercase().contains(query) {
-            description_hits.push(SearchHit::from_package(&package));
-        }
     }
     Ok(())
 }
@@ -230,8 +298,8 @@ mod tests {
 
     use std::vec::Vec;
 
+    use crate::version::Version;
     use chrono::{TimeDelta, Utc};
-    use semver::Version;
     use yare::parameterized;
 
     use crate::test_file_utils::{create_tree, CwdSaver};
@@ -409,13 +477,13 @@ mod tests {
                     assets:
                       any:
                         url: https://example.com/foo
-                        sha256: '1234'
+                        sha256: '1234123412341234123412341234123412341234123412341234123412341234'
                   2.0.0:
                     added_at: {}
                     assets:
                       any:
                         url: https://example.com/foo
-                        sha256: '1234'
+                        sha256: '1234123412341234123412341234123412341234123412341234123412341234'
                 installs: {{}}
             ",
                 v1_added_at, v2_added_at
@@ -430,4 +498,50 @@ mod tests {
         let versions: Vec<Version> = package.releases.keys().cloned().collect();
         assert_eq!(versions, &[Version::new(1, 0, 0)]);
     }
+
+    #[test]
+    fn get_revision_should_change_when_a_store_which_is_not_a_git_clone_changes() {
+        // GIVEN a store which is not a Git clone
+        let dir = assert_fs::TempDir::new().unwrap();
+        let store = GitStore::new(&dir);
+        create_package_file(&dir, "foo");
+        let revision = store.get_revision().unwrap();
+
+        // WHEN a package file is modified
+        create_package_file_with_desc(&dir, "bar", "The new bar package");
+
+        // THEN the revision changes
+        assert_ne!(store.get_revision().unwrap(), revision);
+    }
+
+    #[test]
+    fn get_revision_should_change_when_a_git_store_has_uncommitted_changes() {
+        // GIVEN a store which is a Git clone
+        let dir = assert_fs::TempDir::new().unwrap();
+        let store = GitStore::new(&dir);
+        create_package_file(&dir, "foo");
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        let revision = store.get_revision().unwrap();
+
+        // WHEN a package file is modified without committing it
+        create_package_file_with_content(&dir, "bar", "name: bar");
+
+        // THEN the revision changes
+        let dirty_revision = store.get_revision().unwrap();
+        assert_ne!(dirty_revision, revision);
+        assert!(dirty_revision.starts_with(&revision));
+    }
 }
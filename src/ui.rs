@@ -3,19 +3,90 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use console::{style, StyledObject};
+use serde::Serialize;
+use serde_json::Value;
 
-#[derive(Default)]
+pub use crate::cli::OutputFormat;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// The events emitted when the output format is JSON. Each event is printed on stdout as a
+/// single-line JSON object, whose `event` key contains the event type.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A human-readable message, emitted for all the messages printed in text mode
+    Message {
+        level: MessageLevel,
+        message: String,
+    },
+    /// The version and asset to install for a package have been found
+    Resolved {
+        package: String,
+        version: String,
+        arch_os: String,
+    },
+    /// Download progress
+    Downloading {
+        name: String,
+        downloaded: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<u64>,
+    },
+    /// An asset is being verified. `method` is either "checksum" or "signature"
+    Verifying {
+        package: String,
+        method: String,
+    },
+    Installed {
+        package: String,
+        version: String,
+        files: Vec<String>,
+    },
+    Uninstalled {
+        package: String,
+        version: String,
+    },
+    /// The output of commands producing data, like `show` or `list`
+    Data {
+        data: Value,
+    },
+    /// Always the last event: tells whether the command succeeded
+    Result {
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+#[derive(Clone, Default)]
 pub struct Ui {
     level: u32,
+    format: OutputFormat,
 }
 
 impl Ui {
+    pub fn new(format: OutputFormat) -> Self {
+        Ui { level: 0, format }
+    }
+
     pub fn nest(&self) -> Self {
         Ui {
             level: self.level + 1,
+            format: self.format,
         }
     }
 
+    pub fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
     pub fn print_indent(&self) {
         for _ in 0..self.level {
             eprint!("  ");
@@ -27,24 +98,100 @@ impl Ui {
     }
 
     pub fn println(&self, msg: &str) {
+        if self.is_json() {
+            self.emit_message(MessageLevel::Info, msg);
+            return;
+        }
         self.print_indent();
         eprintln!("{msg}");
     }
 
+    pub fn success(&self, msg: &str) {
+        if self.is_json() {
+            self.emit_message(MessageLevel::Info, msg);
+            return;
+        }
+        self.print_with_indicator(style("[✓]").green(), msg);
+    }
+
     pub fn info(&self, msg: &str) {
+        if self.is_json() {
+            self.emit_message(MessageLevel::Info, msg);
+            return;
+        }
         self.print_with_indicator(style("[I]").blue(), msg);
     }
 
     pub fn warn(&self, msg: &str) {
+        if self.is_json() {
+            self.emit_message(MessageLevel::Warning, msg);
+            return;
+        }
         self.print_with_indicator(style("[!]").yellow(), msg);
     }
 
     pub fn error(&self, msg: &str) {
+        if self.is_json() {
+            self.emit_message(MessageLevel::Error, msg);
+            return;
+        }
         self.print_with_indicator(style("[X]").red(), msg);
     }
 
+    /// Emit a structured event. Does nothing in text mode: commands print human-readable
+    /// messages instead.
+    pub fn emit(&self, event: Event) {
+        if self.is_json() {
+            println!("{}", event_to_json(&event));
+        }
+    }
+
+    fn emit_message(&self, level: MessageLevel, msg: &str) {
+        self.emit(Event::Message {
+            level,
+            message: msg.to_string(),
+        });
+    }
+
     fn print_with_indicator(&self, indicator: StyledObject<&str>, msg: &str) {
         self.print_indent();
         eprintln!("{indicator} {msg}");
     }
 }
+
+fn event_to_json(event: &Event) -> String {
+    serde_json::to_string(event).expect("Events should always be serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn events_should_be_tagged_with_their_type() {
+        let event = Event::Message {
+            level: MessageLevel::Warning,
+            message: "Hello".to_string(),
+        };
+        assert_eq!(
+            event_to_json(&event),
+            r#"{"event":"message","level":"warning","message":"Hello"}"#
+        );
+
+        let event = Event::Result {
+            success: true,
+            error: None,
+        };
+        assert_eq!(
+            event_to_json(&event),
+            r#"{"event":"result","success":true}"#
+        );
+
+        let event = Event::Data {
+            data: json!(["foo"]),
+        };
+        assert_eq!(event_to_json(&event), r#"{"event":"data","data":["foo"]}"#);
+    }
+}
@@ -0,0 +1,189 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::unpacker::squashfs::SquashFs;
+use crate::unpacker::unpack_guard::UnpackGuard;
+use crate::unpacker::{UnpackOptions, Unpacker};
+
+/// AppImages start with an ELF executable containing these bytes at this offset
+pub const APPIMAGE_MAGIC_OFFSET: usize = 8;
+pub const APPIMAGE_TYPE2_MAGIC: &[u8] = b"AI\x02";
+
+const ELF_CLASS_64: u8 = 2;
+const ELF_DATA_LITTLE_ENDIAN: u8 = 1;
+
+/// An unpacker for type 2 AppImages. Extracts the content of the squashfs image appended to the
+/// AppImage runtime, without running the AppImage.
+pub struct AppImageUnpacker {
+    archive_path: PathBuf,
+}
+
+impl AppImageUnpacker {
+    pub fn new(archive: &Path) -> AppImageUnpacker {
+        AppImageUnpacker {
+            archive_path: archive.to_path_buf(),
+        }
+    }
+}
+
+/// Returns the size of the ELF runtime at the beginning of `header`, which is the offset of the
+/// squashfs image. The runtime ends with its section header table.
+fn get_runtime_size(header: &[u8]) -> Result<u64> {
+    let little_endian = header[5] == ELF_DATA_LITTLE_ENDIAN;
+    let read_u16 = |pos: usize| {
+        let bytes = header[pos..pos + 2].try_into().unwrap();
+        if little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        }
+    };
+    let read_u32 = |pos: usize| {
+        let bytes = header[pos..pos + 4].try_into().unwrap();
+        if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        }
+    };
+    let read_u64 = |pos: usize| {
+        let bytes = header[pos..pos + 8].try_into().unwrap();
+        if little_endian {
+            u64::from_le_bytes(bytes)
+        } else {
+            u64::from_be_bytes(bytes)
+        }
+    };
+
+    let (section_header_offset, entry_size, entry_count) = if header[4] == ELF_CLASS_64 {
+        (read_u64(0x28), read_u16(0x3a), read_u16(0x3c))
+    } else {
+        (read_u32(0x20) as u64, read_u16(0x2e), read_u16(0x30))
+    };
+    section_header_offset
+        .checked_add(entry_size as u64 * entry_count as u64)
+        .ok_or_else(|| anyhow!("Invalid ELF header"))
+}
+
+impl Unpacker for AppImageUnpacker {
+    fn unpack(&self, dst_dir: &Path, options: &UnpackOptions) -> Result<Option<String>> {
+        let mut header = [0u8; 64];
+        File::open(&self.archive_path)
+            .and_then(|mut file| file.read_exact(&mut header))
+            .with_context(|| format!("Failed to read {:?}", self.archive_path))?;
+        let magic_end = APPIMAGE_MAGIC_OFFSET + APPIMAGE_TYPE2_MAGIC.len();
+        if &header[APPIMAGE_MAGIC_OFFSET..magic_end] != APPIMAGE_TYPE2_MAGIC {
+            return Err(anyhow!(
+                "{} is not a type 2 AppImage",
+                self.archive_path.display()
+            ));
+        }
+
+        let offset = get_runtime_size(&header)?;
+        let mut squashfs = SquashFs::open(&self.archive_path, offset)
+            .with_context(|| format!("Failed to read {:?}", self.archive_path))?;
+        let mut guard = UnpackGuard::new(dst_dir, options)?;
+        squashfs
+            .extract(&mut guard)
+            .with_context(|| format!("Failed to unpack {:?}", self.archive_path))?;
+        guard.finish()?;
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::unpacker::UnpackLimits;
+
+    use crate::test_file_utils::{get_fixture_path, list_tree, pathbufset_from_strings};
+
+    #[test]
+    fn unpack_should_extract_the_squashfs_image() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN an AppImage
+        let unpacker = AppImageUnpacker::new(&get_fixture_path("test_package.AppImage"));
+
+        // WHEN unpack() is called
+        unpacker.unpack(&dir, &UnpackOptions::default()).unwrap();
+
+        // THEN the content of the image is unpacked
+        assert_eq!(
+            list_tree(&dir).unwrap(),
+            pathbufset_from_strings(&["AppRun", "usr/bin/hello", "usr/share/doc/hello/README.md"])
+        );
+
+        // AND files stored in fragments and in blocks are complete
+        let readme = std::fs::read_to_string(dir.join("usr/share/doc/hello/README.md")).unwrap();
+        assert_eq!(readme.len(), 5000);
+        assert!(readme.starts_with("# Hello"));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("usr/bin/hello")).unwrap(),
+            "#!/bin/sh\necho hello\n"
+        );
+
+        #[cfg(unix)]
+        {
+            // AND the executable keeps its permissions
+            use crate::test_file_utils::is_file_executable;
+            assert!(is_file_executable(&dir.join("usr/bin/hello")));
+
+            // AND symlinks are restored
+            let symlink_path = dir.join("AppRun");
+            assert!(symlink_path.is_symlink());
+            assert_eq!(
+                std::fs::read_link(symlink_path).unwrap(),
+                PathBuf::from("usr/bin/hello")
+            );
+        }
+    }
+
+    #[test]
+    fn unpack_should_honor_strip() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN an AppImage
+        let unpacker = AppImageUnpacker::new(&get_fixture_path("test_package.AppImage"));
+
+        // WHEN unpack() is called with strip = 1
+        unpacker
+            .unpack(&dir, &UnpackOptions::default().with_strip(1))
+            .unwrap();
+
+        // THEN the top-level directory is not there, and top-level files are skipped
+        assert_eq!(
+            list_tree(&dir).unwrap(),
+            pathbufset_from_strings(&["bin/hello", "share/doc/hello/README.md"])
+        );
+    }
+
+    #[test]
+    fn unpack_should_enforce_the_size_limit() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN an AppImage
+        let unpacker = AppImageUnpacker::new(&get_fixture_path("test_package.AppImage"));
+
+        // AND a size limit smaller than its content
+        let options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_size: 1000,
+            ..UnpackLimits::default()
+        });
+
+        // WHEN unpack() is called
+        let result = unpacker.unpack(&dir, &options);
+
+        // THEN it fails before writing the big file
+        assert!(result.is_err());
+        assert!(!dir.join("usr/share/doc/hello/README.md").exists());
+    }
+}
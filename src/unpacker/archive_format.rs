@@ -0,0 +1,369 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::unpacker::appimage_unpacker::{APPIMAGE_MAGIC_OFFSET, APPIMAGE_TYPE2_MAGIC};
+use crate::vendored::archiver_rs;
+
+/// Size of a tar header block, enough to find the magic bytes of all the formats we support
+const HEADER_SIZE: usize = 512;
+
+const TAR_MAGIC_OFFSET: usize = 257;
+const TAR_MAGIC: &[u8] = b"ustar";
+
+/// A .deb package is an ar archive whose first member is "debian-binary"
+const DEB_MAGIC: &[u8] = b"!<arch>\ndebian-binary";
+const RPM_MAGIC: &[u8] = &[0xed, 0xab, 0xee, 0xdb];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+    Lzip,
+}
+
+impl Compression {
+    /// Detects the compression from the magic bytes at the beginning of `header`
+    pub fn from_magic(header: &[u8]) -> Compression {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if header.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if header.starts_with(b"LZIP") {
+            Compression::Lzip
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Returns a reader on the decompressed content of `path`
+    pub fn open(&self, path: &Path) -> Result<Box<dyn Read>> {
+        self.decoder(File::open(path)?)
+    }
+
+    /// Returns a reader on the decompressed content of `reader`
+    pub fn decoder<'a, R: Read + 'a>(&self, reader: R) -> Result<Box<dyn Read + 'a>> {
+        let decoder: Box<dyn Read + 'a> = match self {
+            Compression::None => Box::new(reader),
+            Compression::Gzip => Box::new(archiver_rs::Gzip::new(reader)?),
+            Compression::Bzip2 => Box::new(archiver_rs::Bzip2::new(reader)?),
+            Compression::Xz => Box::new(archiver_rs::Xz::new(reader)?),
+            Compression::Zstd => Box::new(archiver_rs::Zstd::new(reader)?),
+            Compression::Lzip => Box::new(archiver_rs::Lzip::new(reader)?),
+        };
+        Ok(decoder)
+    }
+
+    /// Extensions used by files compressed this way
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Compression::None => &[],
+            Compression::Gzip => &["gz"],
+            Compression::Bzip2 => &["bz2"],
+            Compression::Xz => &["xz"],
+            Compression::Zstd => &["zst"],
+            Compression::Lzip => &["lz"],
+        }
+    }
+}
+
+/// Format of an asset, tells Clyde how to unpack it. Can be set in the `format` entry of an
+/// install, when Clyde cannot detect it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "7z")]
+    SevenZ,
+    #[serde(rename = "tar")]
+    Tar,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+    #[serde(rename = "tar.bz2")]
+    TarBz2,
+    #[serde(rename = "tar.xz")]
+    TarXz,
+    #[serde(rename = "tar.zst")]
+    TarZst,
+    #[serde(rename = "tar.lz")]
+    TarLz,
+    #[serde(rename = "gz")]
+    Gz,
+    #[serde(rename = "bz2")]
+    Bz2,
+    #[serde(rename = "xz")]
+    Xz,
+    #[serde(rename = "zst")]
+    Zst,
+    #[serde(rename = "lz")]
+    Lz,
+    #[serde(rename = "deb")]
+    Deb,
+    #[serde(rename = "rpm")]
+    Rpm,
+    #[serde(rename = "appimage")]
+    AppImage,
+    #[serde(rename = "exe")]
+    Exe,
+}
+
+impl ArchiveFormat {
+    fn tar_with(compression: Compression) -> ArchiveFormat {
+        match compression {
+            Compression::None => ArchiveFormat::Tar,
+            Compression::Gzip => ArchiveFormat::TarGz,
+            Compression::Bzip2 => ArchiveFormat::TarBz2,
+            Compression::Xz => ArchiveFormat::TarXz,
+            Compression::Zstd => ArchiveFormat::TarZst,
+            Compression::Lzip => ArchiveFormat::TarLz,
+        }
+    }
+
+    fn single_file_with(compression: Compression) -> Option<ArchiveFormat> {
+        match compression {
+            Compression::None => None,
+            Compression::Gzip => Some(ArchiveFormat::Gz),
+            Compression::Bzip2 => Some(ArchiveFormat::Bz2),
+            Compression::Xz => Some(ArchiveFormat::Xz),
+            Compression::Zstd => Some(ArchiveFormat::Zst),
+            Compression::Lzip => Some(ArchiveFormat::Lz),
+        }
+    }
+
+    /// Returns true if the format is a tar archive, compressed or not
+    pub fn is_tar(&self) -> bool {
+        matches!(
+            self,
+            ArchiveFormat::Tar
+                | ArchiveFormat::TarGz
+                | ArchiveFormat::TarBz2
+                | ArchiveFormat::TarXz
+                | ArchiveFormat::TarZst
+                | ArchiveFormat::TarLz
+        )
+    }
+
+    /// Returns the compression used by tar archives and single compressed files
+    pub fn compression(&self) -> Compression {
+        match self {
+            ArchiveFormat::TarGz | ArchiveFormat::Gz => Compression::Gzip,
+            ArchiveFormat::TarBz2 | ArchiveFormat::Bz2 => Compression::Bzip2,
+            ArchiveFormat::TarXz | ArchiveFormat::Xz => Compression::Xz,
+            ArchiveFormat::TarZst | ArchiveFormat::Zst => Compression::Zstd,
+            ArchiveFormat::TarLz | ArchiveFormat::Lz => Compression::Lzip,
+            ArchiveFormat::Zip
+            | ArchiveFormat::SevenZ
+            | ArchiveFormat::Tar
+            | ArchiveFormat::Deb
+            | ArchiveFormat::Rpm
+            | ArchiveFormat::AppImage
+            | ArchiveFormat::Exe => Compression::None,
+        }
+    }
+
+    /// Guesses the format from the extension of `name`. Cannot detect executables.
+    pub fn from_name(name: &str) -> Option<ArchiveFormat> {
+        const SUFFIXES: &[(&str, ArchiveFormat)] = &[
+            (".tar", ArchiveFormat::Tar),
+            (".tar.gz", ArchiveFormat::TarGz),
+            (".tgz", ArchiveFormat::TarGz),
+            (".tar.bz2", ArchiveFormat::TarBz2),
+            (".tbz2", ArchiveFormat::TarBz2),
+            (".tar.xz", ArchiveFormat::TarXz),
+            (".tar.zst", ArchiveFormat::TarZst),
+            (".tzst", ArchiveFormat::TarZst),
+            (".tar.lz", ArchiveFormat::TarLz),
+            (".zip", ArchiveFormat::Zip),
+            (".7z", ArchiveFormat::SevenZ),
+            (".deb", ArchiveFormat::Deb),
+            (".rpm", ArchiveFormat::Rpm),
+            (".appimage", ArchiveFormat::AppImage),
+            // Single-file suffixes must come after the tar ones
+            (".gz", ArchiveFormat::Gz),
+            (".bz2", ArchiveFormat::Bz2),
+            (".xz", ArchiveFormat::Xz),
+            (".zst", ArchiveFormat::Zst),
+            (".lz", ArchiveFormat::Lz),
+        ];
+        // AppImages are usually named "Foo.AppImage"
+        let name = name.to_lowercase();
+        SUFFIXES
+            .iter()
+            .find(|(suffix, _)| name.ends_with(suffix))
+            .map(|(_, format)| *format)
+    }
+
+    /// Detects the format of the archive at `path` from its content. `name` is used when the
+    /// content is not enough to tell: a tar archive with no "ustar" magic looks like a single
+    /// compressed file. Cannot detect executables.
+    pub fn detect(path: &Path, name: &str) -> Result<Option<ArchiveFormat>> {
+        let header = read_header(path, Compression::None)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            return Ok(Some(ArchiveFormat::Zip));
+        }
+        if header.starts_with(&[b'7', b'z', 0xbc, 0xaf, 0x27, 0x1c]) {
+            return Ok(Some(ArchiveFormat::SevenZ));
+        }
+        if is_tar_header(&header) {
+            return Ok(Some(ArchiveFormat::Tar));
+        }
+        if header.starts_with(DEB_MAGIC) {
+            return Ok(Some(ArchiveFormat::Deb));
+        }
+        if header.starts_with(RPM_MAGIC) {
+            return Ok(Some(ArchiveFormat::Rpm));
+        }
+        if header.get(APPIMAGE_MAGIC_OFFSET..APPIMAGE_MAGIC_OFFSET + APPIMAGE_TYPE2_MAGIC.len())
+            == Some(APPIMAGE_TYPE2_MAGIC)
+        {
+            return Ok(Some(ArchiveFormat::AppImage));
+        }
+
+        let compression = Compression::from_magic(&header);
+        if compression == Compression::None {
+            return Ok(None);
+        }
+        let uncompressed_header = read_header(path, compression)
+            .with_context(|| format!("Failed to decompress {}", path.display()))?;
+        let name_says_tar = ArchiveFormat::from_name(name)
+            .is_some_and(|x| x.is_tar() && x.compression() == compression);
+        if is_tar_header(&uncompressed_header) || name_says_tar {
+            Ok(Some(ArchiveFormat::tar_with(compression)))
+        } else {
+            Ok(ArchiveFormat::single_file_with(compression))
+        }
+    }
+}
+
+fn is_tar_header(header: &[u8]) -> bool {
+    header.get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + TAR_MAGIC.len()) == Some(TAR_MAGIC)
+}
+
+/// Reads the first bytes of the content of `path`, once decompressed using `compression`
+fn read_header(path: &Path, compression: Compression) -> Result<Vec<u8>> {
+    let reader = compression.open(path)?;
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    match reader.take(HEADER_SIZE as u64).read_to_end(&mut header) {
+        Ok(_) => Ok(header),
+        // Happens with small compressed files: the header is fine, it's just short
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(header),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use yare::parameterized;
+
+    use super::*;
+
+    use crate::test_file_utils::get_fixture_path;
+
+    #[parameterized(
+        tar = { "test_archive.tar", ArchiveFormat::Tar },
+        tar_gz = { "test_archive.tar.gz", ArchiveFormat::TarGz },
+        tgz = { "test_archive.tgz", ArchiveFormat::TarGz },
+        tar_bz2 = { "test_archive.tar.bz2", ArchiveFormat::TarBz2 },
+        tar_xz = { "test_archive.tar.xz", ArchiveFormat::TarXz },
+        tar_zst = { "test_archive.tar.zst", ArchiveFormat::TarZst },
+        tar_lz = { "test_archive.tar.lz", ArchiveFormat::TarLz },
+        zip = { "test_archive.zip", ArchiveFormat::Zip },
+        sevenz = { "test_archive.7z", ArchiveFormat::SevenZ },
+        gz = { "test_exe.gz", ArchiveFormat::Gz },
+        bz2 = { "test_exe.bz2", ArchiveFormat::Bz2 },
+        xz = { "test_exe.xz", ArchiveFormat::Xz },
+        zst = { "test_exe.zst", ArchiveFormat::Zst },
+        lz = { "test_exe.lz", ArchiveFormat::Lz },
+        deb = { "test_package.deb", ArchiveFormat::Deb },
+        rpm = { "test_package.rpm", ArchiveFormat::Rpm },
+        appimage = { "test_package.AppImage", ArchiveFormat::AppImage },
+    )]
+    fn detect_should_not_need_the_file_extension(fixture_name: &str, expected: ArchiveFormat) {
+        // GIVEN an archive stored without its extension
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.join("download");
+        fs::copy(get_fixture_path(fixture_name), &path).unwrap();
+
+        // WHEN detect() is called on it
+        let format = ArchiveFormat::detect(&path, "download").unwrap();
+
+        // THEN the format is detected from the content
+        assert_eq!(format, Some(expected));
+    }
+
+    #[test]
+    fn detect_should_ignore_misleading_extensions() {
+        // GIVEN a zip file named as a .tar.gz
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.join("foo.tar.gz");
+        fs::copy(get_fixture_path("test_archive.zip"), &path).unwrap();
+
+        // WHEN detect() is called on it
+        let format = ArchiveFormat::detect(&path, "foo.tar.gz").unwrap();
+
+        // THEN it is detected as a zip file
+        assert_eq!(format, Some(ArchiveFormat::Zip));
+    }
+
+    #[test]
+    fn detect_should_return_none_for_unknown_content() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.join("foo.txt");
+        fs::write(&path, "Hello").unwrap();
+
+        assert_eq!(ArchiveFormat::detect(&path, "foo.txt").unwrap(), None);
+    }
+
+    #[test]
+    fn from_name_should_not_confuse_tar_and_single_files() {
+        assert_eq!(
+            ArchiveFormat::from_name("foo.tar.gz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_name("foo.tar.zst"),
+            Some(ArchiveFormat::TarZst)
+        );
+        assert_eq!(ArchiveFormat::from_name("foo.gz"), Some(ArchiveFormat::Gz));
+        assert_eq!(ArchiveFormat::from_name("foo.lz"), Some(ArchiveFormat::Lz));
+        assert_eq!(ArchiveFormat::from_name("foo"), None);
+    }
+
+    #[test]
+    fn from_name_should_ignore_the_case_of_appimage_extensions() {
+        assert_eq!(
+            ArchiveFormat::from_name("Foo-1.0-x86_64.AppImage"),
+            Some(ArchiveFormat::AppImage)
+        );
+        assert_eq!(
+            ArchiveFormat::from_name("foo-1.0.appimage"),
+            Some(ArchiveFormat::AppImage)
+        );
+    }
+
+    #[test]
+    fn archive_format_should_use_file_extensions_in_yaml() {
+        let format: ArchiveFormat = serde_yaml::from_str("tar.gz").unwrap();
+        assert_eq!(format, ArchiveFormat::TarGz);
+        let format: ArchiveFormat = serde_yaml::from_str("7z").unwrap();
+        assert_eq!(format, ArchiveFormat::SevenZ);
+    }
+}
@@ -0,0 +1,108 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::unpacker::archive_format::ArchiveFormat;
+use crate::unpacker::tar_unpacker::unpack_tar;
+use crate::unpacker::unpack_guard::UnpackGuard;
+use crate::unpacker::{UnpackOptions, Unpacker};
+
+/// An unpacker for Debian packages. Only unpacks the files of the package, found in the
+/// `data.tar.*` member of the package. Maintainer scripts are ignored.
+pub struct DebUnpacker {
+    archive_path: PathBuf,
+}
+
+impl DebUnpacker {
+    pub fn new(archive: &Path) -> DebUnpacker {
+        DebUnpacker {
+            archive_path: archive.to_path_buf(),
+        }
+    }
+}
+
+impl Unpacker for DebUnpacker {
+    fn unpack(&self, dst_dir: &Path, options: &UnpackOptions) -> Result<Option<String>> {
+        let file = File::open(&self.archive_path)
+            .with_context(|| format!("Failed to open {:?}", self.archive_path))?;
+        let mut archive = ar::Archive::new(file);
+
+        while let Some(entry) = archive.next_entry() {
+            let entry = entry.with_context(|| format!("Failed to read {:?}", self.archive_path))?;
+            let name = String::from_utf8_lossy(entry.header().identifier()).to_string();
+            if !name.starts_with("data.tar") {
+                continue;
+            }
+            let format = ArchiveFormat::from_name(&name)
+                .filter(|x| x.is_tar())
+                .ok_or_else(|| anyhow!("Unsupported data member {name}"))?;
+            let reader = format.compression().decoder(entry)?;
+            let mut guard = UnpackGuard::new(dst_dir, options)?;
+            unpack_tar(reader, &mut guard).with_context(|| format!("Failed to unpack {name}"))?;
+            guard.finish()?;
+            return Ok(None);
+        }
+        Err(anyhow!(
+            "No data.tar member in {}",
+            self.archive_path.display()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::unpacker::UnpackLimits;
+
+    use crate::test_file_utils::{get_fixture_path, list_tree, pathbufset_from_strings};
+
+    #[test]
+    fn unpack_should_unpack_the_data_member() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN a .deb package
+        let unpacker = DebUnpacker::new(&get_fixture_path("test_package.deb"));
+
+        // WHEN unpack() is called
+        unpacker.unpack(&dir, &UnpackOptions::default()).unwrap();
+
+        // THEN the files of the package are unpacked
+        assert_eq!(
+            list_tree(&dir).unwrap(),
+            pathbufset_from_strings(&["usr/bin/hello", "usr/share/doc/hello/README.md"])
+        );
+
+        // AND the executable keeps its permissions
+        #[cfg(unix)]
+        {
+            use crate::test_file_utils::is_file_executable;
+            assert!(is_file_executable(&dir.join("usr/bin/hello")));
+        }
+    }
+
+    #[test]
+    fn unpack_should_enforce_the_size_limit() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN a .deb package
+        let unpacker = DebUnpacker::new(&get_fixture_path("test_package.deb"));
+
+        // AND a size limit smaller than its content
+        let options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_size: 8,
+            ..UnpackLimits::default()
+        });
+
+        // WHEN unpack() is called
+        let result = unpacker.unpack(&dir, &options);
+
+        // THEN it fails
+        assert!(result.is_err());
+    }
+}
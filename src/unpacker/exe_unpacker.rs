@@ -2,15 +2,16 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::fs::{self, File};
-use std::io::{self, Read, Seek};
+use std::fs::File;
+use std::io::{Read, Seek};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use goblin::{self, Hint};
 
 use crate::file_utils;
-use crate::unpacker::Unpacker;
+use crate::unpacker::unpack_guard::UnpackGuard;
+use crate::unpacker::{UnpackOptions, Unpacker};
 
 /// An "unpacker" for archives which are actually directly an executable
 pub struct ExeUnpacker {
@@ -24,7 +25,9 @@ impl ExeUnpacker {
         }
     }
 
-    pub fn supports(archive_path: &Path) -> bool {
+    /// Returns true if `archive_path` is a script or an executable. If `host_only` is true, only
+    /// executables which can run on the host are accepted.
+    pub fn supports(archive_path: &Path, host_only: bool) -> bool {
         let mut file = match File::open(archive_path) {
             Ok(x) => x,
             Err(_) => {
@@ -41,6 +44,13 @@ impl ExeUnpacker {
             }
         };
 
+        if !host_only {
+            return matches!(
+                hint,
+                Hint::Elf(_) | Hint::Mach(_) | Hint::MachFat(_) | Hint::PE
+            );
+        }
+
         #[cfg(all(unix, not(target_os = "macos")))]
         return matches!(hint, Hint::Elf(_));
 
@@ -64,19 +74,18 @@ fn is_script(file: &mut File) -> bool {
 }
 
 impl Unpacker for ExeUnpacker {
-    fn unpack(&self, dst_dir: &Path, _strip: u32) -> Result<Option<String>> {
+    fn unpack(&self, dst_dir: &Path, options: &UnpackOptions) -> Result<Option<String>> {
         let exe_file_name = self.archive_path.file_name().unwrap();
 
+        let mut guard = UnpackGuard::new(dst_dir, options)?;
         let dst_path = dst_dir.join(exe_file_name);
-        fs::create_dir_all(dst_path.parent().unwrap())?;
 
         let mut src_file = File::open(&self.archive_path)
             .with_context(|| format!("Error with {:?}", self.archive_path))?;
 
-        let mut dst_file =
-            File::create(&dst_path).with_context(|| format!("Can't create {dst_path:?}"))?;
+        let mut dst_file = guard.create_file(&dst_path)?;
 
-        io::copy(&mut src_file, &mut dst_file)?;
+        guard.copy(&mut src_file, &mut dst_file)?;
 
         #[cfg(unix)]
         file_utils::set_file_executable(&dst_path)?;
@@ -90,6 +99,8 @@ impl Unpacker for ExeUnpacker {
 mod tests {
     use super::*;
     use assert_fs::prelude::*;
+    use std::fs;
+    use std::io;
 
     use crate::test_file_utils::get_fixture_path;
 
@@ -110,7 +121,7 @@ mod tests {
     #[test]
     fn supports_should_accept_executable() {
         let exe_path = get_test_executable_path();
-        assert!(ExeUnpacker::supports(&exe_path));
+        assert!(ExeUnpacker::supports(&exe_path, true));
     }
 
     #[test]
@@ -119,13 +130,29 @@ mod tests {
         let script_path = temp_dir.child("script");
         fs::write(&script_path, "#!/bin/bash\necho 'hello'\n").unwrap();
 
-        assert!(ExeUnpacker::supports(&script_path));
+        assert!(ExeUnpacker::supports(&script_path, true));
     }
 
     #[test]
     fn supports_should_not_accept_zip_files() {
         let zip_path = get_fixture_path("test_archive.zip");
-        assert!(!ExeUnpacker::supports(&zip_path));
+        assert!(!ExeUnpacker::supports(&zip_path, true));
+        assert!(!ExeUnpacker::supports(&zip_path, false));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn supports_should_accept_foreign_executables_if_not_host_only() {
+        // GIVEN a file starting with a Windows executable header
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let exe_path = temp_dir.child("foo.exe");
+        let mut content = b"MZ".to_vec();
+        content.resize(64, 0);
+        fs::write(&exe_path, content).unwrap();
+
+        // THEN it is only supported if foreign executables are accepted
+        assert!(!ExeUnpacker::supports(&exe_path, true));
+        assert!(ExeUnpacker::supports(&exe_path, false));
     }
 
     #[test]
@@ -149,7 +176,9 @@ mod tests {
 
         // WHEN unpack() is called in a subdir of `dir`
         let dst_dir = dir.join("sub");
-        unpacker.unpack(&dst_dir, 0).unwrap();
+        unpacker
+            .unpack(&dst_dir, &UnpackOptions::default())
+            .unwrap();
 
         // THEN the executable is copied there
         let dst_path = dst_dir.join(exe_file_name);
@@ -7,41 +7,79 @@ use std::path::Path;
 
 use anyhow::{anyhow, Result};
 
+mod appimage_unpacker;
+mod archive_format;
+mod deb_unpacker;
 mod exe_unpacker;
+mod rpm_unpacker;
+mod sevenz_unpacker;
 mod single_file_unpacker;
+mod squashfs;
 mod tar_unpacker;
+mod unpack_guard;
+mod unpack_options;
 mod unpacker_utils;
 mod zip_unpacker;
 
+use appimage_unpacker::AppImageUnpacker;
+pub use archive_format::{ArchiveFormat, Compression};
+use deb_unpacker::DebUnpacker;
 use exe_unpacker::ExeUnpacker;
+use rpm_unpacker::RpmUnpacker;
+use sevenz_unpacker::SevenZUnpacker;
 use single_file_unpacker::SingleFileUnpacker;
 use tar_unpacker::TarUnpacker;
+pub use unpack_guard::SkippedHardLinksError;
+pub use unpack_options::{UnpackFilter, UnpackLimits, UnpackOptions};
 use zip_unpacker::ZipUnpacker;
 
 pub trait Unpacker {
-    /// Unpacks the archive in `dst_dir`
+    /// Unpacks the archive in `dst_dir`. Fails if the archive contains entries pointing outside
+    /// `dst_dir` or exceeds the limits of `options`.
     ///
     /// Returns the name of the unpacked asset if the archive was a single-file one
-    fn unpack(&self, dst_dir: &Path, strip: u32) -> Result<Option<String>>;
+    fn unpack(&self, dst_dir: &Path, options: &UnpackOptions) -> Result<Option<String>>;
 }
 
-pub fn get_unpacker(archive: &Path) -> Result<Box<dyn Unpacker>> {
+/// Returns the format of `archive`. The content of the archive is looked at first, its name is
+/// only used if the content is not enough. If `host_only` is false, executables which cannot run on
+/// the host are accepted too, this is used when installing packages for another arch-os.
+pub fn detect_format(archive: &Path, host_only: bool) -> Result<ArchiveFormat> {
     let name = archive
         .file_name()
         .ok_or_else(|| anyhow!("Can't find file name in {}", archive.display()))?
         .to_str()
         .ok_or_else(|| anyhow!("Invalid file name in {}", archive.display()))?;
-    if TarUnpacker::supports(name) {
-        return Ok(Box::new(TarUnpacker::new(archive)));
+    if let Some(format) = ArchiveFormat::detect(archive, name)? {
+        return Ok(format);
     }
-    if ZipUnpacker::supports(name) {
-        return Ok(Box::new(ZipUnpacker::new(archive)));
+    if ExeUnpacker::supports(archive, host_only) {
+        return Ok(ArchiveFormat::Exe);
     }
-    if ExeUnpacker::supports(archive) {
-        return Ok(Box::new(ExeUnpacker::new(archive)));
-    }
-    if SingleFileUnpacker::supports(name) {
-        return Ok(Box::new(SingleFileUnpacker::new(archive)));
-    }
-    Err(anyhow!("Unsupported format {}", archive.display()))
+    ArchiveFormat::from_name(name)
+        .ok_or_else(|| anyhow!("Unsupported format {}", archive.display()))
+}
+
+/// Returns the unpacker to use for `archive`. If `format` is None, it is detected using
+/// `detect_format()`.
+pub fn get_unpacker(
+    archive: &Path,
+    format: Option<ArchiveFormat>,
+    host_only: bool,
+) -> Result<Box<dyn Unpacker>> {
+    let format = match format {
+        Some(x) => x,
+        None => detect_format(archive, host_only)?,
+    };
+    let unpacker: Box<dyn Unpacker> = match format {
+        ArchiveFormat::Zip => Box::new(ZipUnpacker::new(archive)),
+        ArchiveFormat::SevenZ => Box::new(SevenZUnpacker::new(archive)),
+        ArchiveFormat::Deb => Box::new(DebUnpacker::new(archive)),
+        ArchiveFormat::Rpm => Box::new(RpmUnpacker::new(archive)),
+        ArchiveFormat::AppImage => Box::new(AppImageUnpacker::new(archive)),
+        ArchiveFormat::Exe => Box::new(ExeUnpacker::new(archive)),
+        x if x.is_tar() => Box::new(TarUnpacker::new(archive, x.compression())),
+        x => Box::new(SingleFileUnpacker::new(archive, x.compression())),
+    };
+    Ok(unpacker)
 }
@@ -0,0 +1,325 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+#[cfg(unix)]
+use std::fs;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::unpacker::archive_format::Compression;
+use crate::unpacker::unpack_guard::UnpackGuard;
+use crate::unpacker::{UnpackOptions, Unpacker};
+
+const LEAD_SIZE: usize = 96;
+const LEAD_MAGIC: &[u8] = &[0xed, 0xab, 0xee, 0xdb];
+const HEADER_MAGIC: &[u8] = &[0x8e, 0xad, 0xe8, 0x01];
+const HEADER_INDEX_ENTRY_SIZE: u64 = 16;
+
+const CPIO_HEADER_SIZE: usize = 110;
+const CPIO_TRAILER_NAME: &str = "TRAILER!!!";
+/// Maximum size of an entry name, including its terminating NUL, like PATH_MAX on Linux
+const CPIO_MAX_NAME_SIZE: u64 = 4096;
+
+const S_IFMT: u32 = 0o170000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFREG: u32 = 0o100000;
+const S_IFLNK: u32 = 0o120000;
+
+/// An unpacker for RPM packages. Only unpacks the files of the package, found in its cpio
+/// payload. Scripts are ignored.
+pub struct RpmUnpacker {
+    archive_path: PathBuf,
+}
+
+impl RpmUnpacker {
+    pub fn new(archive: &Path) -> RpmUnpacker {
+        RpmUnpacker {
+            archive_path: archive.to_path_buf(),
+        }
+    }
+}
+
+/// Skips an RPM header structure. If `align` is true, also skips the padding following it (the
+/// signature header is aligned on 8 bytes).
+fn skip_header<R: Read>(reader: &mut R, align: bool) -> Result<()> {
+    let mut intro = [0u8; 16];
+    reader.read_exact(&mut intro)?;
+    if &intro[..4] != HEADER_MAGIC {
+        return Err(anyhow!("Invalid RPM header"));
+    }
+    let index_count = u32::from_be_bytes(intro[8..12].try_into().unwrap()) as u64;
+    let data_size = u32::from_be_bytes(intro[12..16].try_into().unwrap()) as u64;
+    let mut size = index_count * HEADER_INDEX_ENTRY_SIZE + data_size;
+    if align {
+        size += (8 - size % 8) % 8;
+    }
+    io::copy(&mut reader.take(size), &mut io::sink())?;
+    Ok(())
+}
+
+fn skip_padding<R: Read>(reader: &mut R, size: u64) -> Result<()> {
+    let padding = (4 - size % 4) % 4;
+    io::copy(&mut reader.take(padding), &mut io::sink())?;
+    Ok(())
+}
+
+fn parse_hex_field(header: &[u8], index: usize) -> Result<u32> {
+    // Fields start after the 6 bytes of the magic and are 8 bytes long
+    let start = 6 + index * 8;
+    let field = std::str::from_utf8(&header[start..start + 8])?;
+    u32::from_str_radix(field, 16).map_err(|_| anyhow!("Invalid cpio field '{field}'"))
+}
+
+/// Unpacks a cpio archive in the "newc" format, as used by RPM payloads
+fn unpack_cpio<R: Read>(reader: &mut R, guard: &mut UnpackGuard) -> Result<()> {
+    loop {
+        let mut header = [0u8; CPIO_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        if &header[..6] != b"070701" && &header[..6] != b"070702" {
+            return Err(anyhow!("Unsupported cpio format"));
+        }
+        let mode = parse_hex_field(&header, 1)?;
+        let file_size = parse_hex_field(&header, 6)? as u64;
+        let name_size = parse_hex_field(&header, 11)? as u64;
+        if name_size > CPIO_MAX_NAME_SIZE {
+            return Err(anyhow!(
+                "cpio entry name is longer than {CPIO_MAX_NAME_SIZE} bytes"
+            ));
+        }
+
+        let mut name = vec![0u8; name_size as usize];
+        reader.read_exact(&mut name)?;
+        skip_padding(reader, CPIO_HEADER_SIZE as u64 + name_size)?;
+        let name = String::from_utf8_lossy(name.strip_suffix(&[0]).unwrap_or(&name)).to_string();
+        if name == CPIO_TRAILER_NAME {
+            return Ok(());
+        }
+
+        let mut data = reader.by_ref().take(file_size);
+        if let Some(dst_path) = guard.entry_path(Path::new(&name))? {
+            unpack_cpio_entry(guard, &mut data, mode, &dst_path)
+                .with_context(|| format!("Failed to unpack {name}"))?;
+        }
+        // Skip any data the entry did not read
+        io::copy(&mut data, &mut io::sink())?;
+        skip_padding(reader, file_size)?;
+    }
+}
+
+fn unpack_cpio_entry<R: Read>(
+    guard: &mut UnpackGuard,
+    data: &mut R,
+    mode: u32,
+    dst_path: &Path,
+) -> Result<()> {
+    match mode & S_IFMT {
+        S_IFDIR => {
+            guard.create_dir(dst_path)?;
+        }
+        S_IFREG => {
+            let mut dst_file = guard.create_file(dst_path)?;
+            guard.copy(data, &mut dst_file)?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(dst_path, fs::Permissions::from_mode(mode & 0o7777))?;
+            }
+        }
+        S_IFLNK => {
+            let target = guard.read_link_target(data)?;
+            guard.create_symlink(&target, dst_path)?;
+        }
+        // Device files, fifos and sockets have nothing to do in a package
+        _ => (),
+    }
+    Ok(())
+}
+
+impl Unpacker for RpmUnpacker {
+    fn unpack(&self, dst_dir: &Path, options: &UnpackOptions) -> Result<Option<String>> {
+        let file = File::open(&self.archive_path)
+            .with_context(|| format!("Failed to open {:?}", self.archive_path))?;
+        let mut reader = BufReader::new(file);
+
+        let mut lead = [0u8; LEAD_SIZE];
+        reader.read_exact(&mut lead)?;
+        if &lead[..4] != LEAD_MAGIC {
+            return Err(anyhow!(
+                "{} is not an RPM file",
+                self.archive_path.display()
+            ));
+        }
+        // Signature header, then main header
+        skip_header(&mut reader, true)?;
+        skip_header(&mut reader, false)?;
+
+        let compression = Compression::from_magic(reader.fill_buf()?);
+        let mut payload = compression.decoder(reader)?;
+        let mut guard = UnpackGuard::new(dst_dir, options)?;
+        unpack_cpio(&mut payload, &mut guard)
+            .with_context(|| format!("Failed to unpack {}", self.archive_path.display()))?;
+        guard.finish()?;
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_file_utils::{get_fixture_path, list_tree, pathbufset_from_strings};
+    use crate::unpacker::unpack_guard::MAX_LINK_TARGET_SIZE;
+
+    #[test]
+    fn unpack_should_unpack_the_payload() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN an .rpm package
+        let unpacker = RpmUnpacker::new(&get_fixture_path("test_package.rpm"));
+
+        // WHEN unpack() is called
+        unpacker.unpack(&dir, &UnpackOptions::default()).unwrap();
+
+        // THEN the files of the package are unpacked
+        assert_eq!(
+            list_tree(&dir).unwrap(),
+            pathbufset_from_strings(&[
+                "usr/bin/hello",
+                "usr/bin/hello-symlink",
+                "usr/share/doc/hello/README.md"
+            ])
+        );
+
+        #[cfg(unix)]
+        {
+            // AND the executable keeps its permissions
+            use crate::test_file_utils::is_file_executable;
+            let exe_path = dir.join("usr/bin/hello");
+            assert!(is_file_executable(&exe_path));
+
+            // AND symlinks are restored
+            let symlink_path = dir.join("usr/bin/hello-symlink");
+            assert!(symlink_path.is_symlink());
+            assert_eq!(
+                symlink_path.canonicalize().unwrap(),
+                exe_path.canonicalize().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn unpack_should_honor_strip() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN an .rpm package
+        let unpacker = RpmUnpacker::new(&get_fixture_path("test_package.rpm"));
+
+        // WHEN unpack() is called with strip = 2
+        unpacker
+            .unpack(&dir, &UnpackOptions::default().with_strip(2))
+            .unwrap();
+
+        // THEN the `.` and `usr` components are not there
+        assert_eq!(
+            list_tree(&dir).unwrap(),
+            pathbufset_from_strings(&[
+                "bin/hello",
+                "bin/hello-symlink",
+                "share/doc/hello/README.md"
+            ])
+        );
+    }
+
+    /// Returns a cpio entry in the "newc" format
+    fn create_cpio_entry(name: &str, mode: u32, data: &[u8]) -> Vec<u8> {
+        let name_size = name.len() + 1;
+        let mut entry = format!(
+            "070701{:08x}{mode:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{name_size:08x}{:08x}",
+            1, 0, 0, 1, 0, data.len(), 0, 0, 0, 0, 0
+        )
+        .into_bytes();
+        entry.extend(name.as_bytes());
+        entry.push(0);
+        entry.resize(entry.len().next_multiple_of(4), 0);
+        entry.extend(data);
+        entry.resize(entry.len().next_multiple_of(4), 0);
+        entry
+    }
+
+    fn unpack_crafted_cpio(entries: &[(&str, u32, &[u8])]) -> (assert_fs::TempDir, Result<()>) {
+        let mut cpio = vec![];
+        for (name, mode, data) in entries {
+            cpio.extend(create_cpio_entry(name, *mode, data));
+        }
+        cpio.extend(create_cpio_entry(CPIO_TRAILER_NAME, 0, b""));
+
+        let dir = assert_fs::TempDir::new().unwrap();
+        let dst_dir = dir.join("dst");
+        let options = UnpackOptions::default();
+        let mut guard = UnpackGuard::new(&dst_dir, &options).unwrap();
+        let result = unpack_cpio(&mut &cpio[..], &mut guard).and_then(|_| guard.finish());
+        (dir, result)
+    }
+
+    #[test]
+    fn unpack_cpio_should_reject_paths_going_outside() {
+        // GIVEN a cpio archive containing a file outside of the unpack dir
+        // WHEN it is unpacked
+        let (dir, result) = unpack_crafted_cpio(&[("./../evil", S_IFREG | 0o644, b"evil")]);
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!dir.join("evil").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unpack_cpio_should_reject_symlinks_going_outside() {
+        // GIVEN a cpio archive containing a symlink pointing outside of the unpack dir, followed
+        // by a file written through it
+        // WHEN it is unpacked
+        let (dir, result) = unpack_crafted_cpio(&[
+            ("./link", S_IFLNK | 0o777, b".."),
+            ("./link/evil", S_IFREG | 0o644, b"evil"),
+        ]);
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!dir.join("evil").exists());
+    }
+
+    #[test]
+    fn unpack_cpio_should_reject_too_long_names() {
+        // GIVEN a cpio archive whose first entry claims to have a 4 GB name
+        let mut cpio = create_cpio_entry("./foo", S_IFREG | 0o644, b"foo");
+        let name_size_start = 6 + 11 * 8;
+        cpio[name_size_start..name_size_start + 8].copy_from_slice(b"ffffffff");
+
+        // WHEN it is unpacked
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default();
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+        let result = unpack_cpio(&mut &cpio[..], &mut guard);
+
+        // THEN it fails without trying to read the name
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("name is longer"), "{err}");
+    }
+
+    #[test]
+    fn unpack_cpio_should_reject_too_long_symlink_targets() {
+        // GIVEN a cpio archive containing a symlink with a too long target
+        let target = vec![b'a'; MAX_LINK_TARGET_SIZE as usize + 1];
+
+        // WHEN it is unpacked
+        let (dir, result) = unpack_crafted_cpio(&[("./link", S_IFLNK | 0o777, &target)]);
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!dir.join("dst/link").is_symlink());
+    }
+}
@@ -0,0 +1,263 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use sevenz_rust::{Password, SevenZArchiveEntry, SevenZReader};
+
+use crate::unpacker::unpack_guard::UnpackGuard;
+use crate::unpacker::{UnpackOptions, Unpacker};
+
+/// Set in the Windows attributes of an entry when its high 16 bits contain Unix mode bits
+#[cfg(unix)]
+const UNIX_EXTENSION_ATTRIBUTE: u32 = 0x8000;
+
+pub struct SevenZUnpacker {
+    archive_path: PathBuf,
+}
+
+impl SevenZUnpacker {
+    pub fn new(archive: &Path) -> SevenZUnpacker {
+        SevenZUnpacker {
+            archive_path: archive.to_path_buf(),
+        }
+    }
+}
+
+/// Returns the Unix mode of `entry`, if the archive stored it
+#[cfg(unix)]
+fn get_unix_mode(entry: &SevenZArchiveEntry) -> Option<u32> {
+    if entry.has_windows_attributes && entry.windows_attributes & UNIX_EXTENSION_ATTRIBUTE != 0 {
+        Some(entry.windows_attributes >> 16)
+    } else {
+        None
+    }
+}
+
+fn unpack_entry(
+    guard: &mut UnpackGuard,
+    entry: &SevenZArchiveEntry,
+    reader: &mut dyn io::Read,
+    dst_path: &Path,
+) -> Result<()> {
+    if entry.is_directory() {
+        return guard.create_dir(dst_path);
+    }
+
+    #[cfg(unix)]
+    {
+        const S_IFMT: u32 = 0o170000;
+        const S_IFLNK: u32 = 0o120000;
+
+        if get_unix_mode(entry).is_some_and(|mode| mode & S_IFMT == S_IFLNK) {
+            // Symlinks are stored as files containing the link target
+            let target = guard.read_link_target(reader)?;
+            return guard.create_symlink(&target, dst_path);
+        }
+    }
+
+    let mut dst_file = guard.create_file(dst_path)?;
+    guard
+        .copy(reader, &mut dst_file)
+        .with_context(|| format!("Failed to write {:?} to {dst_path:?}", entry.name()))?;
+
+    #[cfg(unix)]
+    if let Some(mode) = get_unix_mode(entry) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dst_path, fs::Permissions::from_mode(mode & 0o7777))?;
+    }
+    Ok(())
+}
+
+impl Unpacker for SevenZUnpacker {
+    fn unpack(&self, dst_dir: &Path, options: &UnpackOptions) -> Result<Option<String>> {
+        let mut archive = SevenZReader::open(&self.archive_path, Password::empty())
+            .with_context(|| format!("Failed to read {:?}", self.archive_path))?;
+
+        let mut guard = UnpackGuard::new(dst_dir, options)?;
+
+        // for_each_entries() wants a sevenz_rust::Error, so keep our own error here
+        let mut unpack_error = None;
+        archive
+            .for_each_entries(|entry, reader| {
+                let result = match guard.entry_path(Path::new(entry.name())) {
+                    Ok(Some(dst_path)) => unpack_entry(&mut guard, entry, reader, &dst_path),
+                    // Entries share the same stream, so skipped entries must still be
+                    // decompressed. They count in the size limit like the others.
+                    Ok(None) => guard.copy(reader, &mut io::sink()).map(|_| ()),
+                    Err(err) => Err(err),
+                };
+                match result {
+                    Ok(()) => Ok(true),
+                    Err(err) => {
+                        unpack_error = Some(err);
+                        Ok(false)
+                    }
+                }
+            })
+            .with_context(|| format!("Failed to unpack {:?}", self.archive_path))?;
+
+        match unpack_error {
+            Some(err) => Err(err),
+            None => {
+                guard.finish()?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::unpacker::{UnpackFilter, UnpackLimits};
+
+    /// Creates a 7z file in `dir` containing the (name, content) `entries`
+    fn create_crafted_7z(dir: &Path, entries: &[(&str, &[u8])]) -> PathBuf {
+        let archive_path = dir.join("crafted.7z");
+        let mut writer = sevenz_rust::SevenZWriter::create(&archive_path).unwrap();
+        for (name, data) in entries {
+            let mut entry = SevenZArchiveEntry::new();
+            entry.name = name.to_string();
+            entry.has_stream = true;
+            writer.push_archive_entry(entry, Some(*data)).unwrap();
+        }
+        writer.finish().unwrap();
+        archive_path
+    }
+
+    use crate::test_file_utils::{get_fixture_path, list_tree, pathbufset_from_strings};
+
+    #[test]
+    fn unpack_should_unpack_in_the_right_dir() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN the test 7z file
+        let archive_path = get_fixture_path("test_archive.7z");
+
+        // AND an unpacker on this 7z file
+        let unpacker = SevenZUnpacker::new(&archive_path);
+
+        // WHEN unpack() is called in a subdir of `dir`
+        let dst_dir = dir.join("sub");
+        unpacker
+            .unpack(&dst_dir, &UnpackOptions::default())
+            .unwrap();
+
+        // THEN the 7z file is unpacked there
+        assert_eq!(
+            list_tree(&dst_dir).unwrap(),
+            pathbufset_from_strings(&[
+                "hello/bin/hello",
+                "hello/bin/hello-symlink",
+                "hello/README.md"
+            ])
+        );
+
+        #[cfg(unix)]
+        {
+            // AND hello/bin/hello is executable
+            use crate::test_file_utils::is_file_executable;
+            let exe_path = dst_dir.join("hello/bin/hello").canonicalize().unwrap();
+            assert!(is_file_executable(&exe_path));
+
+            // AND hello/bin/hello-symlink points to hello/bin/hello
+            let symlink_path = dst_dir.join("hello/bin/hello-symlink");
+            assert!(symlink_path.is_symlink());
+            assert_eq!(symlink_path.canonicalize().unwrap(), exe_path);
+        }
+    }
+
+    #[test]
+    fn unpack_should_honor_strip() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN a 7z file with the following content:
+        // hello/
+        // hello/bin/
+        // hello/bin/hello
+        // hello/bin/hello-symlink
+        // hello/README.md
+        let archive_path = get_fixture_path("test_archive.7z");
+
+        // AND an unpacker on this 7z file
+        let unpacker = SevenZUnpacker::new(&archive_path);
+
+        // WHEN unpack() is called with strip = 1
+        unpacker
+            .unpack(&dir, &UnpackOptions::default().with_strip(1))
+            .unwrap();
+
+        // THEN the top-level directory is not there
+        assert_eq!(
+            list_tree(&dir).unwrap(),
+            pathbufset_from_strings(&["bin/hello", "bin/hello-symlink", "README.md"])
+        );
+    }
+
+    #[test]
+    fn unpack_should_enforce_limits() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let unpacker = SevenZUnpacker::new(&get_fixture_path("test_archive.7z"));
+
+        // GIVEN limits lower than the content of the 7z file
+        let size_options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_size: 4,
+            ..UnpackLimits::default()
+        });
+        let entry_options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_entries: 2,
+            ..UnpackLimits::default()
+        });
+
+        // WHEN unpack() is called with these limits
+        // THEN it fails
+        assert!(unpacker.unpack(&dir.join("size"), &size_options).is_err());
+        assert!(unpacker
+            .unpack(&dir.join("entries"), &entry_options)
+            .is_err());
+    }
+
+    #[test]
+    fn unpack_should_reject_paths_going_outside() {
+        // GIVEN a 7z file containing a file outside of the unpack dir
+        let dir = assert_fs::TempDir::new().unwrap();
+        let archive_path = create_crafted_7z(&dir, &[("../evil", b"evil")]);
+
+        // WHEN it is unpacked
+        let dst_dir = dir.join("dst");
+        let unpacker = SevenZUnpacker::new(&archive_path);
+        let result = unpacker.unpack(&dst_dir, &UnpackOptions::default());
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!dir.join("evil").exists());
+    }
+
+    #[test]
+    fn unpack_should_count_skipped_entries_in_the_size_limit() {
+        // GIVEN a 7z file containing a large entry followed by a small one
+        let dir = assert_fs::TempDir::new().unwrap();
+        let archive_path = create_crafted_7z(&dir, &[("big", &[0u8; 100]), ("small", b"a")]);
+
+        // AND a filter skipping the large entry, with a size limit smaller than it
+        let options = UnpackOptions::default()
+            .with_filter(Some(UnpackFilter::new(&["small"])))
+            .with_limits(UnpackLimits {
+                max_size: 10,
+                ..UnpackLimits::default()
+            });
+
+        // WHEN it is unpacked
+        let unpacker = SevenZUnpacker::new(&archive_path);
+        let result = unpacker.unpack(&dir.join("dst"), &options);
+
+        // THEN it fails, because the large entry must still be decompressed
+        assert!(result.is_err());
+    }
+}
@@ -4,57 +4,60 @@
 
 use std::path::{Path, PathBuf};
 
-use crate::vendored::archiver_rs::{self, Compressed};
-use anyhow::{anyhow, Result};
+use anyhow::{Context, Result};
 
 use crate::file_utils;
-use crate::unpacker::{tar_unpacker::TarUnpacker, Unpacker};
+use crate::unpacker::archive_format::Compression;
+use crate::unpacker::unpack_guard::UnpackGuard;
+use crate::unpacker::{UnpackOptions, Unpacker};
 
 /// An "unpacker" for archives which are a single compressed file (for example foo.gz)
 pub struct SingleFileUnpacker {
     archive_path: PathBuf,
+    compression: Compression,
 }
 
 impl SingleFileUnpacker {
-    pub fn new(archive: &Path) -> SingleFileUnpacker {
+    pub fn new(archive: &Path, compression: Compression) -> SingleFileUnpacker {
         SingleFileUnpacker {
             archive_path: archive.to_path_buf(),
+            compression,
         }
     }
 
-    pub fn supports(name: &str) -> bool {
-        if TarUnpacker::supports(name) {
-            return false;
-        }
-        name.ends_with(".gz") || name.ends_with(".bz2") || name.ends_with(".xz")
+    /// Returns the name of the decompressed file: the archive name without its compression
+    /// extension. If the archive name does not end with a compression extension, returns it
+    /// unchanged.
+    fn get_unpacked_name(&self) -> Result<String> {
+        let name = file_utils::get_file_name(&self.archive_path)?;
+        let stem = self
+            .compression
+            .extensions()
+            .iter()
+            .find_map(|ext| name.strip_suffix(&format!(".{ext}")));
+        Ok(stem.unwrap_or(name).to_string())
     }
 }
 
 impl Unpacker for SingleFileUnpacker {
-    fn unpack(&self, dst_dir: &Path, _strip: u32) -> Result<Option<String>> {
-        let extension = self
-            .archive_path
-            .extension()
-            .ok_or_else(|| anyhow!("Can't get extension for {:?}", self.archive_path))?
-            .to_str()
-            .unwrap();
-        let dst_path = dst_dir.join(self.archive_path.file_stem().unwrap());
-
-        let mut compressed: Box<dyn Compressed> = match extension {
-            "gz" => Box::new(archiver_rs::Gzip::open(&self.archive_path)?),
-            "bz2" => Box::new(archiver_rs::Bzip2::open(&self.archive_path)?),
-            "xz" => Box::new(archiver_rs::Xz::open(&self.archive_path)?),
-            _ => {
-                return Err(anyhow!("Don't know how to unpack {:?}", self.archive_path));
-            }
-        };
-        compressed.decompress(&dst_path)?;
+    fn unpack(&self, dst_dir: &Path, options: &UnpackOptions) -> Result<Option<String>> {
+        let name = self.get_unpacked_name()?;
+
+        let mut reader = self
+            .compression
+            .open(&self.archive_path)
+            .with_context(|| format!("Failed to open {:?}", self.archive_path))?;
+        let mut guard = UnpackGuard::new(dst_dir, options)?;
+        let dst_path = dst_dir.join(&name);
+        let mut dst_file = guard.create_file(&dst_path)?;
+        guard
+            .copy(&mut reader, &mut dst_file)
+            .with_context(|| format!("Failed to decompress {:?}", self.archive_path))?;
 
         #[cfg(unix)]
         file_utils::set_file_executable(&dst_path)?;
 
-        let name = file_utils::get_file_name(&dst_path)?;
-        Ok(Some(name.to_string()))
+        Ok(Some(name))
     }
 }
 
@@ -62,28 +65,21 @@ impl Unpacker for SingleFileUnpacker {
 mod tests {
     use super::*;
 
-    use crate::test_file_utils::get_fixture_path;
+    use crate::unpacker::UnpackLimits;
 
-    #[test]
-    fn supports_should_not_accept_tar_compressed_files() {
-        assert!(!SingleFileUnpacker::supports("foo.tar.gz"));
-        assert!(!SingleFileUnpacker::supports("foo.tar.bz2"));
-        assert!(!SingleFileUnpacker::supports("foo.tar.xz"));
-    }
+    use crate::test_file_utils::get_fixture_path;
 
-    fn check_unpack_should_copy_file(compressed_exe_path: &Path) {
+    fn check_unpack_should_copy_file(compressed_exe_path: &Path, compression: Compression) {
         // GIVEN a compressed file
         let exe_file_name = compressed_exe_path.file_stem().unwrap();
 
         let dir = assert_fs::TempDir::new().unwrap();
 
         // AND a SingleFileUnpacker on it
-        let unpacker = SingleFileUnpacker {
-            archive_path: compressed_exe_path.to_path_buf(),
-        };
+        let unpacker = SingleFileUnpacker::new(compressed_exe_path, compression);
 
         // WHEN unpack() is called
-        unpacker.unpack(&dir, 0).unwrap();
+        unpacker.unpack(&dir, &UnpackOptions::default()).unwrap();
 
         // THEN the executable is copied there
         let dst_path = dir.join(exe_file_name);
@@ -99,8 +95,75 @@ mod tests {
 
     #[test]
     fn unpack_should_copy_file() {
-        check_unpack_should_copy_file(&get_fixture_path("test_exe.gz"));
-        check_unpack_should_copy_file(&get_fixture_path("test_exe.bz2"));
-        check_unpack_should_copy_file(&get_fixture_path("test_exe.xz"));
+        check_unpack_should_copy_file(&get_fixture_path("test_exe.gz"), Compression::Gzip);
+        check_unpack_should_copy_file(&get_fixture_path("test_exe.bz2"), Compression::Bzip2);
+        check_unpack_should_copy_file(&get_fixture_path("test_exe.xz"), Compression::Xz);
+        check_unpack_should_copy_file(&get_fixture_path("test_exe.zst"), Compression::Zstd);
+        check_unpack_should_copy_file(&get_fixture_path("test_exe.lz"), Compression::Lzip);
+    }
+
+    #[test]
+    fn unpack_should_keep_names_without_compression_extension() {
+        // GIVEN a gzip-compressed file whose name has no .gz extension
+        let dir = assert_fs::TempDir::new().unwrap();
+        let archive_path = dir.join("foo-1.2");
+        std::fs::copy(get_fixture_path("test_exe.gz"), &archive_path).unwrap();
+
+        // AND a SingleFileUnpacker on it
+        let unpacker = SingleFileUnpacker::new(&archive_path, Compression::Gzip);
+
+        // WHEN unpack() is called
+        let dst_dir = dir.join("dst");
+        let name = unpacker
+            .unpack(&dst_dir, &UnpackOptions::default())
+            .unwrap();
+
+        // THEN the unpacked file keeps the archive name
+        assert_eq!(name, Some("foo-1.2".to_string()));
+        assert!(dst_dir.join("foo-1.2").exists());
+    }
+
+    #[test]
+    fn unpack_should_enforce_the_size_limit() {
+        // GIVEN a compressed file
+        let dir = assert_fs::TempDir::new().unwrap();
+        let unpacker = SingleFileUnpacker::new(&get_fixture_path("test_exe.gz"), Compression::Gzip);
+
+        // AND a size limit smaller than its decompressed size
+        let options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_size: 8,
+            ..UnpackLimits::default()
+        });
+
+        // WHEN unpack() is called
+        let result = unpacker.unpack(&dir, &options);
+
+        // THEN it fails
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn unpack_should_stream_lzip_files() {
+        // GIVEN an lzip file with a corrupted trailer, so that it fails to decompress once all its
+        // content has been read
+        let dir = assert_fs::TempDir::new().unwrap();
+        let archive_path = dir.join("test_exe.lz");
+        let mut data = std::fs::read(get_fixture_path("test_exe.lz")).unwrap();
+        *data.last_mut().unwrap() ^= 0xff;
+        std::fs::write(&archive_path, data).unwrap();
+
+        // AND a size limit smaller than its decompressed size
+        let options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_size: 8,
+            ..UnpackLimits::default()
+        });
+
+        // WHEN unpack() is called
+        let unpacker = SingleFileUnpacker::new(&archive_path, Compression::Lzip);
+        let result = unpacker.unpack(&dir.join("dst"), &options);
+
+        // THEN it fails because of the size limit: the content is not decompressed beforehand
+        let err = result.unwrap_err();
+        assert!(format!("{err:#}").contains("limit"), "{err:#}");
     }
 }
@@ -0,0 +1,506 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! A minimal read-only implementation of the squashfs 4.0 format, enough to extract the content
+//! of AppImages.
+//!
+//! Format reference: <https://dr-emann.github.io/squashfs/squashfs.html>
+
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::fs;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::unpacker::unpack_guard::UnpackGuard;
+
+const MAGIC: &[u8] = b"hsqs";
+const SUPERBLOCK_SIZE: usize = 96;
+
+const METADATA_BLOCK_SIZE: usize = 8192;
+const METADATA_UNCOMPRESSED: u16 = 0x8000;
+const MIN_BLOCK_SIZE: u32 = 4096;
+const MAX_BLOCK_SIZE: u32 = 1024 * 1024;
+const DATA_UNCOMPRESSED: u32 = 1 << 24;
+const NO_FRAGMENT: u32 = 0xffff_ffff;
+const FRAGMENT_ENTRY_SIZE: usize = 16;
+const FRAGMENT_ENTRIES_PER_BLOCK: usize = METADATA_BLOCK_SIZE / FRAGMENT_ENTRY_SIZE;
+
+/// Directories can reference any inode, so a corrupted image can contain loops
+const MAX_DIR_DEPTH: usize = 256;
+const MAX_SYMLINK_TARGET_SIZE: usize = 4096;
+
+const INODE_HEADER_SIZE: usize = 16;
+
+const BASIC_DIR: u16 = 1;
+const BASIC_FILE: u16 = 2;
+const BASIC_SYMLINK: u16 = 3;
+const EXTENDED_DIR: u16 = 8;
+const EXTENDED_FILE: u16 = 9;
+const EXTENDED_SYMLINK: u16 = 10;
+
+#[derive(Clone, Copy, Debug)]
+enum Compressor {
+    Zlib,
+    Lzma,
+    Xz,
+    Zstd,
+}
+
+impl Compressor {
+    fn from_id(id: u16) -> Result<Compressor> {
+        match id {
+            1 => Ok(Compressor::Zlib),
+            2 => Ok(Compressor::Lzma),
+            4 => Ok(Compressor::Xz),
+            6 => Ok(Compressor::Zstd),
+            3 => Err(anyhow!("Unsupported squashfs compression: lzo")),
+            5 => Err(anyhow!("Unsupported squashfs compression: lz4")),
+            _ => Err(anyhow!("Unknown squashfs compression {id}")),
+        }
+    }
+
+    /// Decompresses a block, which cannot be bigger than `max_size` once decompressed
+    fn decompress(&self, data: &[u8], max_size: usize) -> Result<Vec<u8>> {
+        let decoder: Box<dyn Read> = match self {
+            Compressor::Zlib => Box::new(flate2::read::ZlibDecoder::new(data)),
+            Compressor::Lzma => {
+                let mut output = Vec::new();
+                let options = lzma_rs::decompress::Options {
+                    memlimit: Some(max_size),
+                    ..Default::default()
+                };
+                lzma_rs::lzma_decompress_with_options(
+                    &mut io::BufReader::new(data),
+                    &mut output,
+                    &options,
+                )
+                .map_err(|err| anyhow!("Invalid lzma data: {err}"))?;
+                Box::new(io::Cursor::new(output))
+            }
+            Compressor::Xz => Box::new(xz2::read::XzDecoder::new(data)),
+            Compressor::Zstd => Box::new(zstd::stream::read::Decoder::new(data)?),
+        };
+        let mut output = Vec::new();
+        decoder.take(max_size as u64 + 1).read_to_end(&mut output)?;
+        if output.len() > max_size {
+            return Err(anyhow!("Invalid squashfs block: too big"));
+        }
+        Ok(output)
+    }
+}
+
+struct Superblock {
+    block_size: u32,
+    fragment_count: u32,
+    compressor: Compressor,
+    root_inode_ref: u64,
+    inode_table_start: u64,
+    directory_table_start: u64,
+    fragment_table_start: u64,
+}
+
+fn u16_at(data: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes(data[pos..pos + 2].try_into().unwrap())
+}
+
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap())
+}
+
+fn u64_at(data: &[u8], pos: usize) -> u64 {
+    u64::from_le_bytes(data[pos..pos + 8].try_into().unwrap())
+}
+
+impl Superblock {
+    fn parse(data: &[u8]) -> Result<Superblock> {
+        if &data[0..4] != MAGIC {
+            return Err(anyhow!("Not a squashfs image"));
+        }
+        let version_major = u16_at(data, 28);
+        if version_major != 4 {
+            return Err(anyhow!("Unsupported squashfs version {version_major}"));
+        }
+        let block_size = u32_at(data, 12);
+        if !block_size.is_power_of_two() || !(MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&block_size)
+        {
+            return Err(anyhow!("Invalid squashfs block size {block_size}"));
+        }
+        Ok(Superblock {
+            block_size,
+            fragment_count: u32_at(data, 16),
+            compressor: Compressor::from_id(u16_at(data, 20))?,
+            root_inode_ref: u64_at(data, 32),
+            inode_table_start: u64_at(data, 64),
+            directory_table_start: u64_at(data, 72),
+            fragment_table_start: u64_at(data, 80),
+        })
+    }
+}
+
+struct FileInode {
+    blocks_start: u64,
+    file_size: u64,
+    fragment: u32,
+    fragment_offset: u32,
+    /// The list of block sizes is only read when the file is extracted, after checking its size
+    /// fits the limits
+    inode_ref: u64,
+    block_list_start: usize,
+}
+
+enum InodeKind {
+    Dir {
+        block_index: u32,
+        block_offset: u16,
+        size: u32,
+    },
+    File(FileInode),
+    Symlink(String),
+    /// Devices, fifos and sockets
+    Other,
+}
+
+struct Inode {
+    permissions: u16,
+    kind: InodeKind,
+}
+
+/// A squashfs image, stored at `offset` in `reader`
+pub struct SquashFs<R: Read + Seek> {
+    reader: R,
+    offset: u64,
+    superblock: Superblock,
+    /// Decompressed metadata blocks, indexed by their position in the image. Values are the
+    /// content of the block and the position of the next block.
+    metadata_cache: HashMap<u64, (Vec<u8>, u64)>,
+}
+
+impl SquashFs<File> {
+    pub fn open(path: &Path, offset: u64) -> Result<SquashFs<File>> {
+        let file = File::open(path).with_context(|| format!("Failed to open {path:?}"))?;
+        SquashFs::new(file, offset)
+    }
+}
+
+impl<R: Read + Seek> SquashFs<R> {
+    pub fn new(mut reader: R, offset: u64) -> Result<SquashFs<R>> {
+        let mut data = [0u8; SUPERBLOCK_SIZE];
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut data)?;
+        let superblock = Superblock::parse(&data)?;
+        Ok(SquashFs {
+            reader,
+            offset,
+            superblock,
+            metadata_cache: HashMap::new(),
+        })
+    }
+
+    fn read_at(&mut self, pos: u64, size: usize) -> Result<Vec<u8>> {
+        let mut data = vec![0u8; size];
+        self.reader.seek(SeekFrom::Start(self.offset + pos))?;
+        self.reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    /// Returns the content of the metadata block at `pos` and the position of the next one
+    fn read_metadata_block(&mut self, pos: u64) -> Result<(Vec<u8>, u64)> {
+        if let Some(entry) = self.metadata_cache.get(&pos) {
+            return Ok(entry.clone());
+        }
+        let header = u16_at(&self.read_at(pos, 2)?, 0);
+        let size = (header & !METADATA_UNCOMPRESSED) as usize;
+        let data = self.read_at(pos + 2, size)?;
+        let data = if header & METADATA_UNCOMPRESSED != 0 {
+            data
+        } else {
+            self.superblock
+                .compressor
+                .decompress(&data, METADATA_BLOCK_SIZE)?
+        };
+        let entry = (data, pos + 2 + size as u64);
+        self.metadata_cache.insert(pos, entry.clone());
+        Ok(entry)
+    }
+
+    /// Reads `size` bytes of metadata, starting at `offset` in the block at `block_pos`.
+    /// Metadata can span several blocks.
+    fn read_metadata(&mut self, block_pos: u64, offset: usize, size: usize) -> Result<Vec<u8>> {
+        // Do not trust `size` for the allocation, it comes from the image
+        let mut output = Vec::with_capacity(size.min(METADATA_BLOCK_SIZE));
+        let mut pos = block_pos;
+        let mut skip = offset;
+        while output.len() < size {
+            let (block, next_pos) = self.read_metadata_block(pos)?;
+            if block.is_empty() {
+                return Err(anyhow!("Invalid squashfs metadata block"));
+            }
+            if skip < block.len() {
+                let end = block.len().min(skip + size - output.len());
+                output.extend_from_slice(&block[skip..end]);
+                skip = 0;
+            } else {
+                skip -= block.len();
+            }
+            pos = next_pos;
+        }
+        Ok(output)
+    }
+
+    fn read_inode_data(&mut self, inode_ref: u64, size: usize) -> Result<Vec<u8>> {
+        let block_pos = self.superblock.inode_table_start + (inode_ref >> 16);
+        let offset = (inode_ref & 0xffff) as usize;
+        self.read_metadata(block_pos, offset, size)
+    }
+
+    fn read_inode(&mut self, inode_ref: u64) -> Result<Inode> {
+        let header = self.read_inode_data(inode_ref, INODE_HEADER_SIZE)?;
+        let inode_type = u16_at(&header, 0);
+        let permissions = u16_at(&header, 2);
+
+        let kind = match inode_type {
+            BASIC_DIR => {
+                let data = self.read_inode_data(inode_ref, INODE_HEADER_SIZE + 16)?;
+                InodeKind::Dir {
+                    block_index: u32_at(&data, 16),
+                    size: u16_at(&data, 24) as u32,
+                    block_offset: u16_at(&data, 26),
+                }
+            }
+            EXTENDED_DIR => {
+                let data = self.read_inode_data(inode_ref, INODE_HEADER_SIZE + 24)?;
+                InodeKind::Dir {
+                    size: u32_at(&data, 20),
+                    block_index: u32_at(&data, 24),
+                    block_offset: u16_at(&data, 34),
+                }
+            }
+            BASIC_FILE | EXTENDED_FILE => {
+                let fixed_size = if inode_type == BASIC_FILE { 16 } else { 40 };
+                let data = self.read_inode_data(inode_ref, INODE_HEADER_SIZE + fixed_size)?;
+                let block_list_start = INODE_HEADER_SIZE + fixed_size;
+                let file = if inode_type == BASIC_FILE {
+                    FileInode {
+                        blocks_start: u32_at(&data, 16) as u64,
+                        fragment: u32_at(&data, 20),
+                        fragment_offset: u32_at(&data, 24),
+                        file_size: u32_at(&data, 28) as u64,
+                        inode_ref,
+                        block_list_start,
+                    }
+                } else {
+                    FileInode {
+                        blocks_start: u64_at(&data, 16),
+                        file_size: u64_at(&data, 24),
+                        fragment: u32_at(&data, 44),
+                        fragment_offset: u32_at(&data, 48),
+                        inode_ref,
+                        block_list_start,
+                    }
+                };
+                InodeKind::File(file)
+            }
+            BASIC_SYMLINK | EXTENDED_SYMLINK => {
+                let data = self.read_inode_data(inode_ref, INODE_HEADER_SIZE + 8)?;
+                let target_size = u32_at(&data, 20) as usize;
+                if target_size > MAX_SYMLINK_TARGET_SIZE {
+                    return Err(anyhow!("Invalid squashfs symlink"));
+                }
+                let start = INODE_HEADER_SIZE + 8;
+                let data = self.read_inode_data(inode_ref, start + target_size)?;
+                InodeKind::Symlink(String::from_utf8_lossy(&data[start..]).to_string())
+            }
+            _ => InodeKind::Other,
+        };
+        Ok(Inode { permissions, kind })
+    }
+
+    /// Returns the entries of a directory, as (name, inode reference) tuples
+    fn read_dir(
+        &mut self,
+        block_index: u32,
+        block_offset: u16,
+        size: u32,
+    ) -> Result<Vec<(String, u64)>> {
+        // The stored size is 3 bytes bigger than the real one
+        let size = (size as usize).saturating_sub(3);
+        if size == 0 {
+            return Ok(vec![]);
+        }
+        let block_pos = self.superblock.directory_table_start + block_index as u64;
+        let data = self.read_metadata(block_pos, block_offset as usize, size)?;
+
+        let mut entries = Vec::new();
+        let mut pos = 0;
+        while pos + 12 <= data.len() {
+            let count = u32_at(&data, pos) as usize + 1;
+            let inode_block = u32_at(&data, pos + 4) as u64;
+            pos += 12;
+            for _ in 0..count {
+                if pos + 8 > data.len() {
+                    return Err(anyhow!("Invalid squashfs directory entry"));
+                }
+                let inode_offset = u16_at(&data, pos) as u64;
+                let name_size = u16_at(&data, pos + 6) as usize + 1;
+                let name_start = pos + 8;
+                let name = data
+                    .get(name_start..name_start + name_size)
+                    .ok_or_else(|| anyhow!("Invalid squashfs directory entry"))?;
+                entries.push((
+                    String::from_utf8_lossy(name).to_string(),
+                    (inode_block << 16) | inode_offset,
+                ));
+                pos = name_start + name_size;
+            }
+        }
+        Ok(entries)
+    }
+
+    fn read_data_block(&mut self, pos: u64, size_entry: u32) -> Result<Vec<u8>> {
+        let size = (size_entry & !DATA_UNCOMPRESSED) as usize;
+        let data = self.read_at(pos, size)?;
+        if size_entry & DATA_UNCOMPRESSED != 0 {
+            Ok(data)
+        } else {
+            self.superblock
+                .compressor
+                .decompress(&data, self.superblock.block_size as usize)
+        }
+    }
+
+    fn read_fragment_block(&mut self, index: u32) -> Result<Vec<u8>> {
+        if index >= self.superblock.fragment_count {
+            return Err(anyhow!("Invalid squashfs fragment index {index}"));
+        }
+        let index = index as usize;
+        let lookup_pos =
+            self.superblock.fragment_table_start + (index / FRAGMENT_ENTRIES_PER_BLOCK * 8) as u64;
+        let block_pos = u64_at(&self.read_at(lookup_pos, 8)?, 0);
+        let offset = (index % FRAGMENT_ENTRIES_PER_BLOCK) * FRAGMENT_ENTRY_SIZE;
+        let entry = self.read_metadata(block_pos, offset, FRAGMENT_ENTRY_SIZE)?;
+        self.read_data_block(u64_at(&entry, 0), u32_at(&entry, 8))
+    }
+
+    fn read_block_sizes(&mut self, file: &FileInode) -> Result<Vec<u32>> {
+        let block_size = self.superblock.block_size as u64;
+        let block_count = if file.fragment == NO_FRAGMENT {
+            file.file_size.div_ceil(block_size)
+        } else {
+            file.file_size / block_size
+        } as usize;
+        let start = file.block_list_start;
+        let data = self.read_inode_data(file.inode_ref, start + block_count * 4)?;
+        Ok((0..block_count)
+            .map(|idx| u32_at(&data, start + idx * 4))
+            .collect())
+    }
+
+    fn write_file<W: Write>(&mut self, file: &FileInode, out: &mut W) -> Result<()> {
+        let block_size = self.superblock.block_size as u64;
+        let mut pos = file.blocks_start;
+        let mut remaining = file.file_size;
+        for size_entry in self.read_block_sizes(file)? {
+            let length = remaining.min(block_size) as usize;
+            let stored_size = (size_entry & !DATA_UNCOMPRESSED) as u64;
+            if stored_size == 0 {
+                // Sparse block
+                out.write_all(&vec![0u8; length])?;
+            } else {
+                let data = self.read_data_block(pos, size_entry)?;
+                let data = data
+                    .get(..length)
+                    .ok_or_else(|| anyhow!("Invalid squashfs data block"))?;
+                out.write_all(data)?;
+                pos += stored_size;
+            }
+            remaining -= length as u64;
+        }
+        if file.fragment != NO_FRAGMENT && remaining > 0 {
+            let data = self.read_fragment_block(file.fragment)?;
+            let start = file.fragment_offset as usize;
+            let data = data
+                .get(start..start + remaining as usize)
+                .ok_or_else(|| anyhow!("Invalid squashfs fragment"))?;
+            out.write_all(data)?;
+        }
+        Ok(())
+    }
+
+    /// Extracts the content of the image using `guard`, ignoring the first `strip` components of
+    /// the paths
+    pub fn extract(&mut self, guard: &mut UnpackGuard) -> Result<()> {
+        let root_inode_ref = self.superblock.root_inode_ref;
+        self.extract_dir(guard, root_inode_ref, Path::new(""))
+    }
+
+    fn extract_dir(
+        &mut self,
+        guard: &mut UnpackGuard,
+        inode_ref: u64,
+        rel_path: &Path,
+    ) -> Result<()> {
+        if rel_path.components().count() > MAX_DIR_DEPTH {
+            return Err(anyhow!(
+                "Invalid squashfs image: directories are nested too deep"
+            ));
+        }
+        let inode = self.read_inode(inode_ref)?;
+        let InodeKind::Dir {
+            block_index,
+            block_offset,
+            size,
+        } = inode.kind
+        else {
+            return Err(anyhow!("Invalid squashfs directory inode"));
+        };
+        for (name, child_ref) in self.read_dir(block_index, block_offset, size)? {
+            let child_rel_path = rel_path.join(&name);
+            let dst_path = guard.entry_path(&child_rel_path)?;
+            let child = self.read_inode(child_ref)?;
+            if let InodeKind::Dir { .. } = child.kind {
+                self.extract_dir(guard, child_ref, &child_rel_path)?;
+                continue;
+            }
+            let Some(dst_path) = dst_path else {
+                continue;
+            };
+            self.extract_inode(guard, &child, &dst_path)
+                .with_context(|| format!("Failed to extract {}", child_rel_path.display()))?;
+        }
+        Ok(())
+    }
+
+    fn extract_inode(
+        &mut self,
+        guard: &mut UnpackGuard,
+        inode: &Inode,
+        dst_path: &Path,
+    ) -> Result<()> {
+        match &inode.kind {
+            InodeKind::File(file) => {
+                guard.add_size(file.file_size)?;
+                let mut out = io::BufWriter::new(guard.create_file(dst_path)?);
+                self.write_file(file, &mut out)?;
+                out.flush()?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let mode = (inode.permissions & 0o7777) as u32;
+                    fs::set_permissions(dst_path, fs::Permissions::from_mode(mode))?;
+                }
+            }
+            InodeKind::Symlink(target) => {
+                guard.create_symlink(Path::new(target), dst_path)?;
+            }
+            // Directories are handled by extract_dir(), devices, fifos and sockets have nothing
+            // to do in a package
+            InodeKind::Dir { .. } | InodeKind::Other => (),
+        }
+        Ok(())
+    }
+}
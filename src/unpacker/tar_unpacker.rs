@@ -3,82 +3,90 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::fs;
-use std::io;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
-use anyhow::{anyhow, Result};
-use tar::Archive;
+use anyhow::{anyhow, Context, Result};
+use tar::{Archive, EntryType};
 
-use crate::unpacker::unpacker_utils::apply_strip;
-use crate::unpacker::Unpacker;
-
-use crate::vendored::archiver_rs;
+use crate::unpacker::archive_format::Compression;
+use crate::unpacker::unpack_guard::UnpackGuard;
+use crate::unpacker::{UnpackOptions, Unpacker};
 
 pub struct TarUnpacker {
     pub archive_path: PathBuf,
+    compression: Compression,
 }
 
 impl TarUnpacker {
-    pub fn new(archive_path: &Path) -> TarUnpacker {
+    pub fn new(archive_path: &Path, compression: Compression) -> TarUnpacker {
         TarUnpacker {
             archive_path: archive_path.to_path_buf(),
+            compression,
         }
     }
-
-    pub fn supports(name: &str) -> bool {
-        name.ends_with(".tar.gz")
-            || name.ends_with(".tar.bz2")
-            || name.ends_with(".tar.xz")
-            || name.ends_with(".tgz")
-            || name.ends_with(".tbz2")
-    }
 }
 
-impl Unpacker for TarUnpacker {
-    fn unpack(&self, dst_dir: &Path, strip: u32) -> Result<Option<String>> {
-        let extension = self
-            .archive_path
-            .extension()
-            .ok_or_else(|| anyhow!("Can't get extension for {:?}", self.archive_path))?
-            .to_str()
-            .unwrap();
+/// Unpacks the tar archive read from `reader` using `guard`. Also used by the unpackers of formats
+/// containing a tar archive.
+pub fn unpack_tar<R: Read>(reader: R, guard: &mut UnpackGuard) -> Result<()> {
+    let mut tar_reader = Archive::new(reader);
 
-        let compressed_reader: Box<dyn io::Read> = match extension {
-            "gz" | "tgz" => Box::new(archiver_rs::Gzip::open(&self.archive_path)?),
-            "bz2" | "tbz2" => Box::new(archiver_rs::Bzip2::open(&self.archive_path)?),
-            "xz" => Box::new(archiver_rs::Xz::open(&self.archive_path)?),
-            _ => {
-                return Err(anyhow!("Don't know how to unpack {:?}", self.archive_path));
-            }
+    for entry in tar_reader.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.into_owned();
+        let path = match guard.entry_path(&name)? {
+            Some(x) => x,
+            None => continue,
         };
-
-        let mut tar_reader = Archive::new(compressed_reader);
-
-        // Code below is adapted from tar::Archive::unpack()
-
-        // Canonicalizing the dst_dir directory will prepend the path with '\\?\'
-        // on windows which will allow windows APIs to treat the path as an
-        // extended-length path with a 32,767 character limit. Otherwise all
-        // unpacked paths over 260 characters will fail on creation with a
-        // NotFound exception.
-        let dst_dir = &dst_dir.canonicalize().unwrap_or(dst_dir.to_path_buf());
-
-        fs::create_dir_all(dst_dir)?;
-
-        for entry in tar_reader.entries()? {
-            let mut entry = entry?;
-            let path = match apply_strip(&entry.path()?, strip) {
-                Some(x) => x,
-                None => continue,
-            };
-            if entry.header().entry_type() != tar::EntryType::Directory {
-                let entry_path = dst_dir.join(path);
-                let entry_dir = entry_path.parent().unwrap();
-                fs::create_dir_all(entry_dir)?;
-                entry.unpack(entry_path)?;
+        match entry.header().entry_type() {
+            // Directories are created when files are unpacked in them
+            EntryType::Directory => (),
+            EntryType::Symlink => {
+                let target = entry
+                    .link_name()?
+                    .ok_or_else(|| anyhow!("Symlink {name:?} has no target"))?;
+                guard.create_symlink(&target, &path)?;
+            }
+            EntryType::Link => {
+                let target = entry
+                    .link_name()?
+                    .ok_or_else(|| anyhow!("Hard link {name:?} has no target"))?;
+                guard.create_hard_link(&target, &path)?;
             }
+            EntryType::Regular | EntryType::Continuous | EntryType::GNUSparse => {
+                let mut file = guard.create_file(&path)?;
+                guard
+                    .copy(&mut entry, &mut file)
+                    .with_context(|| format!("Failed to unpack {name:?}"))?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let mode = entry.header().mode()? & 0o777;
+                    fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+                }
+            }
+            // Devices and fifos have nothing to do in a package
+            _ => (),
+        }
+        if guard.is_done() {
+            break;
         }
+    }
+    Ok(())
+}
 
+impl Unpacker for TarUnpacker {
+    fn unpack(&self, dst_dir: &Path, options: &UnpackOptions) -> Result<Option<String>> {
+        let compressed_reader = self
+            .compression
+            .open(&self.archive_path)
+            .with_context(|| format!("Failed to open {:?}", self.archive_path))?;
+
+        let mut guard = UnpackGuard::new(dst_dir, options)?;
+        unpack_tar(compressed_reader, &mut guard)
+            .with_context(|| format!("Failed to unpack {:?}", self.archive_path))?;
+        guard.finish()?;
         Ok(None)
     }
 }
@@ -90,25 +98,30 @@ mod tests {
     use super::*;
 
     use crate::test_file_utils::{get_fixture_path, list_tree, pathbufset_from_strings};
+    use crate::unpacker::{UnpackFilter, UnpackLimits};
 
     #[parameterized(
-        tar_gz = { "test_archive.tar.gz" },
-        tar_bz2 = { "test_archive.tar.bz2" },
-        tar_xz = { "test_archive.tar.xz" },
-        tgz = { "test_archive.tgz" },
-        tbz2 = { "test_archive.tbz2" },
+        tar = { "test_archive.tar", Compression::None },
+        tar_gz = { "test_archive.tar.gz", Compression::Gzip },
+        tar_bz2 = { "test_archive.tar.bz2", Compression::Bzip2 },
+        tar_xz = { "test_archive.tar.xz", Compression::Xz },
+        tar_zst = { "test_archive.tar.zst", Compression::Zstd },
+        tar_lz = { "test_archive.tar.lz", Compression::Lzip },
+        tgz = { "test_archive.tgz", Compression::Gzip },
+        tbz2 = { "test_archive.tbz2", Compression::Bzip2 },
+        tzst = { "test_archive.tzst", Compression::Zstd },
     )]
-    fn test_unpack(filename: &str) {
+    fn test_unpack(filename: &str, compression: Compression) {
         // GIVEN a compressed tar archive
         let path = get_fixture_path(filename);
 
         let dir = assert_fs::TempDir::new().unwrap();
 
         // AND a TarUnpacker on it
-        let unpacker = TarUnpacker::new(&path);
+        let unpacker = TarUnpacker::new(&path, compression);
 
         // WHEN unpack() is called
-        let ret = unpacker.unpack(&dir, 0).unwrap();
+        let ret = unpacker.unpack(&dir, &UnpackOptions::default()).unwrap();
         assert!(ret.is_none());
 
         // THEN the archive content is copied in dir
@@ -136,18 +149,24 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_strip_components() {
+    #[parameterized(
+        tar_gz = { "test_archive.tar.gz", Compression::Gzip },
+        tar_zst = { "test_archive.tar.zst", Compression::Zstd },
+        tar_lz = { "test_archive.tar.lz", Compression::Lzip },
+    )]
+    fn test_strip_components(filename: &str, compression: Compression) {
         // GIVEN a compressed tar archive
-        let path = get_fixture_path("test_archive.tar.gz");
+        let path = get_fixture_path(filename);
 
         let dir = assert_fs::TempDir::new().unwrap();
 
         // AND a TarUnpacker on it
-        let unpacker = TarUnpacker::new(&path);
+        let unpacker = TarUnpacker::new(&path, compression);
 
         // WHEN unpack() is called with strip = 1
-        let ret = unpacker.unpack(&dir, 1).unwrap();
+        let ret = unpacker
+            .unpack(&dir, &UnpackOptions::default().with_strip(1))
+            .unwrap();
         assert!(ret.is_none());
 
         // THEN the archive content is copied in dir, but the top-level directory is not there
@@ -156,4 +175,206 @@ mod tests {
             pathbufset_from_strings(&["bin/hello", "bin/hello-symlink", "README.md"]);
         assert_eq!(actual_files, expected_files);
     }
+
+    #[parameterized(
+        no_strip = { 0, "hello/bin/hello" },
+        strip = { 1, "bin/hello" },
+    )]
+    fn unpack_should_only_unpack_the_entries_matching_the_filter(strip: u32, source: &str) {
+        // GIVEN a compressed tar archive
+        let path = get_fixture_path("test_archive.tar.gz");
+        let dir = assert_fs::TempDir::new().unwrap();
+        let unpacker = TarUnpacker::new(&path, Compression::Gzip);
+
+        // WHEN unpack() is called with a filter only selecting the executable
+        let options = UnpackOptions::default()
+            .with_strip(strip)
+            .with_filter(Some(UnpackFilter::new(&[source])));
+        unpacker.unpack(&dir, &options).unwrap();
+
+        // THEN only the executable is unpacked
+        assert_eq!(list_tree(&dir).unwrap(), pathbufset_from_strings(&[source]));
+    }
+
+    /// Creates a tar archive in `dir`. Names and link targets are written as-is: tar::Builder
+    /// refuses to create the malicious entries we need.
+    fn create_crafted_tar(dir: &Path, entries: &[(&str, EntryType, &str, &[u8])]) -> PathBuf {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, entry_type, link_name, data) in entries {
+            let mut header = tar::Header::new_old();
+            let old_header = header.as_old_mut();
+            old_header.name[..name.len()].copy_from_slice(name.as_bytes());
+            old_header.linkname[..link_name.len()].copy_from_slice(link_name.as_bytes());
+            header.set_entry_type(*entry_type);
+            header.set_mode(0o644);
+            header.set_size(data.len() as u64);
+            header.set_cksum();
+            builder.append(&header, *data).unwrap();
+        }
+        let path = dir.join("crafted.tar");
+        fs::write(&path, builder.into_inner().unwrap()).unwrap();
+        path
+    }
+
+    #[parameterized(
+        parent = { "../evil" },
+        nested_parent = { "hello/../../evil" },
+        absolute = { "/tmp/clyde-evil" },
+    )]
+    fn unpack_should_reject_paths_going_outside(name: &str) {
+        // GIVEN a tar archive containing a file outside of the unpack dir
+        let dir = assert_fs::TempDir::new().unwrap();
+        let tar_path = create_crafted_tar(&dir, &[(name, EntryType::Regular, "", b"evil")]);
+
+        // WHEN it is unpacked
+        let dst_dir = dir.join("dst");
+        let unpacker = TarUnpacker::new(&tar_path, Compression::None);
+        let result = unpacker.unpack(&dst_dir, &UnpackOptions::default());
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!dir.join("evil").exists());
+        assert!(!Path::new("/tmp/clyde-evil").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unpack_should_reject_symlinks_going_outside() {
+        // GIVEN a tar archive containing a symlink pointing outside of the unpack dir, followed by
+        // a file written through it
+        let dir = assert_fs::TempDir::new().unwrap();
+        let tar_path = create_crafted_tar(
+            &dir,
+            &[
+                ("link", EntryType::Symlink, "..", b""),
+                ("link/evil", EntryType::Regular, "", b"evil"),
+            ],
+        );
+
+        // WHEN it is unpacked
+        let dst_dir = dir.join("dst");
+        let unpacker = TarUnpacker::new(&tar_path, Compression::None);
+        let result = unpacker.unpack(&dst_dir, &UnpackOptions::default());
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!dir.join("evil").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unpack_should_reject_files_written_through_symlink_chains() {
+        // GIVEN a tar archive containing a chain of symlinks resolving to the parent of the unpack
+        // dir, a symlink going through this chain, and a file written at the path of this symlink
+        let dir = assert_fs::TempDir::new().unwrap();
+        let tar_path = create_crafted_tar(
+            &dir,
+            &[
+                ("b", EntryType::Symlink, "a/..", b""),
+                ("a", EntryType::Symlink, "sub/up", b""),
+                ("sub/up", EntryType::Symlink, "..", b""),
+                ("x", EntryType::Symlink, "b/evil", b""),
+                ("x", EntryType::Regular, "", b"evil"),
+            ],
+        );
+
+        // WHEN it is unpacked
+        let dst_dir = dir.join("dst");
+        let unpacker = TarUnpacker::new(&tar_path, Compression::None);
+        let result = unpacker.unpack(&dst_dir, &UnpackOptions::default());
+
+        // THEN it fails
+        assert!(result.is_err());
+
+        // AND nothing is written outside the unpack dir
+        assert!(!dir.join("evil").exists());
+    }
+
+    #[test]
+    fn unpack_should_report_hard_links_to_entries_skipped_by_the_filter() {
+        // GIVEN a tar archive containing a file outside of the `bin` dir, and a hard link to it
+        // inside the `bin` dir
+        let dir = assert_fs::TempDir::new().unwrap();
+        let tar_path = create_crafted_tar(
+            &dir,
+            &[
+                ("lib/foo", EntryType::Regular, "", b"foo"),
+                ("bin/foo", EntryType::Link, "lib/foo", b""),
+            ],
+        );
+
+        // WHEN it is unpacked with a filter selecting the `bin` dir
+        let options = UnpackOptions::default().with_filter(Some(UnpackFilter::new(&["bin"])));
+        let unpacker = TarUnpacker::new(&tar_path, Compression::None);
+        let result = unpacker.unpack(&dir.join("dst"), &options);
+
+        // THEN it fails, reporting the skipped hard link
+        let err = result.unwrap_err();
+        let err = err
+            .downcast_ref::<crate::unpacker::SkippedHardLinksError>()
+            .unwrap();
+        assert_eq!(err.links.len(), 1);
+        assert!(err.links[0].ends_with("bin/foo"));
+    }
+
+    #[test]
+    fn unpack_should_reject_hard_links_going_outside() {
+        // GIVEN a tar archive containing a hard link to a file outside of the unpack dir
+        let dir = assert_fs::TempDir::new().unwrap();
+        let outside_path = dir.join("outside");
+        fs::write(&outside_path, "secret").unwrap();
+        let tar_path = create_crafted_tar(
+            &dir,
+            &[("link", EntryType::Link, outside_path.to_str().unwrap(), b"")],
+        );
+
+        // WHEN it is unpacked
+        let dst_dir = dir.join("dst");
+        let unpacker = TarUnpacker::new(&tar_path, Compression::None);
+        let result = unpacker.unpack(&dst_dir, &UnpackOptions::default());
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!dst_dir.join("link").exists());
+    }
+
+    #[test]
+    fn unpack_should_enforce_the_size_limit() {
+        // GIVEN a tar archive containing a 100 bytes file
+        let dir = assert_fs::TempDir::new().unwrap();
+        let tar_path = create_crafted_tar(&dir, &[("big", EntryType::Regular, "", &[0u8; 100])]);
+
+        // AND a size limit smaller than this
+        let options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_size: 10,
+            ..UnpackLimits::default()
+        });
+
+        // WHEN it is unpacked
+        let unpacker = TarUnpacker::new(&tar_path, Compression::None);
+        let result = unpacker.unpack(&dir.join("dst"), &options);
+
+        // THEN it fails
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn unpack_should_enforce_the_entry_limit() {
+        // GIVEN a compressed tar archive containing 5 entries
+        let dir = assert_fs::TempDir::new().unwrap();
+        let unpacker =
+            TarUnpacker::new(&get_fixture_path("test_archive.tar.gz"), Compression::Gzip);
+
+        // AND an entry limit smaller than this
+        let options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_entries: 2,
+            ..UnpackLimits::default()
+        });
+
+        // WHEN it is unpacked
+        let result = unpacker.unpack(&dir, &options);
+
+        // THEN it fails
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,559 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use thiserror::Error;
+
+use crate::unpacker::unpack_options::{normalize_path, UnpackOptions};
+use crate::unpacker::unpacker_utils::apply_strip;
+
+/// Maximum length of a symlink target, like PATH_MAX on Linux
+pub const MAX_LINK_TARGET_SIZE: u64 = 4096;
+
+/// Maximum number of symlinks followed when resolving a symlink target, like the Linux kernel
+const MAX_FOLLOWED_LINKS: u32 = 40;
+
+fn escape_error(name: &Path) -> anyhow::Error {
+    anyhow!(
+        "Refusing to unpack {}: it points outside the unpack directory",
+        name.display()
+    )
+}
+
+/// Fails if `name`, the path of an archive entry, is absolute or goes up
+fn check_relative(name: &Path) -> Result<()> {
+    for component in name.components() {
+        match component {
+            Component::Normal(_) | Component::CurDir => (),
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(escape_error(name));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returned by UnpackGuard::finish() when hard links could not be created because their target
+/// had been skipped by the filter. The archive must be unpacked again, without filter.
+#[derive(Debug, Error)]
+#[error("Hard links to entries skipped by the filter could not be created: {links:?}")]
+pub struct SkippedHardLinksError {
+    pub links: Vec<PathBuf>,
+}
+
+/// Goes through all the writes of an unpacker, to make sure a malicious or corrupted archive
+/// cannot write outside the unpack directory, create links pointing outside it, or fill the disk.
+/// Also applies the strip and the filter of the unpack options.
+pub struct UnpackGuard<'a> {
+    /// Canonicalized unpack directory
+    dst_dir: PathBuf,
+    options: &'a UnpackOptions,
+    entry_count: u64,
+    size: u64,
+    symlinks: Vec<PathBuf>,
+    /// Hard links which could not be created because their target was skipped by the filter
+    skipped_hard_links: Vec<PathBuf>,
+    /// Sources of the filter which have not been unpacked yet
+    missing_sources: HashSet<PathBuf>,
+}
+
+impl<'a> UnpackGuard<'a> {
+    /// Creates a guard to unpack in `dst_dir`. Creates `dst_dir` if it does not exist.
+    pub fn new(dst_dir: &Path, options: &'a UnpackOptions) -> Result<UnpackGuard<'a>> {
+        fs::create_dir_all(dst_dir)
+            .with_context(|| format!("Failed to create directory {dst_dir:?}"))?;
+        // Canonicalizing the dst_dir directory will prepend the path with '\\?\'
+        // on windows which will allow windows APIs to treat the path as an
+        // extended-length path with a 32,767 character limit. Otherwise all
+        // unpacked paths over 260 characters will fail on creation with a
+        // NotFound exception.
+        let dst_dir = dst_dir.canonicalize()?;
+        let missing_sources = match &options.filter {
+            Some(filter) => filter.sources().iter().cloned().collect(),
+            None => HashSet::new(),
+        };
+        Ok(UnpackGuard {
+            dst_dir,
+            options,
+            entry_count: 0,
+            size: 0,
+            symlinks: vec![],
+            skipped_hard_links: vec![],
+            missing_sources,
+        })
+    }
+
+    /// Counts a new entry named `name` in the archive, and returns where it must be unpacked, or
+    /// None if it is skipped because of the strip or the filter. Fails if `name` is absolute or
+    /// goes up.
+    pub fn entry_path(&mut self, name: &Path) -> Result<Option<PathBuf>> {
+        self.entry_count += 1;
+        if self.entry_count > self.options.limits.max_entries {
+            return Err(anyhow!(
+                "Archive contains more than {} entries",
+                self.options.limits.max_entries
+            ));
+        }
+        check_relative(name)?;
+        let Some(path) = apply_strip(name, self.options.strip) else {
+            return Ok(None);
+        };
+        if let Some(filter) = &self.options.filter {
+            if !filter.matches(&path) {
+                return Ok(None);
+            }
+        }
+        Ok(Some(self.dst_dir.join(path)))
+    }
+
+    /// Returns true if the unpack options have a filter, and all its sources have been unpacked as
+    /// files or links. Unpackers can stop reading the archive at this point. Sources which are
+    /// directories are never considered complete, since their content can come later in the
+    /// archive.
+    pub fn is_done(&self) -> bool {
+        self.options.filter.is_some() && self.missing_sources.is_empty()
+    }
+
+    /// Records that `size` more bytes are going to be unpacked
+    pub fn add_size(&mut self, size: u64) -> Result<()> {
+        self.size = self.size.saturating_add(size);
+        if self.size > self.options.limits.max_size {
+            return Err(anyhow!(
+                "Unpacked content is larger than the limit of {} bytes",
+                self.options.limits.max_size
+            ));
+        }
+        Ok(())
+    }
+
+    /// Copies `reader` to `writer`, failing as soon as the size limit is reached
+    pub fn copy<R: Read + ?Sized, W: Write + ?Sized>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<u64> {
+        let remaining = self.options.limits.max_size.saturating_sub(self.size);
+        let copied = io::copy(&mut Read::take(reader, remaining + 1), writer)?;
+        self.add_size(copied)?;
+        Ok(copied)
+    }
+
+    /// Reads the target of a symlink stored as the content of an archive entry. Fails if it is
+    /// too long to be a valid target.
+    pub fn read_link_target<R: Read + ?Sized>(&mut self, reader: &mut R) -> Result<PathBuf> {
+        let mut target = Vec::new();
+        Read::take(reader, MAX_LINK_TARGET_SIZE + 1).read_to_end(&mut target)?;
+        if target.len() as u64 > MAX_LINK_TARGET_SIZE {
+            return Err(anyhow!(
+                "Symlink target is longer than {MAX_LINK_TARGET_SIZE} bytes"
+            ));
+        }
+        self.add_size(target.len() as u64)?;
+        let target = String::from_utf8(target).context("Symlink target is not valid UTF-8")?;
+        Ok(PathBuf::from(target))
+    }
+
+    /// Creates the directory `path`
+    pub fn create_dir(&self, path: &Path) -> Result<()> {
+        fs::create_dir_all(path).with_context(|| format!("Failed to create directory {path:?}"))?;
+        self.check_inside(path)
+    }
+
+    /// Creates the file `path`, and its parent directory if needed. If `path` is an existing
+    /// symlink, the symlink is replaced: writing through it could write outside the unpack
+    /// directory.
+    pub fn create_file(&mut self, path: &Path) -> Result<File> {
+        self.create_parent_dir(path)?;
+        if path.is_symlink() {
+            fs::remove_file(path)
+                .with_context(|| format!("Failed to remove existing symlink {path:?}"))?;
+        }
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .with_context(|| format!("Failed to create file {path:?}"))?;
+        if path.is_symlink() {
+            // Another symlink appeared between the removal and the creation
+            return Err(escape_error(path));
+        }
+        self.record_unpacked(path);
+        Ok(file)
+    }
+
+    /// Creates a symlink at `path`, pointing to `target`. Fails if `target` is absolute or points
+    /// outside the unpack directory.
+    pub fn create_symlink(&mut self, target: &Path, path: &Path) -> Result<()> {
+        self.create_parent_dir(path)?;
+        self.check_link_target(target, path)?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(target, path)
+            .with_context(|| format!("Failed to create symlink {path:?}"))?;
+        #[cfg(not(unix))]
+        return Err(anyhow!(
+            "Symbolic links are not supported on this OS ({path:?})"
+        ));
+        self.symlinks.push(path.to_path_buf());
+        self.record_unpacked(path);
+        Ok(())
+    }
+
+    /// Creates a hard link at `path` to the already unpacked archive entry named `target_name`
+    pub fn create_hard_link(&mut self, target_name: &Path, path: &Path) -> Result<()> {
+        check_relative(target_name)?;
+        let target = apply_strip(target_name, self.options.strip)
+            .map(|x| self.dst_dir.join(x))
+            .ok_or_else(|| anyhow!("Hard link target {target_name:?} has been skipped"))?;
+        if self.options.filter.is_some() && !target.exists() {
+            // The target has been skipped by the filter, so the link cannot be created. finish()
+            // reports it, so that callers unpack the whole archive.
+            self.skipped_hard_links.push(path.to_path_buf());
+            return Ok(());
+        }
+        let target = target
+            .canonicalize()
+            .with_context(|| format!("Hard link target {target_name:?} does not exist"))?;
+        if !target.starts_with(&self.dst_dir) {
+            return Err(escape_error(target_name));
+        }
+        self.create_parent_dir(path)?;
+        fs::hard_link(&target, path)
+            .with_context(|| format!("Failed to create hard link {path:?}"))?;
+        self.record_unpacked(path);
+        Ok(())
+    }
+
+    /// Checks the symlinks created during the unpacking. A symlink which looked fine when it was
+    /// created can point outside the unpack directory once other symlinks it goes through have
+    /// been created, so this must be called once everything has been unpacked.
+    /// Fails with SkippedHardLinksError if hard links could not be created because of the filter.
+    pub fn finish(self) -> Result<()> {
+        for path in &self.symlinks {
+            // canonicalize() fails for dangling symlinks, these have been checked when they were
+            // created
+            if path
+                .canonicalize()
+                .is_ok_and(|x| !x.starts_with(&self.dst_dir))
+            {
+                return Err(escape_error(path));
+            }
+        }
+        if !self.skipped_hard_links.is_empty() {
+            return Err(SkippedHardLinksError {
+                links: self.skipped_hard_links,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    fn record_unpacked(&mut self, path: &Path) {
+        if let Ok(rel_path) = path.strip_prefix(&self.dst_dir) {
+            self.missing_sources.remove(&normalize_path(rel_path));
+        }
+    }
+
+    fn create_parent_dir(&self, path: &Path) -> Result<()> {
+        let parent = path
+            .parent()
+            .ok_or_else(|| anyhow!("{path:?} has no parent"))?;
+        self.create_dir(parent)
+    }
+
+    /// Fails if the real path of the existing directory `dir` is not inside the unpack directory.
+    /// This happens when it is reached through a symlink.
+    fn check_inside(&self, dir: &Path) -> Result<()> {
+        let real_dir = dir.canonicalize()?;
+        if !real_dir.starts_with(&self.dst_dir) {
+            return Err(escape_error(dir));
+        }
+        Ok(())
+    }
+
+    /// Fails if `target`, the target of a symlink created at `path`, points outside the unpack
+    /// directory. Symlinks already created on disk are followed while resolving `target`.
+    fn check_link_target(&self, target: &Path, path: &Path) -> Result<()> {
+        // create_parent_dir() has been called, so the parent exists and is inside dst_dir
+        let mut resolved = path.parent().unwrap().canonicalize()?;
+        // Components still to resolve, in reverse order
+        let mut pending: Vec<PathBuf> = target
+            .components()
+            .rev()
+            .map(|x| PathBuf::from(x.as_os_str()))
+            .collect();
+        let mut followed_links = 0;
+        while let Some(component) = pending.pop() {
+            match component.components().next() {
+                Some(Component::Normal(name)) => {
+                    resolved.push(name);
+                    if let Ok(link_target) = fs::read_link(&resolved) {
+                        followed_links += 1;
+                        if followed_links > MAX_FOLLOWED_LINKS {
+                            return Err(anyhow!("Too many levels of symlinks in {path:?}"));
+                        }
+                        resolved.pop();
+                        pending.extend(
+                            link_target
+                                .components()
+                                .rev()
+                                .map(|x| PathBuf::from(x.as_os_str())),
+                        );
+                    }
+                }
+                Some(Component::ParentDir) => {
+                    resolved.pop();
+                }
+                Some(Component::CurDir) | None => (),
+                Some(Component::RootDir | Component::Prefix(_)) => {
+                    return Err(escape_error(path));
+                }
+            }
+        }
+        if !resolved.starts_with(&self.dst_dir) {
+            return Err(escape_error(path));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::unpacker::unpack_options::{UnpackFilter, UnpackLimits};
+
+    #[test]
+    fn entry_path_should_reject_paths_going_outside() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default();
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+
+        assert!(guard.entry_path(Path::new("../evil")).is_err());
+        assert!(guard.entry_path(Path::new("foo/../../evil")).is_err());
+        assert!(guard.entry_path(Path::new("/tmp/evil")).is_err());
+    }
+
+    #[test]
+    fn entry_path_should_reject_paths_going_outside_even_if_stripped() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default().with_strip(1);
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+
+        assert!(guard.entry_path(Path::new("../evil")).is_err());
+    }
+
+    #[test]
+    fn entry_path_should_apply_strip() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default().with_strip(1);
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+
+        let dst_dir = dir.canonicalize().unwrap();
+        assert_eq!(
+            guard.entry_path(Path::new("./foo/bar")).unwrap(),
+            Some(dst_dir.join("foo/bar"))
+        );
+        assert_eq!(guard.entry_path(Path::new("foo")).unwrap(), None);
+    }
+
+    #[test]
+    fn entry_path_should_apply_the_filter_after_strip() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default()
+            .with_strip(1)
+            .with_filter(Some(UnpackFilter::new(&["bin/foo"])));
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+
+        let dst_dir = dir.canonicalize().unwrap();
+        assert_eq!(
+            guard.entry_path(Path::new("foo-1.0/bin/foo")).unwrap(),
+            Some(dst_dir.join("bin/foo"))
+        );
+        assert_eq!(
+            guard.entry_path(Path::new("foo-1.0/bin/bar")).unwrap(),
+            None
+        );
+        assert_eq!(guard.entry_path(Path::new("bin/foo")).unwrap(), None);
+    }
+
+    #[test]
+    fn is_done_should_be_true_once_all_filter_sources_are_unpacked() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default()
+            .with_filter(Some(UnpackFilter::new(&["bin/foo", "README.md"])));
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+        let dst_dir = dir.canonicalize().unwrap();
+
+        guard.create_file(&dst_dir.join("bin/foo")).unwrap();
+        assert!(!guard.is_done());
+        guard.create_file(&dst_dir.join("README.md")).unwrap();
+        assert!(guard.is_done());
+    }
+
+    #[test]
+    fn is_done_should_be_false_without_filter() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default();
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+
+        guard.create_file(&dir.join("foo")).unwrap();
+        assert!(!guard.is_done());
+    }
+
+    #[test]
+    fn entry_path_should_enforce_the_entry_limit() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_entries: 2,
+            ..UnpackLimits::default()
+        });
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+
+        assert!(guard.entry_path(Path::new("a")).is_ok());
+        assert!(guard.entry_path(Path::new("b")).is_ok());
+        assert!(guard.entry_path(Path::new("c")).is_err());
+    }
+
+    #[test]
+    fn copy_should_enforce_the_size_limit() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_size: 10,
+            ..UnpackLimits::default()
+        });
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+
+        let mut output = vec![];
+        assert_eq!(guard.copy(&mut &b"123456"[..], &mut output).unwrap(), 6);
+        assert!(guard.copy(&mut &b"123456"[..], &mut output).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn create_symlink_should_reject_targets_outside() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let options = UnpackOptions::default();
+        let mut guard = UnpackGuard::new(&dir, &options).unwrap();
+        let dst_dir = dir.canonicalize().unwrap();
+
+        // Relative targets inside the directory are OK
+        guard
+            .create_symlink(Path::new("../bin/foo"), &dst_dir.join("lib/foo"))
+            .unwrap();
+
+        assert!(guard
+            .create_symlink(Path::new("../../foo"), &dst_dir.join("lib/bar"))
+            .is_err());
+        assert!(guard
+            .create_symlink(Path::new("/etc/passwd"), &dst_dir.join("passwd"))
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn create_file_should_not_write_through_symlinks_going_outside() {
+        // GIVEN a directory containing a symlink pointing outside
+        let dir = assert_fs::TempDir::new().unwrap();
+        let outside_dir = dir.join("outside");
+        fs::create_dir(&outside_dir).unwrap();
+        let dst_dir = dir.join("dst");
+        fs::create_dir(&dst_dir).unwrap();
+        std::os::unix::fs::symlink(&outside_dir, dst_dir.join("link")).unwrap();
+
+        // WHEN a file is created through this symlink
+        let options = UnpackOptions::default();
+        let mut guard = UnpackGuard::new(&dst_dir, &options).unwrap();
+        let result = guard.create_file(&dst_dir.join("link/evil"));
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!outside_dir.join("evil").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn create_file_should_replace_existing_symlinks() {
+        // GIVEN an unpack dir containing a symlink pointing to a file outside
+        let dir = assert_fs::TempDir::new().unwrap();
+        let outside_path = dir.join("outside");
+        fs::write(&outside_path, "secret").unwrap();
+        let dst_dir = dir.join("dst");
+        fs::create_dir(&dst_dir).unwrap();
+        std::os::unix::fs::symlink(&outside_path, dst_dir.join("link")).unwrap();
+
+        // WHEN a file is created at the path of the symlink
+        let options = UnpackOptions::default();
+        let mut guard = UnpackGuard::new(&dst_dir, &options).unwrap();
+        let mut file = guard.create_file(&dst_dir.join("link")).unwrap();
+        file.write_all(b"evil").unwrap();
+
+        // THEN the symlink is replaced with a file
+        assert!(!dst_dir.join("link").is_symlink());
+        assert_eq!(fs::read_to_string(dst_dir.join("link")).unwrap(), "evil");
+
+        // AND the file outside is not modified
+        assert_eq!(fs::read_to_string(&outside_path).unwrap(), "secret");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn create_symlink_should_follow_existing_symlinks() {
+        // GIVEN an unpack dir containing a symlink pointing to its parent
+        let dir = assert_fs::TempDir::new().unwrap();
+        let dst_dir = dir.join("dst");
+        let options = UnpackOptions::default();
+        let mut guard = UnpackGuard::new(&dst_dir, &options).unwrap();
+        let dst_dir = dst_dir.canonicalize().unwrap();
+        guard
+            .create_symlink(Path::new("sub/up"), &dst_dir.join("a"))
+            .unwrap();
+        guard
+            .create_symlink(Path::new(".."), &dst_dir.join("sub/up"))
+            .unwrap();
+
+        // WHEN a symlink going through it is created
+        let result = guard.create_symlink(Path::new("a/../evil"), &dst_dir.join("x"));
+
+        // THEN it fails
+        assert!(result.is_err());
+
+        // AND symlinks going through it but staying inside are accepted
+        guard
+            .create_symlink(Path::new("a/sub"), &dst_dir.join("y"))
+            .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finish_should_reject_symlink_chains_going_outside() {
+        // GIVEN symlinks which all look fine when they are created, but where `b` ends up pointing
+        // to the parent of the unpack dir once `a` and `sub/up` have been created
+        let dir = assert_fs::TempDir::new().unwrap();
+        let dst_dir = dir.join("dst");
+        let options = UnpackOptions::default();
+        let mut guard = UnpackGuard::new(&dst_dir, &options).unwrap();
+        let dst_dir = dst_dir.canonicalize().unwrap();
+        guard
+            .create_symlink(Path::new("a/.."), &dst_dir.join("b"))
+            .unwrap();
+        guard
+            .create_symlink(Path::new("sub/up"), &dst_dir.join("a"))
+            .unwrap();
+        guard
+            .create_symlink(Path::new(".."), &dst_dir.join("sub/up"))
+            .unwrap();
+
+        // WHEN finish() is called
+        let result = guard.finish();
+
+        // THEN it fails
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,149 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+const DEFAULT_MAX_SIZE_MB: u64 = 4096;
+const DEFAULT_MAX_ENTRIES: u64 = 100_000;
+
+const MB: u64 = 1024 * 1024;
+
+/// Limits enforced when unpacking an asset, to protect against decompression bombs. The defaults
+/// can be changed using the `CLYDE_UNPACK_MAX_SIZE_MB` and `CLYDE_UNPACK_MAX_ENTRIES` environment
+/// variables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnpackLimits {
+    /// Maximum number of bytes the unpacked content can use
+    pub max_size: u64,
+    /// Maximum number of entries (files, directories and links) an archive can contain
+    pub max_entries: u64,
+}
+
+impl Default for UnpackLimits {
+    fn default() -> Self {
+        UnpackLimits {
+            max_size: DEFAULT_MAX_SIZE_MB * MB,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+}
+
+fn read_env_u64(name: &str) -> Option<u64> {
+    env::var(name).ok()?.parse().ok()
+}
+
+impl UnpackLimits {
+    pub fn from_env() -> UnpackLimits {
+        let default = UnpackLimits::default();
+        UnpackLimits {
+            max_size: read_env_u64("CLYDE_UNPACK_MAX_SIZE_MB")
+                .map(|x| x.saturating_mul(MB))
+                .unwrap_or(default.max_size),
+            max_entries: read_env_u64("CLYDE_UNPACK_MAX_ENTRIES").unwrap_or(default.max_entries),
+        }
+    }
+}
+
+/// Returns `path` without its `.` components, so that `./foo/bar` and `foo/bar/` compare equal
+pub fn normalize_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|x| *x != Component::CurDir)
+        .collect()
+}
+
+/// Selects the archive entries to unpack. Built from the sources of the `files` mapping of an
+/// install, so that files which are not going to be installed are not unpacked.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnpackFilter {
+    sources: Vec<PathBuf>,
+}
+
+impl UnpackFilter {
+    /// Creates a filter for `sources`, which are relative to the unpack directory, once `strip`
+    /// has been applied
+    pub fn new<S: AsRef<str>>(sources: &[S]) -> UnpackFilter {
+        UnpackFilter {
+            sources: sources
+                .iter()
+                .map(|x| normalize_path(Path::new(x.as_ref())))
+                .collect(),
+        }
+    }
+
+    /// Returns true if the entry at `path` must be unpacked: it is a source, it is inside a
+    /// source directory, or it is one of the parent directories of a source
+    pub fn matches(&self, path: &Path) -> bool {
+        let path = normalize_path(path);
+        self.sources
+            .iter()
+            .any(|src| path.starts_with(src) || src.starts_with(&path))
+    }
+
+    pub fn sources(&self) -> &[PathBuf] {
+        &self.sources
+    }
+}
+
+/// Tells unpackers what to unpack and how
+#[derive(Clone, Debug, Default)]
+pub struct UnpackOptions {
+    /// Number of leading components to remove from the paths of the entries
+    pub strip: u32,
+    pub limits: UnpackLimits,
+    /// If set, only unpack the entries matching this filter
+    pub filter: Option<UnpackFilter>,
+}
+
+impl UnpackOptions {
+    pub fn with_strip(mut self, strip: u32) -> Self {
+        self.strip = strip;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: UnpackLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Option<UnpackFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_should_match_sources_and_their_content() {
+        let filter = UnpackFilter::new(&["bin/foo", "./share/doc/"]);
+
+        // Sources
+        assert!(filter.matches(Path::new("bin/foo")));
+        assert!(filter.matches(Path::new("./bin/foo")));
+        assert!(filter.matches(Path::new("share/doc")));
+
+        // Content of source directories
+        assert!(filter.matches(Path::new("share/doc/foo/README.md")));
+
+        // Parent directories of sources
+        assert!(filter.matches(Path::new("bin")));
+        assert!(filter.matches(Path::new("share/")));
+
+        // Other entries
+        assert!(!filter.matches(Path::new("bin/bar")));
+        assert!(!filter.matches(Path::new("bin/foobar")));
+        assert!(!filter.matches(Path::new("lib/libfoo.so")));
+        assert!(!filter.matches(Path::new("share/man/foo.1")));
+    }
+
+    #[test]
+    fn filter_should_match_everything_if_a_source_is_the_root() {
+        let filter = UnpackFilter::new(&["."]);
+        assert!(filter.matches(Path::new("bin/foo")));
+        assert!(filter.matches(Path::new("README.md")));
+    }
+}
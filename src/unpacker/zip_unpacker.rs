@@ -3,14 +3,13 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::fs;
-use std::io;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use zip::ZipArchive;
 
-use crate::unpacker::unpacker_utils::apply_strip;
-use crate::unpacker::Unpacker;
+use crate::unpacker::unpack_guard::UnpackGuard;
+use crate::unpacker::{UnpackOptions, Unpacker};
 
 pub struct ZipUnpacker {
     archive_path: PathBuf,
@@ -22,47 +21,46 @@ impl ZipUnpacker {
             archive_path: archive.to_path_buf(),
         }
     }
+}
 
-    pub fn supports(name: &str) -> bool {
-        name.ends_with(".zip")
-    }
+#[cfg(unix)]
+fn is_symlink(mode: Option<u32>) -> bool {
+    const S_IFMT: u32 = 0o170000;
+    const S_IFLNK: u32 = 0o120000;
+    mode.is_some_and(|x| x & S_IFMT == S_IFLNK)
 }
 
 impl Unpacker for ZipUnpacker {
-    fn unpack(&self, dst_dir: &Path, strip: u32) -> Result<Option<String>> {
+    fn unpack(&self, dst_dir: &Path, options: &UnpackOptions) -> Result<Option<String>> {
         let archive_file = fs::File::open(&self.archive_path)
             .with_context(|| format!("Failed to open {:?}", self.archive_path))?;
 
         let mut archive = ZipArchive::new(archive_file)
             .with_context(|| format!("Failed to read {:?}", self.archive_path))?;
 
+        let mut guard = UnpackGuard::new(dst_dir, options)?;
         for idx in 0..archive.len() {
             let mut file = archive.by_index(idx)?;
-            let dst_sub_path = match file.enclosed_name() {
-                Some(path) => path.to_owned(),
-                None => continue,
-            };
-
-            let dst_sub_path = match apply_strip(&dst_sub_path, strip) {
+            let dst_path = match guard.entry_path(Path::new(file.name()))? {
                 Some(x) => x,
                 None => continue,
             };
 
-            let dst_path = dst_dir.join(dst_sub_path);
-
-            if (*file.name()).ends_with('/') {
-                fs::create_dir_all(&dst_path)
-                    .with_context(|| format!("Failed to create directory {dst_path:?}"))?;
+            if file.is_dir() {
+                guard.create_dir(&dst_path)?;
             } else {
-                if let Some(parent) = dst_path.parent() {
-                    if !parent.exists() {
-                        fs::create_dir_all(parent)
-                            .with_context(|| format!("Failed to create directory {parent:?}"))?;
+                #[cfg(unix)]
+                if is_symlink(file.unix_mode()) {
+                    // Symlinks are stored as files containing the link target
+                    let target = guard.read_link_target(&mut file)?;
+                    guard.create_symlink(&target, &dst_path)?;
+                    if guard.is_done() {
+                        break;
                     }
+                    continue;
                 }
-                let mut dst_file = fs::File::create(&dst_path)
-                    .with_context(|| format!("Failed to create file {dst_path:?}"))?;
-                io::copy(&mut file, &mut dst_file).with_context(|| {
+                let mut dst_file = guard.create_file(&dst_path)?;
+                guard.copy(&mut file, &mut dst_file).with_context(|| {
                     format!("Failed to write {:?} to {dst_path:?}", file.name())
                 })?;
             }
@@ -76,7 +74,11 @@ impl Unpacker for ZipUnpacker {
                     fs::set_permissions(&dst_path, fs::Permissions::from_mode(mode))?;
                 }
             }
+            if guard.is_done() {
+                break;
+            }
         }
+        guard.finish()?;
         Ok(None)
     }
 }
@@ -86,6 +88,25 @@ mod tests {
     use super::*;
 
     use crate::test_file_utils::{get_fixture_path, list_tree, pathbufset_from_strings};
+    use crate::unpacker::{UnpackFilter, UnpackLimits};
+
+    /// Creates a zip file in `dir`. Each entry is a (name, symlink target, content) tuple, with an
+    /// empty symlink target for files.
+    fn create_crafted_zip(dir: &Path, entries: &[(&str, &str, &[u8])]) -> PathBuf {
+        let zip_path = dir.join("crafted.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        for (name, target, data) in entries {
+            let options = zip::write::FileOptions::default();
+            if target.is_empty() {
+                writer.start_file(*name, options).unwrap();
+                std::io::Write::write_all(&mut writer, data).unwrap();
+            } else {
+                writer.add_symlink(*name, *target, options).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+        zip_path
+    }
 
     #[test]
     fn unpack_should_unpack_in_the_right_dir() {
@@ -99,7 +120,9 @@ mod tests {
 
         // WHEN unpack() is called in a subdir of `dir`
         let dst_dir = dir.join("sub");
-        unpacker.unpack(&dst_dir, 0).unwrap();
+        unpacker
+            .unpack(&dst_dir, &UnpackOptions::default())
+            .unwrap();
 
         // THEN the zip file is unpacked there
         assert_eq!(
@@ -124,7 +147,9 @@ mod tests {
 
         // WHEN unpack() is called in a subdir of `dir` with a strip of 1
         let dst_dir = dir.join("sub");
-        unpacker.unpack(&dst_dir, 1).unwrap();
+        unpacker
+            .unpack(&dst_dir, &UnpackOptions::default().with_strip(1))
+            .unwrap();
 
         // THEN the zip file is unpacked as expected
         assert_eq!(
@@ -149,7 +174,9 @@ mod tests {
 
         // WHEN unpack() is called in a subdir of `dir` with a strip of 2
         let dst_dir = dir.join("sub");
-        unpacker.unpack(&dst_dir, 2).unwrap();
+        unpacker
+            .unpack(&dst_dir, &UnpackOptions::default().with_strip(2))
+            .unwrap();
 
         // THEN the zip file is unpacked as expected
         assert_eq!(
@@ -157,4 +184,80 @@ mod tests {
             pathbufset_from_strings(&["hello"])
         );
     }
+
+    #[test]
+    fn unpack_should_only_unpack_the_entries_matching_the_filter() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // GIVEN the test zip file
+        let zip_path = get_fixture_path("test_archive.zip");
+        let unpacker = ZipUnpacker::new(&zip_path);
+
+        // WHEN unpack() is called with a filter selecting the `bin` directory
+        let dst_dir = dir.join("sub");
+        let options = UnpackOptions::default()
+            .with_strip(1)
+            .with_filter(Some(UnpackFilter::new(&["bin"])));
+        unpacker.unpack(&dst_dir, &options).unwrap();
+
+        // THEN only the content of the directory is unpacked
+        assert_eq!(
+            list_tree(&dst_dir).unwrap(),
+            pathbufset_from_strings(&["bin/hello"])
+        );
+    }
+
+    #[test]
+    fn unpack_should_reject_paths_going_outside() {
+        // GIVEN a zip file containing a file outside of the unpack dir
+        let dir = assert_fs::TempDir::new().unwrap();
+        let zip_path = create_crafted_zip(&dir, &[("../evil", "", b"evil")]);
+
+        // WHEN it is unpacked
+        let dst_dir = dir.join("dst");
+        let unpacker = ZipUnpacker::new(&zip_path);
+        let result = unpacker.unpack(&dst_dir, &UnpackOptions::default());
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!dir.join("evil").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unpack_should_reject_symlinks_going_outside() {
+        // GIVEN a zip file containing a symlink pointing outside of the unpack dir, followed by a
+        // file written through it
+        let dir = assert_fs::TempDir::new().unwrap();
+        let zip_path = create_crafted_zip(&dir, &[("link", "..", b""), ("link/evil", "", b"evil")]);
+
+        // WHEN it is unpacked
+        let dst_dir = dir.join("dst");
+        let unpacker = ZipUnpacker::new(&zip_path);
+        let result = unpacker.unpack(&dst_dir, &UnpackOptions::default());
+
+        // THEN it fails
+        assert!(result.is_err());
+        assert!(!dir.join("evil").exists());
+    }
+
+    #[test]
+    fn unpack_should_enforce_the_size_limit() {
+        // GIVEN a zip file containing a 100 bytes file
+        let dir = assert_fs::TempDir::new().unwrap();
+        let zip_path = create_crafted_zip(&dir, &[("big", "", &[0u8; 100])]);
+
+        // AND a size limit smaller than this
+        let options = UnpackOptions::default().with_limits(UnpackLimits {
+            max_size: 10,
+            ..UnpackLimits::default()
+        });
+
+        // WHEN it is unpacked
+        let unpacker = ZipUnpacker::new(&zip_path);
+        let result = unpacker.unpack(&dir.join("dst"), &options);
+
+        // THEN it fails
+        assert!(result.is_err());
+    }
 }
@@ -14,15 +14,29 @@ pub fn expand_var(src: &str, key: &str, value: &str) -> String {
     src.replace(&from, value)
 }
 
-/// Return a copy of `src` with all ${var} expanded
+/// Return a copy of `src` with all ${var} expanded. `${var:-default}` expands to `default` if
+/// `var` is not defined.
 pub fn expand_vars(src: &str, vars: &VarsMap) -> Result<String> {
-    let mut dst = src.to_string();
-    for (name, value) in vars.iter() {
-        dst = expand_var(&dst, name, value);
-    }
-    if dst.contains("${") {
-        return Err(anyhow!("'{dst}' contains unknown variables (original: '{src}'). It's possible Clyde needs to be updated."));
+    let mut dst = String::new();
+    let mut rest = src;
+    while let Some(start) = rest.find("${") {
+        dst.push_str(&rest[..start]);
+        let expr_and_rest = &rest[start + 2..];
+        let end = expr_and_rest
+            .find('}')
+            .ok_or_else(|| anyhow!("'{src}' contains an unterminated variable"))?;
+        let expr = &expr_and_rest[..end];
+        let (name, default) = match expr.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expr, None),
+        };
+        let value = vars.get(name).map(String::as_str).or(default).ok_or_else(|| {
+            anyhow!("'{src}' contains an unknown variable: '{name}'. It's possible Clyde needs to be updated.")
+        })?;
+        dst.push_str(value);
+        rest = &expr_and_rest[end + 1..];
     }
+    dst.push_str(rest);
     Ok(dst)
 }
 
@@ -59,4 +73,32 @@ mod tests {
         let result = expand_vars("foo${ex}", &map);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn expand_vars_should_use_default_values_for_unknown_vars() {
+        let map = VarsMap::from([("exe".into(), ".exe".into())]);
+
+        assert_eq!(
+            expand_vars("foo${exe:-.bin}", &map).unwrap(),
+            "foo.exe".to_string()
+        );
+        assert_eq!(
+            expand_vars("${dir:-bin}/foo${suffix:-}", &map).unwrap(),
+            "bin/foo".to_string()
+        );
+    }
+
+    #[test]
+    fn expand_vars_should_not_expand_values() {
+        let map = VarsMap::from([("a".into(), "${b}".into()), ("b".into(), "b".into())]);
+
+        assert_eq!(expand_vars("${a}", &map).unwrap(), "${b}".to_string());
+    }
+
+    #[test]
+    fn expand_vars_should_fail_on_unterminated_vars() {
+        let map = VarsMap::from([("exe".into(), ".exe".into())]);
+
+        assert!(expand_vars("foo${exe", &map).is_err());
+    }
 }
@@ -1,3 +1,3 @@
 pub mod src;
 
-pub use src::{Bzip2, Compressed, Gzip, Result, Xz};
+pub use src::{Bzip2, Compressed, Gzip, Lzip, Result, Xz, Zstd};
@@ -0,0 +1,209 @@
+//#[cfg(feature = "lzip")]
+pub use self::lzip::Lzip;
+
+//#[cfg(feature = "lzip")]
+#[allow(clippy::module_inception)]
+mod lzip {
+    use std::cell::RefCell;
+    use std::fs::{create_dir_all, File};
+    use std::io::{copy, Error, ErrorKind, Read};
+    use std::path::Path;
+    use std::rc::Rc;
+
+    use crc32fast::Hasher;
+    use lzma_rust::LZMAReader;
+
+    use crate::vendored::archiver_rs::{Compressed, Result};
+
+    const MAGIC: &[u8; 4] = b"LZIP";
+
+    // lzip streams always use these LZMA parameters
+    const LC: u32 = 3;
+    const LP: u32 = 0;
+    const PB: u32 = 2;
+
+    const HEADER_SIZE: usize = 6;
+
+    const TRAILER_SIZE: usize = 20;
+
+    /// The compressed input, shared between the Lzip struct, which reads the headers and trailers
+    /// of the members, and the LZMA decoder of the current member
+    struct Input<R> {
+        reader: R,
+        position: u64,
+    }
+
+    struct SharedInput<R>(Rc<RefCell<Input<R>>>);
+
+    impl<R: Read> Read for SharedInput<R> {
+        fn read(&mut self, into: &mut [u8]) -> std::io::Result<usize> {
+            let mut input = self.0.borrow_mut();
+            let count = input.reader.read(into)?;
+            input.position += count as u64;
+            Ok(count)
+        }
+    }
+
+    /// The member being decompressed
+    struct Member<R> {
+        decoder: LZMAReader<SharedInput<R>>,
+        start: u64,
+        hasher: Hasher,
+        data_size: u64,
+    }
+
+    /// Streaming decompressor for lzip files. An lzip file can contain several members, which are
+    /// decompressed one after the other.
+    pub struct Lzip<R: Read> {
+        input: Rc<RefCell<Input<R>>>,
+        member: Option<Member<R>>,
+        member_count: u64,
+        finished: bool,
+    }
+
+    impl Lzip<File> {
+        pub fn open(path: &Path) -> std::io::Result<Self> {
+            let archive = File::open(path)?;
+
+            Self::new(archive)
+        }
+    }
+
+    impl<R: Read> Lzip<R> {
+        pub fn new(r: R) -> std::io::Result<Self> {
+            let input = Input {
+                reader: r,
+                position: 0,
+            };
+            Ok(Self {
+                input: Rc::new(RefCell::new(input)),
+                member: None,
+                member_count: 0,
+                finished: false,
+            })
+        }
+
+        /// Reads the header of the next member and prepares its decoder. Returns None if there are
+        /// no more members.
+        fn start_member(&mut self) -> std::io::Result<Option<Member<R>>> {
+            let start = self.input.borrow().position;
+            let mut header = Vec::with_capacity(HEADER_SIZE);
+            SharedInput(self.input.clone())
+                .take(HEADER_SIZE as u64)
+                .read_to_end(&mut header)?;
+            if header.len() < HEADER_SIZE || &header[..4] != MAGIC {
+                if self.member_count > 0 {
+                    // Like lzip, ignore trailing data
+                    return Ok(None);
+                }
+                return Err(invalid_data("Not an lzip file"));
+            }
+            if header[4] != 1 {
+                return Err(invalid_data("Unsupported lzip version"));
+            }
+            let dict_size = decode_dict_size(header[5])?;
+
+            // The unpacked size is not stored in the header: lzip streams end with an end-of-stream
+            // marker
+            let decoder = LZMAReader::new(
+                SharedInput(self.input.clone()),
+                u64::MAX,
+                LC,
+                LP,
+                PB,
+                dict_size,
+                None,
+            )
+            .map_err(|err| invalid_data(&format!("Invalid lzip data: {err}")))?;
+            self.member_count += 1;
+            Ok(Some(Member {
+                decoder,
+                start,
+                hasher: Hasher::new(),
+                data_size: 0,
+            }))
+        }
+
+        /// Reads the trailer of `member` and checks the decompressed data matches it
+        fn finish_member(&mut self, member: Member<R>) -> std::io::Result<()> {
+            // Drop the decoder, so that it releases the input
+            let Member {
+                start,
+                hasher,
+                data_size,
+                ..
+            } = member;
+            let mut trailer = [0u8; TRAILER_SIZE];
+            SharedInput(self.input.clone())
+                .read_exact(&mut trailer)
+                .map_err(|_| invalid_data("Truncated lzip file"))?;
+
+            let crc = u32::from_le_bytes(trailer[0..4].try_into().unwrap());
+            let expected_data_size = u64::from_le_bytes(trailer[4..12].try_into().unwrap());
+            let member_size = u64::from_le_bytes(trailer[12..20].try_into().unwrap());
+
+            if data_size != expected_data_size {
+                return Err(invalid_data("lzip data size mismatch"));
+            }
+            if self.input.borrow().position - start != member_size {
+                return Err(invalid_data("Invalid lzip member size"));
+            }
+            if hasher.finalize() != crc {
+                return Err(invalid_data("lzip CRC mismatch"));
+            }
+            Ok(())
+        }
+    }
+
+    fn invalid_data(message: &str) -> Error {
+        Error::new(ErrorKind::InvalidData, message)
+    }
+
+    /// Decodes the coded dictionary size stored in the lzip header: bits 4-0 contain the base 2
+    /// logarithm of the base size, bits 7-5 the number of 1/16 of the base size to subtract from it.
+    fn decode_dict_size(coded: u8) -> std::io::Result<u32> {
+        let exponent = coded & 0x1f;
+        if !(12..=29).contains(&exponent) {
+            return Err(invalid_data("Invalid lzip dictionary size"));
+        }
+        let base_size = 1u32 << exponent;
+        let fraction = (coded >> 5) as u32;
+        Ok(base_size - (base_size / 16) * fraction)
+    }
+
+    impl<R: Read> Compressed for Lzip<R> {
+        fn decompress(&mut self, target: &Path) -> Result<()> {
+            if let Some(p) = target.parent() {
+                if !p.exists() {
+                    create_dir_all(p)?;
+                }
+            }
+
+            let mut output = File::create(target)?;
+            copy(self, &mut output)?;
+
+            Ok(())
+        }
+    }
+
+    impl<R: Read> Read for Lzip<R> {
+        fn read(&mut self, into: &mut [u8]) -> std::io::Result<usize> {
+            while !self.finished && !into.is_empty() {
+                let Some(member) = &mut self.member else {
+                    self.member = self.start_member()?;
+                    self.finished = self.member.is_none();
+                    continue;
+                };
+                let count = member.decoder.read(into)?;
+                if count > 0 {
+                    member.hasher.update(&into[..count]);
+                    member.data_size += count as u64;
+                    return Ok(count);
+                }
+                let member = self.member.take().unwrap();
+                self.finish_member(member)?;
+            }
+            Ok(0)
+        }
+    }
+}
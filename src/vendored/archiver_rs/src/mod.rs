@@ -8,14 +8,22 @@ pub use bzip2::Bzip2;
 
 pub use gzip::Gzip;
 
+pub use lzip::Lzip;
+
 pub use xz::Xz;
 
+pub use zstd::Zstd;
+
 mod bzip2;
 
 mod gzip;
 
+mod lzip;
+
 mod xz;
 
+mod zstd;
+
 #[derive(Error, Debug)]
 pub enum ArchiverError {
     #[error("{0}")]
@@ -0,0 +1,55 @@
+//#[cfg(feature = "zstd")]
+pub use self::zstd::Zstd;
+
+//#[cfg(feature = "zstd")]
+#[allow(clippy::module_inception)]
+mod zstd {
+    use std::fs::{create_dir_all, File};
+    use std::io::{copy, BufReader, Read};
+    use std::path::Path;
+
+    use zstd::stream::read::Decoder;
+
+    use crate::vendored::archiver_rs::{Compressed, Result};
+
+    pub struct Zstd<R: Read> {
+        archive: Decoder<'static, BufReader<R>>,
+    }
+
+    impl Zstd<File> {
+        pub fn open(path: &Path) -> std::io::Result<Self> {
+            let archive = File::open(path)?;
+
+            Self::new(archive)
+        }
+    }
+
+    impl<R: Read> Zstd<R> {
+        pub fn new(r: R) -> std::io::Result<Self> {
+            let archive = Decoder::new(r)?;
+
+            Ok(Self { archive })
+        }
+    }
+
+    impl<R: Read> Compressed for Zstd<R> {
+        fn decompress(&mut self, target: &Path) -> Result<()> {
+            if let Some(p) = target.parent() {
+                if !p.exists() {
+                    create_dir_all(p)?;
+                }
+            }
+
+            let mut output = File::create(target)?;
+            copy(&mut self.archive, &mut output)?;
+
+            Ok(())
+        }
+    }
+
+    impl<R: Read> Read for Zstd<R> {
+        fn read(&mut self, into: &mut [u8]) -> std::io::Result<usize> {
+            self.archive.read(into)
+        }
+    }
+}
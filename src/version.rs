@@ -0,0 +1,550 @@
+// SPDX-FileCopyrightText: 2026 Aurélien Gâteau <mail@agateau.com>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Defines how the versions of a package are parsed and ordered
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionScheme {
+    /// Semantic versioning: `1.2.3`, `1.2.3-rc.1`
+    #[default]
+    Semver,
+    /// Calendar versioning: `2024.01.15`, `24.04`, `2024-01-15`
+    Calver,
+    /// Any number of dot-separated numbers: `1.2.3.4`
+    Dotted,
+    /// Anything else: `r1234`, `b5`. Versions are compared using natural ordering
+    Opaque,
+}
+
+impl VersionScheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VersionScheme::Semver => "semver",
+            VersionScheme::Calver => "calver",
+            VersionScheme::Dotted => "dotted",
+            VersionScheme::Opaque => "opaque",
+        }
+    }
+}
+
+impl fmt::Display for VersionScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for VersionScheme {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "semver" => Ok(VersionScheme::Semver),
+            "calver" => Ok(VersionScheme::Calver),
+            "dotted" => Ok(VersionScheme::Dotted),
+            "opaque" => Ok(VersionScheme::Opaque),
+            _ => Err(anyhow!("Unknown version scheme '{s}'")),
+        }
+    }
+}
+
+/// A component of a non-semver version. Numbers sort before text.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum Part {
+    Number(u64),
+    Text(String),
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum Key {
+    Semver(semver::Version),
+    Parts(Vec<Part>),
+}
+
+/// The version of a package. Keeps the version string exactly as it was written upstream, the
+/// ordering rules depend on the version scheme of the package.
+#[derive(Clone, Debug)]
+pub struct Version {
+    text: String,
+    scheme: VersionScheme,
+    key: Key,
+}
+
+impl Version {
+    /// Create a semver version
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version::from(semver::Version::new(major, minor, patch))
+    }
+
+    /// Parse a semver version
+    pub fn parse(text: &str) -> Result<Self> {
+        Version::parse_with_scheme(text, VersionScheme::Semver)
+    }
+
+    pub fn parse_with_scheme(text: &str, scheme: VersionScheme) -> Result<Self> {
+        let key = match scheme {
+            VersionScheme::Semver => Key::Semver(semver::Version::parse(text)?),
+            VersionScheme::Calver => Key::Parts(parse_numbers(text, &['.', '-', '_'])?),
+            VersionScheme::Dotted => Key::Parts(parse_numbers(text, &['.'])?),
+            VersionScheme::Opaque => Key::Parts(parse_natural(text)?),
+        };
+        Ok(Version {
+            text: text.to_string(),
+            scheme,
+            key,
+        })
+    }
+
+    pub fn scheme(&self) -> VersionScheme {
+        self.scheme
+    }
+
+    /// Only semver versions can be pre-releases
+    pub fn is_prerelease(&self) -> bool {
+        match &self.key {
+            Key::Semver(version) => !version.pre.is_empty(),
+            Key::Parts(_) => false,
+        }
+    }
+
+    /// Returns true if the first `count` components of `prefix` are the first components of this
+    /// version. Components are compared using the ordering rules of the scheme: `1.2` is not a
+    /// prefix of `1.20`.
+    fn starts_with(&self, prefix: &Version, count: usize) -> bool {
+        match (&self.key, &prefix.key) {
+            (Key::Parts(parts), Key::Parts(prefix_parts)) => {
+                let count = count.min(prefix_parts.len());
+                parts.starts_with(&prefix_parts[..count])
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<semver::Version> for Version {
+    fn from(version: semver::Version) -> Self {
+        Version {
+            text: version.to_string(),
+            scheme: VersionScheme::Semver,
+            key: Key::Semver(version),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Version::parse(s)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Version {}
+
+impl Hash for Version {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// Parse numbers separated by any of `separators`
+fn parse_numbers(text: &str, separators: &[char]) -> Result<Vec<Part>> {
+    text.split(separators)
+        .map(|x| {
+            x.parse::<u64>()
+                .map(Part::Number)
+                .map_err(|_| anyhow!("Invalid version '{text}'"))
+        })
+        .collect()
+}
+
+/// Split `text` in sequences of digits and sequences of other characters
+fn parse_natural(text: &str) -> Result<Vec<Part>> {
+    if text.is_empty() || text.contains(char::is_whitespace) {
+        return Err(anyhow!("Invalid version '{text}'"));
+    }
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for ch in text.chars() {
+        if !current.is_empty()
+            && current.starts_with(|x: char| x.is_ascii_digit()) != ch.is_ascii_digit()
+        {
+            parts.push(create_natural_part(&current)?);
+            current.clear();
+        }
+        current.push(ch);
+    }
+    parts.push(create_natural_part(&current)?);
+    Ok(parts)
+}
+
+fn create_natural_part(text: &str) -> Result<Part> {
+    if text.starts_with(|x: char| x.is_ascii_digit()) {
+        Ok(Part::Number(text.parse()?))
+    } else {
+        Ok(Part::Text(text.to_string()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+    /// `1.2.*`
+    Wildcard,
+    /// No operator: caret for semver, prefix for the other schemes
+    Default,
+}
+
+const OPS: &[(&str, Op)] = &[
+    (">=", Op::GreaterEq),
+    ("<=", Op::LessEq),
+    ("=", Op::Exact),
+    (">", Op::Greater),
+    ("<", Op::Less),
+    ("~", Op::Tilde),
+    ("^", Op::Caret),
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Comparator {
+    op: Op,
+    version: String,
+}
+
+impl Comparator {
+    fn parse(text: &str) -> Result<Self> {
+        let (op, version) = OPS
+            .iter()
+            .find_map(|(prefix, op)| text.strip_prefix(prefix).map(|x| (*op, x.trim())))
+            .unwrap_or((Op::Default, text));
+        let (op, version) = match version.strip_suffix(".*") {
+            Some(x) if op == Op::Default => (Op::Wildcard, x),
+            _ => (op, version),
+        };
+        if version.is_empty() || version.contains(char::is_whitespace) {
+            return Err(anyhow!("Invalid version requirement '{text}'"));
+        }
+        Ok(Comparator {
+            op,
+            version: version.to_string(),
+        })
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let Ok(wanted) = Version::parse_with_scheme(&self.version, version.scheme) else {
+            return false;
+        };
+        match self.op {
+            Op::Exact => *version == wanted,
+            Op::Greater => *version > wanted,
+            Op::GreaterEq => *version >= wanted,
+            Op::Less => *version < wanted,
+            Op::LessEq => *version <= wanted,
+            // Like semver: `~1.2.3` accepts changes after the second component, `^1.2.3` after
+            // the first one
+            Op::Tilde => *version >= wanted && version.starts_with(&wanted, 2),
+            Op::Caret => *version >= wanted && version.starts_with(&wanted, 1),
+            Op::Wildcard | Op::Default => version.starts_with(&wanted, usize::MAX),
+        }
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = OPS
+            .iter()
+            .find(|(_, op)| *op == self.op)
+            .map_or("", |(prefix, _)| prefix);
+        if self.op == Op::Wildcard {
+            write!(f, "{}.*", self.version)
+        } else {
+            write!(f, "{op}{}", self.version)
+        }
+    }
+}
+
+/// A version requirement, like `1.2`, `=2024.01.15` or `>=1.2.3.4, <2`. Semver versions use
+/// the semver rules. For the other schemes, operators compare versions using the package
+/// ordering rules, and a version without operator (or followed by `.*`) matches all the versions
+/// starting with it. `~` and `^` work like their semver counterparts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionReq {
+    /// None if the requirement is not a valid semver requirement
+    semver: Option<semver::VersionReq>,
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    pub const STAR: VersionReq = VersionReq {
+        semver: Some(semver::VersionReq::STAR),
+        comparators: Vec::new(),
+    };
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if text == "*" {
+            return Ok(VersionReq::STAR);
+        }
+        let comparators = text
+            .split(',')
+            .map(|x| Comparator::parse(x.trim()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(VersionReq {
+            semver: semver::VersionReq::parse(text).ok(),
+            comparators,
+        })
+    }
+
+    /// Create a requirement matching only `version`
+    pub fn exact(version: &Version) -> Self {
+        let semver = match &version.key {
+            Key::Semver(version) => Some(semver::VersionReq {
+                comparators: vec![semver::Comparator {
+                    op: semver::Op::Exact,
+                    major: version.major,
+                    minor: Some(version.minor),
+                    patch: Some(version.patch),
+                    pre: version.pre.clone(),
+                }],
+            }),
+            Key::Parts(_) => None,
+        };
+        VersionReq {
+            semver,
+            comparators: vec![Comparator {
+                op: Op::Exact,
+                version: version.text.clone(),
+            }],
+        }
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        match &version.key {
+            Key::Semver(version) => self.semver.as_ref().is_some_and(|x| x.matches(version)),
+            Key::Parts(_) => self.comparators.iter().all(|x| x.matches(version)),
+        }
+    }
+
+    /// Like `matches()`, but also accepts pre-releases which are not explicitly mentioned in the
+    /// requirement: semver requirements never match them otherwise. Exact comparators only match
+    /// pre-releases with the same pre-release tag.
+    pub fn matches_prerelease(&self, version: &Version) -> bool {
+        if self.matches(version) {
+            return true;
+        }
+        let Key::Semver(version) = &version.key else {
+            return false;
+        };
+        if version.pre.is_empty() {
+            return false;
+        }
+        self.semver.as_ref().is_some_and(|req| {
+            req.comparators
+                .iter()
+                .all(|x| semver_comparator_matches_prerelease(x, version))
+        })
+    }
+}
+
+fn semver_comparator_matches_prerelease(
+    comparator: &semver::Comparator,
+    version: &semver::Version,
+) -> bool {
+    let full_version = match (comparator.minor, comparator.patch) {
+        (Some(minor), Some(patch)) => Some(semver::Version {
+            major: comparator.major,
+            minor,
+            patch,
+            pre: comparator.pre.clone(),
+            build: semver::BuildMetadata::EMPTY,
+        }),
+        _ => None,
+    };
+    match (comparator.op, full_version) {
+        // If an exact comparator did not match, the pre-release tags are different
+        (semver::Op::Exact, _) => comparator.matches(version),
+        // Compare using the pre-release tags: 1.0.0-rc.1 is less than 1.0.0
+        (semver::Op::Greater, Some(wanted)) => *version > wanted,
+        (semver::Op::GreaterEq, Some(wanted)) => *version >= wanted,
+        (semver::Op::Less, Some(wanted)) => *version < wanted,
+        (semver::Op::LessEq, Some(wanted)) => *version <= wanted,
+        // Range comparators: check the version without its pre-release part
+        _ => {
+            let mut version = version.clone();
+            version.pre = semver::Prerelease::EMPTY;
+            comparator.matches(&version)
+        }
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.comparators.is_empty() {
+            return f.write_str("*");
+        }
+        let comparators: Vec<String> = self.comparators.iter().map(|x| x.to_string()).collect();
+        f.write_str(&comparators.join(", "))
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        VersionReq::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use yare::parameterized;
+
+    fn parse(scheme: VersionScheme, text: &str) -> Version {
+        Version::parse_with_scheme(text, scheme).unwrap()
+    }
+
+    #[parameterized(
+        semver = { VersionScheme::Semver, "1.2.3-rc.1", "1.2.3" },
+        calver = { VersionScheme::Calver, "2023.12.31", "2024.01.15" },
+        calver_dashes = { VersionScheme::Calver, "2024-01-15", "2024-01-16" },
+        calver_no_padding = { VersionScheme::Calver, "24.4", "24.10" },
+        dotted = { VersionScheme::Dotted, "1.2.3.4", "1.2.3.10" },
+        dotted_more_components = { VersionScheme::Dotted, "1.2.3", "1.2.3.1" },
+        opaque = { VersionScheme::Opaque, "r999", "r1234" },
+    )]
+    fn versions_should_be_ordered_according_to_their_scheme(
+        scheme: VersionScheme,
+        lower: &str,
+        higher: &str,
+    ) {
+        assert!(parse(scheme, lower) < parse(scheme, higher));
+    }
+
+    #[test]
+    fn versions_should_keep_their_original_string() {
+        let version = parse(VersionScheme::Calver, "2024.01.05");
+        assert_eq!(version.to_string(), "2024.01.05");
+    }
+
+    #[test]
+    fn parse_should_reject_versions_not_matching_the_scheme() {
+        assert!(Version::parse_with_scheme("2024.01.05", VersionScheme::Semver).is_err());
+        assert!(Version::parse_with_scheme("1.2.3a", VersionScheme::Dotted).is_err());
+        assert!(Version::parse_with_scheme("", VersionScheme::Opaque).is_err());
+    }
+
+    #[parameterized(
+        star = { "*", "2024.01.15", true },
+        prefix = { "2024", "2024.01.15", true },
+        prefix_other_year = { "2024", "2025.01.15", false },
+        wildcard = { "2024.01.*", "2024.01.15", true },
+        exact = { "=2024.01.15", "2024.1.15", true },
+        exact_other = { "=2024.01.15", "2024.01.16", false },
+        range = { ">=2024.01, <2024.02", "2024.01.15", true },
+        range_outside = { ">=2024.01, <2024.02", "2024.02.01", false },
+    )]
+    fn version_req_should_match_non_semver_versions(req: &str, version: &str, expected: bool) {
+        let req = VersionReq::parse(req).unwrap();
+        let version = parse(VersionScheme::Calver, version);
+        assert_eq!(req.matches(&version), expected);
+    }
+
+    #[parameterized(
+        prefix = { "1.2", "1.2.5", true },
+        prefix_numeric = { "1.2", "1.20", false },
+        tilde = { "~1.2", "1.2.5", true },
+        tilde_numeric = { "~1.2", "1.20", false },
+        tilde_older = { "~1.2.3", "1.2.1", false },
+        tilde_other_minor = { "~1.2.3", "1.3.0", false },
+        caret = { "^1.2", "1.20", true },
+        caret_older = { "^1.2", "1.1.9", false },
+        caret_other_major = { "^1.2", "2.0", false },
+    )]
+    fn version_req_should_compare_non_semver_components_numerically(
+        req: &str,
+        version: &str,
+        expected: bool,
+    ) {
+        let req = VersionReq::parse(req).unwrap();
+        let version = parse(VersionScheme::Dotted, version);
+        assert_eq!(req.matches(&version), expected);
+    }
+
+    #[test]
+    fn version_req_should_follow_semver_rules_for_semver_versions() {
+        let req = VersionReq::parse("1.2").unwrap();
+        assert!(req.matches(&Version::new(1, 3, 0)));
+        assert!(!req.matches(&Version::new(2, 0, 0)));
+    }
+
+    #[parameterized(
+        star = { "*", "1.0.0-rc.1", true },
+        tilde = { "~1.0", "1.0.5-beta", true },
+        caret = { "1.1", "1.2.0-rc.1", true },
+        caret_other_major = { "1", "2.0.0-rc.1", false },
+        exact = { "=1.0.0", "1.0.0-rc.1", false },
+        exact_same_tag = { "=1.0.0-rc.1", "1.0.0-rc.1", true },
+        exact_other_tag = { "=1.0.0-rc.1", "1.0.0-rc.2", false },
+        greater_eq = { ">=1.0.0", "1.0.0-rc.1", false },
+        less = { "<1.0.0", "1.0.0-rc.1", true },
+        range = { ">=1.0.0, <2.0.0", "1.5.0-rc.1", true },
+    )]
+    fn matches_prerelease_should_only_use_the_release_for_range_comparators(
+        req: &str,
+        version: &str,
+        expected: bool,
+    ) {
+        let req = VersionReq::parse(req).unwrap();
+        let version = Version::parse(version).unwrap();
+        assert_eq!(req.matches_prerelease(&version), expected);
+    }
+
+    #[test]
+    fn exact_version_req_should_keep_the_version_string() {
+        let version = parse(VersionScheme::Opaque, "r0042");
+        let req = VersionReq::exact(&version);
+        assert_eq!(req.to_string(), "=r0042");
+        assert_eq!(VersionReq::parse(&req.to_string()).unwrap(), req);
+        assert!(req.matches(&version));
+    }
+}